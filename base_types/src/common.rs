@@ -77,9 +77,9 @@ pub trait ConstraintTraits:
 
 pub type Uid = u128;
 
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub struct StrUid(String);
+/// A [`Uid`] written as a canonical UUID string, for JSON consumers which can't hold a u128
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StrUid(Uid);
 pub fn u128_to_string(id: Uid) -> String {
     uuid::Uuid::from_u128(id).to_string()
 }
@@ -87,19 +87,59 @@ pub fn string_to_u128(id: String) -> Uid {
     id.parse::<Uuid>().unwrap().as_u128()
 }
 
-impl From<String> for StrUid {
-    fn from(value: String) -> Self {
-        StrUid(value)
+/// A string which is neither a UUID nor a decimal [`Uid`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidUid(pub String);
+
+impl std::fmt::Display for InvalidUid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' is not a UUID", self.0)
+    }
+}
+
+// Decimal strings, as used for the keys of numeric maps, are accepted as well. They are read as
+// decimal even where they would also pass as a UUID without hyphens
+impl TryFrom<String> for StrUid {
+    type Error = InvalidUid;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let parsed = match value.bytes().all(|byte| byte.is_ascii_digit()) {
+            true => value.parse::<Uid>().ok(),
+            false => value.parse::<Uuid>().ok().map(|uuid| uuid.as_u128()),
+        };
+        parsed.map(StrUid).ok_or(InvalidUid(value))
     }
 }
 impl From<Uid> for StrUid {
     fn from(value: Uid) -> Self {
-        uuid::Uuid::from_u128(value).to_string().into()
+        StrUid(value)
     }
 }
 impl From<StrUid> for Uid {
     fn from(value: StrUid) -> Self {
-        value.0.parse::<Uuid>().unwrap().as_u128()
+        value.0
+    }
+}
+impl std::fmt::Display for StrUid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Uuid::from_u128(self.0))
+    }
+}
+#[cfg(feature = "serde")]
+impl serde::Serialize for StrUid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for StrUid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        StrUid::try_from(String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
     }
 }
 
@@ -148,3 +188,69 @@ impl FuzzyEdgeDescriptor {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(value: &str) -> Result<Uid, InvalidUid> {
+        StrUid::try_from(value.to_string()).map(Uid::from)
+    }
+
+    #[test]
+    fn uids_round_trip_through_uuid_strings() {
+        assert_eq!(
+            StrUid::from(1).to_string(),
+            "00000000-0000-0000-0000-000000000001"
+        );
+        for id in [0, 1, 290342014698977480850629738624973773045, Uid::MAX] {
+            assert_eq!(parse(&StrUid::from(id).to_string()), Ok(id));
+            assert_eq!(StrUid::from(id).to_string(), u128_to_string(id));
+        }
+        assert_eq!(
+            parse("DA6DD688-A044-5B91-9E1A-4AB419EF98F5"),
+            Ok(290342014698977480850629738624973773045)
+        );
+    }
+
+    #[test]
+    fn legacy_decimal_strings_are_accepted() {
+        assert_eq!(parse("0"), Ok(0));
+        assert_eq!(parse(&Uid::MAX.to_string()), Ok(Uid::MAX));
+        // Also a valid UUID without hyphens, but written as a decimal Uid
+        assert_eq!(
+            parse("12345678901234567890123456789012"),
+            Ok(12345678901234567890123456789012)
+        );
+    }
+
+    #[test]
+    fn malformed_strings_are_rejected() {
+        for value in [
+            "",
+            "-1",
+            "1.5",
+            " 1",
+            "340282366920938463463374607431768211456",
+            "00000000-0000-0000-0000-00000000000g",
+            "00000000-0000-0000-0000-0000000000001",
+            "not a uuid",
+        ] {
+            assert_eq!(parse(value), Err(InvalidUid(value.to_string())));
+        }
+        assert_eq!(InvalidUid("x".to_string()).to_string(), "'x' is not a UUID");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn uids_are_serialized_as_uuid_strings() {
+        assert_eq!(
+            serde_json::to_string(&StrUid::from(1)).unwrap(),
+            r#""00000000-0000-0000-0000-000000000001""#
+        );
+        let read: StrUid = serde_json::from_str(r#""42""#).unwrap();
+        assert_eq!(Uid::from(read), 42);
+        let error = serde_json::from_str::<StrUid>(r#""42x""#).unwrap_err();
+        assert!(error.to_string().contains("'42x' is not a UUID"));
+    }
+}
@@ -92,6 +92,30 @@ pub struct TraitMethodDef<TTypes: ConstraintTraits> {
 pub struct FieldConstraint<TTypes: ConstraintTraits> {
     pub tag: Tag,
     pub value_type: TTypes,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub index: FieldIndex,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    strum_macros::EnumString,
+    strum_macros::EnumIter,
+    strum_macros::Display,
+)]
+pub enum FieldIndex {
+    #[default]
+    Unindexed,
+    // Values can be looked up without scanning the graph
+    Indexed,
+    // Values can be looked up without scanning the graph, and no two instances may share a value
+    Unique,
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -7,6 +7,8 @@ pub mod locked_field_digest;
 pub mod operative_digest;
 pub mod post_generation;
 pub mod primitives;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod to_token_impls;
 pub mod trait_impl_digest;
 pub mod utils;
@@ -0,0 +1,621 @@
+//! Moves a fragment of one graph into another as a self-contained [`SubgraphBundle`].
+//!
+//! Exporting collects a root instance and everything reachable from it through outgoing edges,
+//! optionally limited to a depth and to a set of slots. Edges to instances outside of the bundle
+//! are kept as [`BundleReference`]s, along with the values of the referenced instance's unique
+//! fields.
+//!
+//! Importing gives every bundled instance a new id and creates them all in a single transaction.
+//! References are resolved against the receiving graph, first by id and then by the values of
+//! their unique fields, and the import fails if any of them can't be found. As with
+//! [`crate::post_generation::duplicate`], the new instances are named by their id in the bundle in
+//! the resulting [`crate::post_generation::ExecutionResult`]'s temp ids.
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{StrUid, Uid},
+    constraint_schema::{ConstraintSchema, FieldIndex},
+    post_generation::{
+        duplicate::{CopyEdge, CopyEnd, DuplicatePlan, InstanceCopy},
+        journal::from_json_in_either_format,
+        ElementCreationError, IdFormat, StandaloneRGSOConcrete, StrStandaloneRGSOConcrete,
+    },
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+/// Which part of the graph to export
+#[derive(Debug, Clone, Default)]
+pub struct BundleOptions {
+    /// How many edges away from the root to go, unlimited if `None`
+    pub max_depth: Option<usize>,
+    /// Only follow these slots, or every slot if `None`
+    pub slots: Option<Vec<Uid>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubgraphBundle {
+    pub root: Uid,
+    /// Root first. Only edges between bundled instances and edges to references are listed
+    pub instances: Vec<StandaloneRGSOConcrete>,
+    pub references: Vec<BundleReference>,
+}
+
+/// An instance outside of the bundle which bundled instances hold in their slots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleReference {
+    pub id: Uid,
+    pub operative: Uid,
+    /// Values of the instance's unique fields, to find it in a graph where it has another id
+    pub unique_fields: Vec<(Uid, PrimitiveValues)>,
+}
+
+/// [`SubgraphBundle`] with its ids as UUID strings, see [`IdFormat::Uuid`]
+#[derive(Serialize, Deserialize)]
+struct StrSubgraphBundle {
+    root: StrUid,
+    instances: Vec<StrStandaloneRGSOConcrete>,
+    references: Vec<StrBundleReference>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StrBundleReference {
+    id: StrUid,
+    operative: StrUid,
+    unique_fields: Vec<(StrUid, PrimitiveValues)>,
+}
+
+impl From<SubgraphBundle> for StrSubgraphBundle {
+    fn from(value: SubgraphBundle) -> Self {
+        Self {
+            root: value.root.into(),
+            instances: value.instances.into_iter().map(Into::into).collect(),
+            references: value
+                .references
+                .into_iter()
+                .map(|reference| StrBundleReference {
+                    id: reference.id.into(),
+                    operative: reference.operative.into(),
+                    unique_fields: reference
+                        .unique_fields
+                        .into_iter()
+                        .map(|(field_id, value)| (field_id.into(), value))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+impl From<StrSubgraphBundle> for SubgraphBundle {
+    fn from(value: StrSubgraphBundle) -> Self {
+        Self {
+            root: value.root.into(),
+            instances: value.instances.into_iter().map(Into::into).collect(),
+            references: value
+                .references
+                .into_iter()
+                .map(|reference| BundleReference {
+                    id: reference.id.into(),
+                    operative: reference.operative.into(),
+                    unique_fields: reference
+                        .unique_fields
+                        .into_iter()
+                        .map(|(field_id, value)| (field_id.into(), value))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BundleError {
+    /// Neither an instance with the reference's id nor one with its unique values exists
+    UnresolvedReference {
+        id: Uid,
+        operative: Uid,
+    },
+    /// The bundle lists edges to or from instances which are neither bundled nor referenced
+    Malformed(String),
+    Invalid(ElementCreationError),
+}
+
+impl Display for BundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BundleError::UnresolvedReference { id, operative } => write!(
+                f,
+                "referenced instance {} (operative {}) doesn't exist and can't be found by its unique fields",
+                id, operative
+            ),
+            BundleError::Malformed(message) => write!(f, "malformed bundle: {}", message),
+            BundleError::Invalid(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<ElementCreationError> for BundleError {
+    fn from(value: ElementCreationError) -> Self {
+        BundleError::Invalid(value)
+    }
+}
+
+impl SubgraphBundle {
+    /// The bundle as JSON with ids in the given format. The `Serialize` implementation writes them
+    /// as numbers
+    pub fn to_json(&self, format: IdFormat) -> serde_json::Result<String> {
+        match format {
+            IdFormat::Numeric => serde_json::to_string(self),
+            IdFormat::Uuid => serde_json::to_string(&StrSubgraphBundle::from(self.clone())),
+        }
+    }
+    /// Reads a bundle written in either [`IdFormat`]
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        from_json_in_either_format(json, StrSubgraphBundle::into)
+    }
+}
+
+/// Collects the bundle for `root_id`, reading instances through `get`
+pub fn export_bundle(
+    constraint_schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    get: impl Fn(&Uid) -> Option<StandaloneRGSOConcrete>,
+    root_id: &Uid,
+    options: &BundleOptions,
+) -> Result<SubgraphBundle, BundleError> {
+    let follows = |slot_id: &Uid| {
+        options
+            .slots
+            .as_ref()
+            .is_none_or(|slots| slots.contains(slot_id))
+    };
+    let root = get(root_id).ok_or(ElementCreationError::NonexistentInstance { id: *root_id })?;
+    let mut bundled = HashSet::from([*root_id]);
+    // Each instance along with its distance from the root
+    let mut instances = vec![(root, 0)];
+    let mut next = 0;
+    while let Some((instance, depth)) = instances.get(next) {
+        let depth = *depth;
+        if options.max_depth.is_none_or(|max_depth| depth < max_depth) {
+            let reached = instance
+                .outgoing_slots
+                .iter()
+                .filter(|slot_ref| slot_ref.host_instance_id == instance.id)
+                .filter(|slot_ref| follows(&slot_ref.slot_id))
+                .map(|slot_ref| slot_ref.target_instance_id)
+                .filter(|target_id| bundled.insert(*target_id))
+                .collect::<Vec<_>>();
+            for target_id in reached {
+                let target = get(&target_id)
+                    .ok_or(ElementCreationError::NonexistentInstance { id: target_id })?;
+                instances.push((target, depth + 1));
+            }
+        }
+        next += 1;
+    }
+
+    let mut referenced = Vec::new();
+    let instances = instances
+        .into_iter()
+        .map(|(mut instance, _)| {
+            let id = instance.id;
+            instance
+                .outgoing_slots
+                .retain(|slot_ref| slot_ref.host_instance_id == id);
+            instance
+                .incoming_slots
+                .retain(|slot_ref| bundled.contains(&slot_ref.host_instance_id));
+            instance
+                .outgoing_slots
+                .iter()
+                .map(|slot_ref| slot_ref.target_instance_id)
+                .filter(|target_id| !bundled.contains(target_id))
+                .for_each(|target_id| {
+                    if !referenced.contains(&target_id) {
+                        referenced.push(target_id);
+                    }
+                });
+            instance
+        })
+        .collect::<Vec<_>>();
+    let references = referenced
+        .into_iter()
+        .map(|id| {
+            let instance = get(&id).ok_or(ElementCreationError::NonexistentInstance { id })?;
+            Ok(BundleReference {
+                id,
+                operative: instance.operative,
+                unique_fields: unique_fields(constraint_schema, &instance),
+            })
+        })
+        .collect::<Result<Vec<_>, BundleError>>()?;
+    Ok(SubgraphBundle {
+        root: *root_id,
+        instances,
+        references,
+    })
+}
+
+fn unique_fields(
+    constraint_schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    instance: &StandaloneRGSOConcrete,
+) -> Vec<(Uid, PrimitiveValues)> {
+    let Some(template) = constraint_schema.template_library.get(&instance.template) else {
+        return Vec::new();
+    };
+    let mut unique_fields = instance
+        .fields
+        .iter()
+        .filter(|(field_id, _)| {
+            template
+                .field_constraints
+                .get(field_id)
+                .is_some_and(|field| field.index == FieldIndex::Unique)
+        })
+        .map(|(field_id, value)| (*field_id, value.clone()))
+        .collect::<Vec<_>>();
+    unique_fields.sort_by_key(|(field_id, _)| *field_id);
+    unique_fields
+}
+
+/// Resolves the bundle's references against the receiving graph and works out the instances and
+/// edges to create. `find_by_field` finds the instances holding a value in a field
+pub fn plan_bundle_import(
+    constraint_schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    bundle: &SubgraphBundle,
+    get: impl Fn(&Uid) -> Option<StandaloneRGSOConcrete>,
+    find_by_field: impl Fn(&Uid, &PrimitiveValues) -> Vec<StandaloneRGSOConcrete>,
+) -> Result<DuplicatePlan, BundleError> {
+    let mut resolved = HashMap::new();
+    for reference in &bundle.references {
+        let by_id = get(&reference.id)
+            .filter(|instance| instance.operative == reference.operative)
+            .map(|instance| instance.id);
+        let by_unique_fields = || {
+            reference
+                .unique_fields
+                .iter()
+                .find_map(|(field_id, value)| {
+                    find_by_field(field_id, value)
+                        .into_iter()
+                        .find(|instance| instance.operative == reference.operative)
+                        .map(|instance| instance.id)
+                })
+        };
+        let id = by_id
+            .or_else(by_unique_fields)
+            .ok_or(BundleError::UnresolvedReference {
+                id: reference.id,
+                operative: reference.operative,
+            })?;
+        resolved.insert(reference.id, id);
+    }
+
+    let bundled = bundle
+        .instances
+        .iter()
+        .map(|instance| instance.id)
+        .collect::<HashSet<_>>();
+    if !bundled.contains(&bundle.root) {
+        return Err(BundleError::Malformed(format!(
+            "root {} isn't one of the instances",
+            bundle.root
+        )));
+    }
+    let end = |id: Uid| match (bundled.contains(&id), resolved.get(&id)) {
+        (true, _) => Ok(CopyEnd::Copy(id)),
+        (false, Some(resolved_id)) => Ok(CopyEnd::Existing(*resolved_id)),
+        (false, None) => Err(BundleError::Malformed(format!(
+            "{} is neither bundled nor referenced",
+            id
+        ))),
+    };
+
+    let mut copies = Vec::new();
+    let mut edges = Vec::new();
+    for instance in &bundle.instances {
+        let copy = InstanceCopy::of(constraint_schema, instance)?;
+        // The root is created first, as the other instances are built into its builder
+        match instance.id == bundle.root {
+            true => copies.insert(0, copy),
+            false => copies.push(copy),
+        }
+        for slot_ref in instance
+            .outgoing_slots
+            .iter()
+            .filter(|slot_ref| slot_ref.host_instance_id == instance.id)
+        {
+            edges.push(CopyEdge {
+                host: CopyEnd::Copy(instance.id),
+                slot_id: slot_ref.slot_id,
+                target: end(slot_ref.target_instance_id)?,
+            });
+        }
+    }
+    Ok(DuplicatePlan { copies, edges })
+}
+
+#[cfg(feature = "reactive")]
+pub use reactive_bundle::import_bundle_into_graph;
+
+#[cfg(feature = "reactive")]
+mod reactive_bundle {
+    use super::*;
+    use crate::post_generation::{
+        duplicate::execute_plan_in_graph,
+        reactive::{hidden::EditRGSO, BuildByOperative, RBaseGraphEnvironment, RGraphEnvironment},
+        ExecutionResult,
+    };
+
+    /// Imports into a reactive graph as a single undo step
+    pub fn import_bundle_into_graph<TSchema>(
+        graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<TSchema>>>,
+        bundle: &SubgraphBundle,
+    ) -> Result<ExecutionResult, BundleError>
+    where
+        TSchema: BuildByOperative + EditRGSO + Clone + Into<StandaloneRGSOConcrete> + Send + Sync,
+    {
+        let graph = graph.into();
+        let plan = plan_bundle_import(
+            graph.constraint_schema,
+            bundle,
+            |id| graph.get(id).map(Into::into),
+            |field_id, value| {
+                graph
+                    .find_by_field(field_id, value)
+                    .into_iter()
+                    .map(Into::into)
+                    .collect()
+            },
+        )?;
+        Ok(execute_plan_in_graph(&graph, &plan, "Import bundle")?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use super::*;
+    use crate::{
+        post_generation::{
+            duplicate::temp_id,
+            plain::{PlainGraphEnvironment, Transaction},
+        },
+        testing::{
+            LINKS_SLOT_ID, NAME_FIELD_ID, NODE_OPERATIVE_ID, NODE_TEMPLATE_ID, PAIR_OPERATIVE_ID,
+            PARTNER_SLOT_ID, TEST_SCHEMA,
+        },
+    };
+
+    // The test schema with unique node names, so that nodes can be found in another graph
+    static UNIQUE_NAMES: LazyLock<ConstraintSchema<PrimitiveTypes, PrimitiveValues>> =
+        LazyLock::new(|| {
+            let mut schema = TEST_SCHEMA.clone();
+            schema
+                .template_library
+                .get_mut(&NODE_TEMPLATE_ID)
+                .and_then(|template| template.field_constraints.get_mut(&NAME_FIELD_ID))
+                .unwrap()
+                .index = FieldIndex::Unique;
+            schema
+        });
+
+    struct Fixture {
+        graph: PlainGraphEnvironment,
+        a: Uid,
+        b: Uid,
+        c: Uid,
+        pair: Uid,
+    }
+
+    fn create_nodes<const N: usize>(transaction: &mut Transaction, names: [&str; N]) -> [Uid; N] {
+        names.map(|name| {
+            let id = transaction.create(NODE_OPERATIVE_ID);
+            transaction.set_field(id, NAME_FIELD_ID, PrimitiveValues::String(name.to_string()));
+            id
+        })
+    }
+    // The pair is partnered with a, which links to b and c
+    fn fixture() -> Fixture {
+        let mut graph = PlainGraphEnvironment::new(&UNIQUE_NAMES);
+        let mut transaction = Transaction::new();
+        let [a, b, c] = create_nodes(&mut transaction, ["a", "b", "c"]);
+        let pair = transaction.create(PAIR_OPERATIVE_ID);
+        transaction
+            .add_edge(pair, PARTNER_SLOT_ID, a)
+            .add_edge(a, LINKS_SLOT_ID, b)
+            .add_edge(a, LINKS_SLOT_ID, c);
+        graph.execute(transaction).unwrap();
+        Fixture {
+            graph,
+            a,
+            b,
+            c,
+            pair,
+        }
+    }
+    // The pair and a, referring to b and c
+    fn bundle(fixture: &Fixture) -> SubgraphBundle {
+        let options = BundleOptions {
+            max_depth: Some(1),
+            slots: None,
+        };
+        fixture
+            .graph
+            .export_bundle(&fixture.pair, &options)
+            .unwrap()
+    }
+    fn plan(
+        graph: &PlainGraphEnvironment,
+        bundle: &SubgraphBundle,
+    ) -> Result<DuplicatePlan, BundleError> {
+        plan_bundle_import(
+            &UNIQUE_NAMES,
+            bundle,
+            |id| graph.get(id).cloned(),
+            |field_id, value| {
+                graph
+                    .find_by_field(field_id, value)
+                    .into_iter()
+                    .cloned()
+                    .collect()
+            },
+        )
+    }
+    fn ids(bundle: &SubgraphBundle) -> Vec<Uid> {
+        bundle
+            .instances
+            .iter()
+            .map(|instance| instance.id)
+            .collect()
+    }
+    fn reference(id: Uid, name: &str) -> BundleReference {
+        BundleReference {
+            id,
+            operative: NODE_OPERATIVE_ID,
+            unique_fields: vec![(NAME_FIELD_ID, PrimitiveValues::String(name.to_string()))],
+        }
+    }
+    fn targets(graph: &PlainGraphEnvironment, host_id: &Uid, slot_id: &Uid) -> Vec<Uid> {
+        graph
+            .get(host_id)
+            .unwrap()
+            .outgoing_slots
+            .iter()
+            .filter(|slot_ref| slot_ref.slot_id == *slot_id)
+            .map(|slot_ref| slot_ref.target_instance_id)
+            .collect()
+    }
+
+    #[test]
+    fn export_follows_outgoing_edges_within_the_options() {
+        let fixture = fixture();
+        let Fixture { a, b, c, pair, .. } = fixture;
+
+        let everything = fixture
+            .graph
+            .export_bundle(&pair, &BundleOptions::default())
+            .unwrap();
+        assert_eq!(ids(&everything), vec![pair, a, b, c]);
+        assert!(everything.references.is_empty());
+
+        let bundle = bundle(&fixture);
+        assert_eq!(bundle.root, pair);
+        assert_eq!(ids(&bundle), vec![pair, a]);
+        assert_eq!(
+            bundle.references,
+            vec![reference(b, "b"), reference(c, "c")]
+        );
+
+        let options = BundleOptions {
+            max_depth: None,
+            slots: Some(vec![LINKS_SLOT_ID]),
+        };
+        let only_links = fixture.graph.export_bundle(&pair, &options).unwrap();
+        assert_eq!(ids(&only_links), vec![pair]);
+        assert_eq!(only_links.references, vec![reference(a, "a")]);
+    }
+
+    #[test]
+    fn bundles_read_back_the_same_in_either_id_format() {
+        let fixture = fixture();
+        let bundle = bundle(&fixture);
+        let expected_plan = plan(&fixture.graph, &bundle).unwrap();
+        assert_eq!(
+            expected_plan.edges,
+            vec![
+                CopyEdge {
+                    host: CopyEnd::Copy(fixture.pair),
+                    slot_id: PARTNER_SLOT_ID,
+                    target: CopyEnd::Copy(fixture.a),
+                },
+                CopyEdge {
+                    host: CopyEnd::Copy(fixture.a),
+                    slot_id: LINKS_SLOT_ID,
+                    target: CopyEnd::Existing(fixture.b),
+                },
+                CopyEdge {
+                    host: CopyEnd::Copy(fixture.a),
+                    slot_id: LINKS_SLOT_ID,
+                    target: CopyEnd::Existing(fixture.c),
+                },
+            ]
+        );
+
+        for format in [IdFormat::Numeric, IdFormat::Uuid] {
+            let json = bundle.to_json(format).unwrap();
+            let root = uuid::Uuid::from_u128(fixture.pair).to_string();
+            assert_eq!(json.contains(&root), format == IdFormat::Uuid, "{}", json);
+
+            let read = SubgraphBundle::from_json(&json).unwrap();
+            assert_eq!(read.root, bundle.root);
+            assert_eq!(ids(&read), ids(&bundle));
+            assert_eq!(read.references, bundle.references);
+            for (read, written) in read.instances.iter().zip(&bundle.instances) {
+                assert_eq!(read.fields, written.fields);
+                assert_eq!(read.outgoing_slots, written.outgoing_slots);
+                assert_eq!(read.incoming_slots, written.incoming_slots);
+            }
+            assert_eq!(plan(&fixture.graph, &read).unwrap(), expected_plan);
+        }
+    }
+
+    #[test]
+    fn references_are_found_by_their_unique_fields_in_another_graph() {
+        let fixture = fixture();
+        let json = bundle(&fixture).to_json(IdFormat::Uuid).unwrap();
+
+        let mut graph = PlainGraphEnvironment::new(&UNIQUE_NAMES);
+        let mut transaction = Transaction::new();
+        let [c, b] = create_nodes(&mut transaction, ["c", "b"]);
+        graph.execute(transaction).unwrap();
+        let result = graph
+            .import_bundle(&SubgraphBundle::from_json(&json).unwrap())
+            .unwrap();
+        let copy = |original: &Uid| result.temp_id_map[&temp_id(original)];
+        let (pair, a) = (copy(&fixture.pair), copy(&fixture.a));
+        assert!(![pair, a].contains(&fixture.pair) && ![pair, a].contains(&fixture.a));
+        assert_eq!(targets(&graph, &pair, &PARTNER_SLOT_ID), vec![a]);
+        assert_eq!(targets(&graph, &a, &LINKS_SLOT_ID), vec![b, c]);
+        assert_eq!(graph.len(), 4);
+
+        // The import is a single step
+        graph.undo().unwrap();
+        assert_eq!(graph.len(), 2);
+        assert!(graph.get(&b).unwrap().incoming_slots.is_empty());
+    }
+
+    #[test]
+    fn imports_fail_for_references_which_cannot_be_found() {
+        let fixture = fixture();
+        let bundle = bundle(&fixture);
+        let mut graph = PlainGraphEnvironment::new(&UNIQUE_NAMES);
+        let mut transaction = Transaction::new();
+        create_nodes(&mut transaction, ["b"]);
+        graph.execute(transaction).unwrap();
+
+        let error = graph.import_bundle(&bundle).unwrap_err();
+        assert!(matches!(
+            error,
+            BundleError::UnresolvedReference { id, operative: NODE_OPERATIVE_ID }
+                if id == fixture.c
+        ));
+        assert!(error.to_string().contains(&fixture.c.to_string()));
+        assert_eq!(graph.len(), 1);
+    }
+
+    #[test]
+    fn imports_fail_for_bundles_without_their_root() {
+        let fixture = fixture();
+        let mut bundle = bundle(&fixture);
+        bundle.instances.remove(0);
+        assert!(matches!(
+            plan(&fixture.graph, &bundle),
+            Err(BundleError::Malformed(_))
+        ));
+    }
+}
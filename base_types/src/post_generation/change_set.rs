@@ -0,0 +1,343 @@
+#[cfg(feature = "reactive")]
+use std::{collections::HashMap, sync::mpsc};
+
+use crate::{
+    common::Uid,
+    post_generation::{HistoryFieldEdit, SlotRef, StandaloneBlueprint},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeOrigin {
+    Commit,
+    Undo,
+    Redo,
+    /// Merged from another replica, see [`crate::post_generation::merge`]
+    Merge,
+}
+
+/// The precise delta applied to a graph by a single blueprint
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeSet {
+    pub origin: ChangeOrigin,
+    pub label: Option<String>,
+    pub added_instances: Vec<Uid>,
+    pub deleted_instances: Vec<Uid>,
+    pub added_edges: Vec<SlotRef>,
+    pub removed_edges: Vec<SlotRef>,
+    pub field_edits: Vec<HistoryFieldEdit>,
+}
+
+impl ChangeSet {
+    #[cfg(feature = "reactive")]
+    pub(crate) fn from_blueprint<TSchema: crate::post_generation::reactive::RGSO>(
+        blueprint: &crate::post_generation::reactive::Blueprint<TSchema>,
+        origin: ChangeOrigin,
+    ) -> Self {
+        // Edges held by added or deleted instances come and go with them. Every other edge appears
+        // as both an outgoing and an incoming update, the outgoing side is enough to describe it
+        let held_edges = |instances: &[TSchema]| {
+            instances
+                .iter()
+                .flat_map(|instance| {
+                    instance
+                        .outgoing_slots()
+                        .into_iter()
+                        .flat_map(|(slot_id, slot)| {
+                            leptos::prelude::GetUntracked::get_untracked(&slot.slotted_instances)
+                                .into_iter()
+                                .map(|target_instance_id| SlotRef {
+                                    host_instance_id: *instance.get_id(),
+                                    target_instance_id,
+                                    slot_id: *slot_id,
+                                })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        Self {
+            origin,
+            label: blueprint.label.clone(),
+            added_instances: blueprint
+                .added_instances
+                .iter()
+                .map(|instance| *instance.get_id())
+                .collect(),
+            deleted_instances: blueprint
+                .deleted_instances
+                .iter()
+                .map(|instance| *instance.get_id())
+                .collect(),
+            added_edges: held_edges(&blueprint.added_instances)
+                .into_iter()
+                .chain(
+                    blueprint
+                        .add_outgoing_updates
+                        .iter()
+                        .map(|(_, slot_ref)| slot_ref.clone()),
+                )
+                .collect(),
+            removed_edges: held_edges(&blueprint.deleted_instances)
+                .into_iter()
+                .chain(
+                    blueprint
+                        .remove_outgoing_updates
+                        .iter()
+                        .map(|(_, slot_ref)| slot_ref.clone()),
+                )
+                .collect(),
+            field_edits: blueprint
+                .field_updates
+                .iter()
+                .map(|(_, field_edit)| field_edit.clone())
+                .collect(),
+        }
+    }
+    pub fn from_standalone_blueprint(
+        blueprint: &StandaloneBlueprint,
+        origin: ChangeOrigin,
+    ) -> Self {
+        Self {
+            origin,
+            label: blueprint.label.clone(),
+            added_instances: blueprint
+                .added_instances
+                .iter()
+                .map(|instance| instance.id)
+                .collect(),
+            deleted_instances: blueprint
+                .deleted_instances
+                .iter()
+                .map(|instance| instance.id)
+                .collect(),
+            added_edges: blueprint
+                .added_instances
+                .iter()
+                .flat_map(|instance| instance.outgoing_slots.iter().cloned())
+                .chain(
+                    blueprint
+                        .add_outgoing_updates
+                        .iter()
+                        .map(|(_, slot_ref)| slot_ref.clone()),
+                )
+                .collect(),
+            removed_edges: blueprint
+                .deleted_instances
+                .iter()
+                .flat_map(|instance| instance.outgoing_slots.iter().cloned())
+                .chain(
+                    blueprint
+                        .remove_outgoing_updates
+                        .iter()
+                        .map(|(_, slot_ref)| slot_ref.clone()),
+                )
+                .collect(),
+            field_edits: blueprint
+                .field_updates
+                .iter()
+                .map(|(_, field_edit)| field_edit.clone())
+                .collect(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.added_instances.is_empty()
+            && self.deleted_instances.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.field_edits.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+#[cfg(feature = "reactive")]
+type ChangeCallback = std::sync::Arc<dyn Fn(&ChangeSet) + Send + Sync>;
+
+#[cfg(feature = "reactive")]
+enum Subscriber {
+    Callback(ChangeCallback),
+    Channel(mpsc::Sender<ChangeSet>),
+}
+
+#[cfg(feature = "reactive")]
+/// Registered observers of a graph environment, see
+/// [`crate::post_generation::reactive::RBaseGraphEnvironment::subscribe`]
+#[derive(Default)]
+pub struct ChangeSubscribers {
+    next_id: u64,
+    subscribers: HashMap<SubscriptionId, Subscriber>,
+}
+
+#[cfg(feature = "reactive")]
+impl std::fmt::Debug for ChangeSubscribers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChangeSubscribers")
+            .field("count", &self.subscribers.len())
+            .finish()
+    }
+}
+
+#[cfg(feature = "reactive")]
+impl ChangeSubscribers {
+    pub(crate) fn add_callback(&mut self, callback: ChangeCallback) -> SubscriptionId {
+        self.add(Subscriber::Callback(callback))
+    }
+    pub(crate) fn add_channel(&mut self, sender: mpsc::Sender<ChangeSet>) -> SubscriptionId {
+        self.add(Subscriber::Channel(sender))
+    }
+    fn add(&mut self, subscriber: Subscriber) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.subscribers.insert(id, subscriber);
+        id
+    }
+    pub(crate) fn remove(&mut self, id: &SubscriptionId) -> bool {
+        self.subscribers.remove(id).is_some()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+    /// Sends the change sets to every channel, dropping those whose receiver is gone, and returns
+    /// the callbacks so that they can be run once the subscribers are no longer borrowed
+    pub(crate) fn dispatch_channels(&mut self, change_sets: &[ChangeSet]) -> Vec<ChangeCallback> {
+        let mut callbacks = Vec::new();
+        self.subscribers.retain(|_, subscriber| match subscriber {
+            Subscriber::Callback(callback) => {
+                callbacks.push(callback.clone());
+                true
+            }
+            Subscriber::Channel(sender) => change_sets
+                .iter()
+                .all(|change_set| sender.send(change_set.clone()).is_ok()),
+        });
+        callbacks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{post_generation::StandaloneRGSOConcrete, testing::*};
+
+    fn edge(host_instance_id: Uid, target_instance_id: Uid) -> SlotRef {
+        SlotRef {
+            host_instance_id,
+            target_instance_id,
+            slot_id: LINKS_SLOT_ID,
+        }
+    }
+
+    #[test]
+    fn standalone_blueprints_list_each_edge_once() {
+        let added = StandaloneRGSOConcrete {
+            id: 1,
+            fields: Default::default(),
+            outgoing_slots: vec![edge(1, 2)],
+            incoming_slots: Vec::new(),
+            operative: NODE_OPERATIVE_ID,
+            template: NODE_TEMPLATE_ID,
+        };
+        let blueprint = StandaloneBlueprint {
+            added_instances: vec![added],
+            add_outgoing_updates: vec![(3, edge(3, 2))],
+            add_incoming_updates: vec![(2, edge(1, 2)), (2, edge(3, 2))],
+            label: Some("Link".to_string()),
+            ..Default::default()
+        };
+        let change_set = ChangeSet::from_standalone_blueprint(&blueprint, ChangeOrigin::Merge);
+        assert_eq!(change_set.origin, ChangeOrigin::Merge);
+        assert_eq!(change_set.label.as_deref(), Some("Link"));
+        assert_eq!(change_set.added_instances, vec![1]);
+        assert_eq!(change_set.added_edges, vec![edge(1, 2), edge(3, 2)]);
+
+        let reversed =
+            ChangeSet::from_standalone_blueprint(&blueprint.reverse(), ChangeOrigin::Undo);
+        assert_eq!(reversed.deleted_instances, vec![1]);
+        assert_eq!(reversed.removed_edges, vec![edge(1, 2), edge(3, 2)]);
+        assert!(reversed.added_edges.is_empty());
+        assert!(ChangeSet::from_standalone_blueprint(
+            &StandaloneBlueprint::default(),
+            ChangeOrigin::Commit
+        )
+        .is_empty());
+    }
+
+    #[cfg(feature = "reactive")]
+    mod reactive {
+        use std::sync::{Arc, Mutex};
+
+        use super::*;
+        use crate::post_generation::reactive::{RGraphEnvironment, SharedGraph};
+
+        // Collects the change sets delivered to a callback
+        fn collect(
+            graph: &SharedGraph<TestSchema>,
+        ) -> (SubscriptionId, Arc<Mutex<Vec<ChangeSet>>>) {
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let sink = received.clone();
+            let id =
+                graph.subscribe(move |change_set| sink.lock().unwrap().push(change_set.clone()));
+            (id, received)
+        }
+
+        #[test]
+        fn subscribers_see_commits_undos_redos_and_merges() {
+            let remote = initialize_graph();
+            let graph = initialize_graph();
+            remote.attach_replica(1);
+            graph.attach_replica(2);
+            let (_, received) = collect(&graph);
+            let (_, channel) = graph.subscribe_channel();
+
+            let a = create_node(&graph, "a").unwrap();
+            rename(&graph, &a, "b").unwrap();
+            graph.undo().unwrap();
+            graph.redo().unwrap();
+            let remote_node = create_node(&remote, "remote").unwrap();
+            graph.merge(remote.take_replicated()).unwrap();
+
+            let received = received.lock().unwrap().clone();
+            assert_eq!(
+                received
+                    .iter()
+                    .map(|change_set| change_set.origin)
+                    .collect::<Vec<_>>(),
+                vec![
+                    ChangeOrigin::Commit,
+                    ChangeOrigin::Commit,
+                    ChangeOrigin::Undo,
+                    ChangeOrigin::Redo,
+                    ChangeOrigin::Merge,
+                ]
+            );
+            assert_eq!(received[0].added_instances, vec![a]);
+            let new_names = received[1..4]
+                .iter()
+                .map(|change_set| change_set.field_edits[0].new_value.to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(new_names, vec!["b", "a", "b"]);
+            assert_eq!(received[4].added_instances, vec![remote_node]);
+            assert_eq!(channel.try_iter().collect::<Vec<_>>(), received);
+        }
+
+        #[test]
+        fn unsubscribing_stops_delivery() {
+            let graph = initialize_graph();
+            let (callback, received) = collect(&graph);
+            let (channel_id, channel) = graph.subscribe_channel();
+            create_node(&graph, "a").unwrap();
+
+            assert!(graph.unsubscribe(&callback));
+            assert!(!graph.unsubscribe(&callback));
+            create_node(&graph, "b").unwrap();
+            assert_eq!(received.lock().unwrap().len(), 1);
+            assert_eq!(channel.try_iter().count(), 2);
+
+            // Dropping the receiver ends the subscription with the next delivery
+            drop(channel);
+            create_node(&graph, "c").unwrap();
+            assert!(!graph.unsubscribe(&channel_id));
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! A graph environment which can be shared between threads.
+//!
+//! Readers work on snapshots of the graph and never wait for writers to validate their changes.
+//! Writers are run one at a time, each on a copy of the latest graph which replaces it once the
+//! write has succeeded. Copies share their structure with the graph they were taken from, so
+//! neither snapshots nor writes copy the whole graph.
+use std::{
+    collections::{HashMap, HashSet},
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex, RwLock},
+};
+
+use crate::{
+    common::Uid,
+    post_generation::{
+        change_set::ChangeSet,
+        plain::{PlainGraphEnvironment, Transaction},
+        validation::check_versions,
+        ElementCreationError, ExecutionResult, StandaloneBlueprint,
+    },
+};
+
+#[derive(Debug)]
+pub struct ConcurrentGraphEnvironment {
+    current: RwLock<Arc<PlainGraphEnvironment>>,
+    writer: Mutex<()>,
+}
+
+impl ConcurrentGraphEnvironment {
+    pub fn new(graph: PlainGraphEnvironment) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(graph)),
+            writer: Mutex::new(()),
+        }
+    }
+    /// The graph as of the last completed write. Later writes don't change the returned snapshot.
+    pub fn snapshot(&self) -> Arc<PlainGraphEnvironment> {
+        self.current.read().unwrap().clone()
+    }
+    /// Starts a transaction whose reads see the graph as it is now, see [`IsolatedTransaction`]
+    pub fn begin(&self) -> IsolatedTransaction {
+        IsolatedTransaction {
+            snapshot: self.snapshot(),
+            transaction: Transaction::new(),
+            dependencies: HashSet::new(),
+        }
+    }
+    pub fn begin_with_label(&self, label: impl Into<String>) -> IsolatedTransaction {
+        IsolatedTransaction {
+            snapshot: self.snapshot(),
+            transaction: Transaction::with_label(label),
+            dependencies: HashSet::new(),
+        }
+    }
+
+    /// Resolves the transaction against the latest graph and applies it, see
+    /// [`PlainGraphEnvironment::execute`]. Fails with [`ElementCreationError::Conflict`] if an
+    /// instance which the transaction relies on has been changed since its snapshot was taken,
+    /// so of two transactions built on the same state only the first to commit succeeds.
+    pub fn commit(
+        &self,
+        transaction: IsolatedTransaction,
+    ) -> Result<ExecutionResult, ElementCreationError> {
+        let read_versions = transaction.read_versions();
+        self.write(|graph| {
+            check_versions(|id| graph.version(id), &read_versions)?;
+            graph.execute(transaction.transaction)
+        })
+    }
+    pub fn execute(
+        &self,
+        transaction: Transaction,
+    ) -> Result<ExecutionResult, ElementCreationError> {
+        self.write(|graph| graph.execute(transaction))
+    }
+    pub fn apply(&self, blueprint: StandaloneBlueprint) -> Result<ChangeSet, ElementCreationError> {
+        self.write(|graph| graph.apply(blueprint))
+    }
+    pub fn undo(&self) -> Result<Option<ChangeSet>, ElementCreationError> {
+        self.write(|graph| graph.undo())
+    }
+    pub fn redo(&self) -> Result<Option<ChangeSet>, ElementCreationError> {
+        self.write(|graph| graph.redo())
+    }
+
+    fn write<T>(
+        &self,
+        change: impl FnOnce(&mut PlainGraphEnvironment) -> Result<T, ElementCreationError>,
+    ) -> Result<T, ElementCreationError> {
+        let _writer = self.writer.lock().unwrap();
+        let mut next = PlainGraphEnvironment::clone(&self.snapshot());
+        let result = change(&mut next)?;
+        *self.current.write().unwrap() = Arc::new(next);
+        Ok(result)
+    }
+}
+
+// The environment is only of use if it can be shared between threads
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ConcurrentGraphEnvironment>();
+    assert_send_sync::<IsolatedTransaction>();
+};
+
+impl From<PlainGraphEnvironment> for ConcurrentGraphEnvironment {
+    fn from(value: PlainGraphEnvironment) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A [`Transaction`] paired with the snapshot of the graph it was started from, so that the
+/// reads made while building it are consistent with each other no matter what is committed in
+/// the meantime
+#[derive(Debug, Clone)]
+pub struct IsolatedTransaction {
+    snapshot: Arc<PlainGraphEnvironment>,
+    transaction: Transaction,
+    dependencies: HashSet<Uid>,
+}
+
+impl IsolatedTransaction {
+    pub fn graph(&self) -> &PlainGraphEnvironment {
+        &self.snapshot
+    }
+    pub fn into_transaction(self) -> Transaction {
+        self.transaction
+    }
+    /// Makes the commit fail if the instance has changed since the snapshot was taken, even if
+    /// the transaction doesn't touch it, e.g. because it was only read to decide what to do
+    pub fn depend_on(&mut self, id: Uid) -> &mut Self {
+        self.dependencies.insert(id);
+        self
+    }
+    // The version of every instance the transaction relies on, as of the snapshot
+    fn read_versions(&self) -> HashMap<Uid, u64> {
+        self.transaction
+            .existing_ids()
+            .into_iter()
+            .chain(self.dependencies.iter().copied())
+            .map(|id| (id, self.snapshot.version(&id)))
+            .collect()
+    }
+}
+
+impl Deref for IsolatedTransaction {
+    type Target = Transaction;
+    fn deref(&self) -> &Self::Target {
+        &self.transaction
+    }
+}
+
+impl DerefMut for IsolatedTransaction {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.transaction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::{
+        primitives::PrimitiveValues,
+        testing::{NAME_FIELD_ID, NODE_OPERATIVE_ID, TEST_SCHEMA},
+    };
+
+    fn create_node(transaction: &mut Transaction, name: &str) -> Uid {
+        let id = transaction.create(NODE_OPERATIVE_ID);
+        transaction.set_field(id, NAME_FIELD_ID, PrimitiveValues::String(name.to_string()));
+        id
+    }
+    fn graph_with_node(name: &str) -> (ConcurrentGraphEnvironment, Uid) {
+        let graph = ConcurrentGraphEnvironment::new(PlainGraphEnvironment::new(&TEST_SCHEMA));
+        let mut transaction = Transaction::new();
+        let id = create_node(&mut transaction, name);
+        graph.execute(transaction).unwrap();
+        (graph, id)
+    }
+    fn rename(transaction: &mut Transaction, id: Uid, name: &str) {
+        transaction.set_field(id, NAME_FIELD_ID, PrimitiveValues::String(name.to_string()));
+    }
+
+    #[test]
+    fn readers_see_the_last_completed_write_while_another_is_running() {
+        let (graph, _) = graph_with_node("a");
+        let (started_sender, started) = mpsc::channel();
+        let (release, release_receiver) = mpsc::channel::<()>();
+        let graph = &graph;
+        std::thread::scope(|scope| {
+            let writer = scope.spawn(move || {
+                graph.write(|next| {
+                    let mut transaction = Transaction::new();
+                    create_node(&mut transaction, "b");
+                    next.execute(transaction)?;
+                    started_sender.send(()).unwrap();
+                    release_receiver.recv().unwrap();
+                    Ok(())
+                })
+            });
+            started.recv().unwrap();
+            // The readers finish although the write holding the writer lock hasn't
+            let readers = (0..4)
+                .map(|_| scope.spawn(|| graph.snapshot().len()))
+                .collect::<Vec<_>>();
+            let lengths = readers
+                .into_iter()
+                .map(|reader| reader.join().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(lengths, vec![1; 4]);
+            let before = graph.snapshot();
+            release.send(()).unwrap();
+            writer.join().unwrap().unwrap();
+            assert_eq!(before.len(), 1);
+        });
+        assert_eq!(graph.snapshot().len(), 2);
+    }
+
+    #[test]
+    fn writes_from_many_threads_are_all_applied() {
+        let graph = ConcurrentGraphEnvironment::new(PlainGraphEnvironment::new(&TEST_SCHEMA));
+        std::thread::scope(|scope| {
+            (0..8).for_each(|i| {
+                let graph = &graph;
+                scope.spawn(move || {
+                    let mut transaction = Transaction::new();
+                    create_node(&mut transaction, &i.to_string());
+                    graph.execute(transaction).unwrap();
+                });
+            });
+        });
+        assert_eq!(graph.snapshot().len(), 8);
+        assert!(graph.snapshot().check_integrity().is_empty());
+    }
+
+    #[test]
+    fn only_the_first_of_two_transactions_on_the_same_state_commits() {
+        let (graph, id) = graph_with_node("a");
+        let mut first = graph.begin();
+        let mut second = graph.begin();
+        rename(&mut first, id, "b");
+        rename(&mut second, id, "c");
+
+        graph.commit(first).unwrap();
+        let error = graph.commit(second).unwrap_err();
+        assert!(matches!(
+            error.flatten().as_slice(),
+            [ElementCreationError::Conflict { instance_id, read_version, current_version }]
+                if *instance_id == id && read_version < current_version
+        ));
+        assert_eq!(
+            graph.snapshot().get(&id).unwrap().fields[&NAME_FIELD_ID],
+            PrimitiveValues::String("b".to_string())
+        );
+    }
+
+    #[test]
+    fn commits_fail_when_an_instance_they_depend_on_changed() {
+        let (graph, id) = graph_with_node("a");
+        let mut reader = graph.begin_with_label("copy name");
+        let name = reader.graph().get(&id).unwrap().fields[&NAME_FIELD_ID].clone();
+        let copy = reader.create(NODE_OPERATIVE_ID);
+        reader.set_field(copy, NAME_FIELD_ID, name);
+        let mut unrelated = graph.begin();
+        create_node(&mut unrelated, "b");
+        let mut dependent = reader.clone();
+        dependent.depend_on(id);
+
+        let mut rename_transaction = Transaction::new();
+        rename(&mut rename_transaction, id, "renamed");
+        graph.execute(rename_transaction).unwrap();
+
+        // Transactions which didn't touch the renamed instance still commit
+        graph.commit(reader).unwrap();
+        graph.commit(unrelated).unwrap();
+        let error = graph.commit(dependent).unwrap_err();
+        assert!(matches!(
+            error.flatten().as_slice(),
+            [ElementCreationError::Conflict { instance_id, .. }] if *instance_id == id
+        ));
+        assert_eq!(graph.snapshot().len(), 3);
+    }
+}
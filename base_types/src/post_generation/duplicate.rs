@@ -0,0 +1,654 @@
+//! Deep copies of an instance together with the instances it owns.
+//!
+//! The schema has no notion of ownership, so the caller names the slots which own their targets.
+//! Starting from the copied instance, every instance reached through one of those slots is copied
+//! as well. Edges between copied instances are rewired to the copies, while edges to any other
+//! instance are kept and point at the same instance as before. Instances holding the original in
+//! one of their slots only get the copy as well if `add_to_holders` is set, and copying fails if
+//! that takes one of their slots out of its bounds.
+//!
+//! A copy holds the same field values as its original, so copying is refused upfront with an
+//! [`ElementCreationError::UniqueFieldViolation`] for every copied instance which has a value in a
+//! unique field. Those instances have to be left out of the owning slots or created by hand.
+//!
+//! Copies are named by the id of their original (see [`temp_id`]) in the resulting
+//! [`crate::post_generation::ExecutionResult`]'s temp ids.
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    common::Uid,
+    constraint_schema::{ConstraintSchema, FieldIndex},
+    constraint_schema_item::ConstraintSchemaItem,
+    post_generation::{
+        plain::Transaction, ElementCreationError, StandaloneRGSOConcrete, UniqueFieldError,
+    },
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+/// One end of an edge in a [`DuplicatePlan`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CopyEnd {
+    /// The copy of the instance with this id
+    Copy(Uid),
+    /// An instance which isn't copied
+    Existing(Uid),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceCopy {
+    pub original: Uid,
+    pub operative_id: Uid,
+    pub fields: Vec<(Uid, PrimitiveValues)>,
+}
+
+impl InstanceCopy {
+    /// The instance's fields, apart from the locked ones which come with its operative
+    pub fn of(
+        constraint_schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+        instance: &StandaloneRGSOConcrete,
+    ) -> Result<Self, ElementCreationError> {
+        let operative = constraint_schema
+            .operative_library
+            .get(&instance.operative)
+            .ok_or(ElementCreationError::NonexistentOperative {
+                instance_id: instance.id,
+                operative_id: instance.operative,
+            })?;
+        let locked_fields = operative
+            .get_locked_fields_digest(constraint_schema)
+            .map(|digest| digest.locked_fields)
+            .unwrap_or_default();
+        let mut fields = instance
+            .fields
+            .iter()
+            .filter(|(field_id, _)| !locked_fields.contains_key(field_id))
+            .map(|(field_id, value)| (*field_id, value.clone()))
+            .collect::<Vec<_>>();
+        fields.sort_by_key(|(field_id, _)| *field_id);
+        Ok(Self {
+            original: instance.id,
+            operative_id: instance.operative,
+            fields,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CopyEdge {
+    pub host: CopyEnd,
+    pub slot_id: Uid,
+    pub target: CopyEnd,
+}
+
+/// The instances and edges to create, resolved against the graph but without touching it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicatePlan {
+    /// Root first, then in the order they were reached
+    pub copies: Vec<InstanceCopy>,
+    pub edges: Vec<CopyEdge>,
+}
+
+/// The temp id under which the copy of `original` is listed
+pub fn temp_id(original: &Uid) -> String {
+    original.to_string()
+}
+
+/// Works out what copying `root_id` creates, reading instances through `get`
+pub fn plan_duplicate(
+    constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    get: impl Fn(&Uid) -> Option<StandaloneRGSOConcrete>,
+    root_id: &Uid,
+    owning_slots: &[Uid],
+    add_to_holders: bool,
+) -> Result<DuplicatePlan, ElementCreationError> {
+    let root = get(root_id).ok_or(ElementCreationError::NonexistentInstance { id: *root_id })?;
+    let mut copied = HashSet::from([*root_id]);
+    let mut originals = vec![root];
+    let mut next = 0;
+    while let Some(instance) = originals.get(next) {
+        let owned = instance
+            .outgoing_slots
+            .iter()
+            .filter(|slot_ref| slot_ref.host_instance_id == instance.id)
+            .filter(|slot_ref| owning_slots.contains(&slot_ref.slot_id))
+            .map(|slot_ref| slot_ref.target_instance_id)
+            .filter(|target_id| copied.insert(*target_id))
+            .collect::<Vec<_>>();
+        for target_id in owned {
+            originals.push(
+                get(&target_id)
+                    .ok_or(ElementCreationError::NonexistentInstance { id: target_id })?,
+            );
+        }
+        next += 1;
+    }
+
+    let copies = originals
+        .iter()
+        .map(|instance| InstanceCopy::of(constraint_schema, instance))
+        .collect::<Result<Vec<_>, ElementCreationError>>()?;
+    let unique_errors = copies
+        .iter()
+        .flat_map(|copy| unique_fields(constraint_schema, copy))
+        .collect::<Vec<_>>();
+    if !unique_errors.is_empty() {
+        return Err(ElementCreationError::Stack(unique_errors));
+    }
+
+    let end = |id: Uid| match copied.contains(&id) {
+        true => CopyEnd::Copy(id),
+        false => CopyEnd::Existing(id),
+    };
+    let mut edges = originals
+        .iter()
+        .flat_map(|instance| {
+            instance
+                .outgoing_slots
+                .iter()
+                .filter(|slot_ref| slot_ref.host_instance_id == instance.id)
+        })
+        .map(|slot_ref| CopyEdge {
+            host: CopyEnd::Copy(slot_ref.host_instance_id),
+            slot_id: slot_ref.slot_id,
+            target: end(slot_ref.target_instance_id),
+        })
+        .collect::<Vec<_>>();
+
+    // Their bounds are checked when the copies are created
+    if add_to_holders {
+        edges.extend(
+            originals[0]
+                .incoming_slots
+                .iter()
+                .filter(|slot_ref| slot_ref.target_instance_id == *root_id)
+                .filter(|slot_ref| !copied.contains(&slot_ref.host_instance_id))
+                .map(|slot_ref| CopyEdge {
+                    host: CopyEnd::Existing(slot_ref.host_instance_id),
+                    slot_id: slot_ref.slot_id,
+                    target: CopyEnd::Copy(*root_id),
+                }),
+        );
+    }
+    Ok(DuplicatePlan { copies, edges })
+}
+
+// The copy's values in unique fields, which its original already holds
+fn unique_fields(
+    constraint_schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    copy: &InstanceCopy,
+) -> Vec<ElementCreationError> {
+    let Some(template) = constraint_schema
+        .operative_library
+        .get(&copy.operative_id)
+        .and_then(|operative| {
+            constraint_schema
+                .template_library
+                .get(&operative.template_id)
+        })
+    else {
+        return Vec::new();
+    };
+    copy.fields
+        .iter()
+        .filter_map(|(field_id, value)| {
+            let field = template.field_constraints.get(field_id)?;
+            (field.index == FieldIndex::Unique).then(|| {
+                UniqueFieldError {
+                    instance_id: copy.original,
+                    temp_id: Some(temp_id(&copy.original)),
+                    field_id: *field_id,
+                    field_name: field.tag.name.clone(),
+                    value: value.clone(),
+                    conflicting_id: copy.original,
+                }
+                .into()
+            })
+        })
+        .collect()
+}
+
+/// The transaction creating the copies, for a [`crate::post_generation::plain::PlainGraphEnvironment`]
+pub fn plan_transaction(plan: &DuplicatePlan, label: impl Into<String>) -> Transaction {
+    let mut transaction = Transaction::with_label(label);
+    let ids = plan
+        .copies
+        .iter()
+        .map(|copy| {
+            let id = transaction.create(copy.operative_id);
+            transaction.set_temp_id(id, temp_id(&copy.original));
+            copy.fields.iter().for_each(|(field_id, value)| {
+                transaction.set_field(id, *field_id, value.clone());
+            });
+            (copy.original, id)
+        })
+        .collect::<HashMap<_, _>>();
+    let resolve = |end: &CopyEnd| match end {
+        CopyEnd::Copy(original) => ids[original],
+        CopyEnd::Existing(id) => *id,
+    };
+    plan.edges.iter().for_each(|edge| {
+        transaction.add_edge(resolve(&edge.host), edge.slot_id, resolve(&edge.target));
+    });
+    transaction
+}
+
+#[cfg(feature = "reactive")]
+pub use reactive_duplicate::{duplicate_in_graph, execute_plan_in_graph};
+
+#[cfg(feature = "reactive")]
+mod reactive_duplicate {
+    use super::*;
+    use crate::post_generation::{
+        reactive::{
+            hidden::EditRGSO, BlueprintId, BuildByOperative, BuilderVisitor, HasSlotEnum,
+            RBaseGraphEnvironment, RGSOConcrete, RGSOConcreteBuilder, RGraphEnvironment,
+            RIntoSchema, RProducable, SubgraphBuilder, TempAddIncomingSlotRef,
+            TempAddOutgoingSlotRef,
+        },
+        ExecutionResult, SlotRef,
+    };
+
+    /// Copies into a reactive graph, building every copy with its operative's builder and
+    /// executing them together as a single undo step
+    pub fn duplicate_in_graph<TSchema>(
+        graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<TSchema>>>,
+        root_id: &Uid,
+        owning_slots: &[Uid],
+        add_to_holders: bool,
+    ) -> Result<ExecutionResult, ElementCreationError>
+    where
+        TSchema: BuildByOperative + EditRGSO + Clone + Into<StandaloneRGSOConcrete> + Send + Sync,
+    {
+        let graph = graph.into();
+        let plan = plan_duplicate(
+            graph.constraint_schema,
+            |id| graph.get(id).map(Into::into),
+            root_id,
+            owning_slots,
+            add_to_holders,
+        )?;
+        execute_plan_in_graph(&graph, &plan, "Duplicate")
+    }
+
+    /// Creates the plan's copies in a reactive graph with a single labelled transaction
+    pub fn execute_plan_in_graph<TSchema>(
+        graph: &std::sync::Arc<RBaseGraphEnvironment<TSchema>>,
+        plan: &DuplicatePlan,
+        label: &str,
+    ) -> Result<ExecutionResult, ElementCreationError>
+    where
+        TSchema: BuildByOperative + EditRGSO + Send + Sync,
+    {
+        let Some((root, rest)) = plan.copies.split_first() else {
+            return Ok(ExecutionResult {
+                temp_id_map: HashMap::new(),
+            });
+        };
+        TSchema::build_by_operative(
+            &root.operative_id,
+            graph.clone(),
+            RootVisitor {
+                copy: root,
+                rest,
+                edges: &plan.edges,
+                label,
+                graph,
+            },
+        )
+        .unwrap_or_else(|| Err(unknown_operative(root)))
+    }
+
+    // Only if the graph's constraint schema isn't the one its schema was generated from
+    fn unknown_operative(copy: &InstanceCopy) -> ElementCreationError {
+        ElementCreationError::NonexistentOperative {
+            instance_id: copy.original,
+            operative_id: copy.operative_id,
+        }
+    }
+
+    // Adds the copy's fields and every edge to or from it. Temp ids have to be set before any
+    // temporary edges are added
+    fn fill<T, TSchema>(
+        builder: &mut SubgraphBuilder<T, TSchema>,
+        copy: &InstanceCopy,
+        edges: &[CopyEdge],
+    ) where
+        TSchema: EditRGSO + Send + Sync + 'static,
+        T: RIntoSchema<Schema = TSchema>
+            + HasSlotEnum
+            + Send
+            + Sync
+            + Clone
+            + std::fmt::Debug
+            + 'static,
+        <T as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug + Send + Sync,
+        RGSOConcreteBuilder<T, TSchema>: RProducable<RGSOConcrete<T, TSchema>>,
+    {
+        let own_temp_id = temp_id(&copy.original);
+        builder.set_temp_id(&own_temp_id);
+        copy.fields.iter().for_each(|(field_id, value)| {
+            builder.edit_field(*field_id, value.clone());
+        });
+        edges
+            .iter()
+            .for_each(|edge| match (edge.host, edge.target) {
+                (CopyEnd::Copy(host), CopyEnd::Copy(target)) if host == copy.original => {
+                    builder.temp_add_incoming(
+                        &temp_id(&target),
+                        TempAddIncomingSlotRef {
+                            host_instance_id: BlueprintId::Temporary(own_temp_id.clone()),
+                            slot_id: edge.slot_id,
+                        },
+                    );
+                    builder.add_outgoing::<T>(
+                        &edge.slot_id,
+                        BlueprintId::Temporary(temp_id(&target)),
+                        None,
+                    );
+                }
+                (CopyEnd::Copy(host), CopyEnd::Existing(target)) if host == copy.original => {
+                    let slot_ref = SlotRef {
+                        host_instance_id: *builder.get_id(),
+                        target_instance_id: target,
+                        slot_id: edge.slot_id,
+                    };
+                    builder.raw_add_incoming_to_updates(slot_ref);
+                    builder.add_outgoing::<T>(&edge.slot_id, BlueprintId::Existing(target), None);
+                }
+                (CopyEnd::Existing(host), CopyEnd::Copy(target)) if target == copy.original => {
+                    builder.temp_add_incoming(
+                        &own_temp_id,
+                        TempAddIncomingSlotRef {
+                            host_instance_id: BlueprintId::Existing(host),
+                            slot_id: edge.slot_id,
+                        },
+                    );
+                    builder.temp_add_outgoing(
+                        BlueprintId::Existing(host),
+                        TempAddOutgoingSlotRef {
+                            target_instance_id: BlueprintId::Temporary(own_temp_id.clone()),
+                            slot_id: edge.slot_id,
+                        },
+                    );
+                }
+                _ => {}
+            });
+    }
+
+    struct RootVisitor<'a, TSchema: 'static> {
+        copy: &'a InstanceCopy,
+        rest: &'a [InstanceCopy],
+        edges: &'a [CopyEdge],
+        label: &'a str,
+        graph: &'a std::sync::Arc<RBaseGraphEnvironment<TSchema>>,
+    }
+
+    impl<TSchema> BuilderVisitor<TSchema> for RootVisitor<'_, TSchema>
+    where
+        TSchema: BuildByOperative + EditRGSO + Send + Sync,
+    {
+        type Output = Result<ExecutionResult, ElementCreationError>;
+
+        fn visit<T>(self, mut builder: SubgraphBuilder<T, TSchema>) -> Self::Output
+        where
+            T: RIntoSchema<Schema = TSchema>
+                + HasSlotEnum
+                + Send
+                + Sync
+                + Clone
+                + std::fmt::Debug
+                + 'static,
+            <T as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug + Send + Sync,
+            RGSOConcreteBuilder<T, TSchema>: RProducable<RGSOConcrete<T, TSchema>>,
+        {
+            fill(&mut builder, self.copy, self.edges);
+            for copy in self.rest {
+                TSchema::build_by_operative(
+                    &copy.operative_id,
+                    self.graph.clone(),
+                    IncorporateVisitor {
+                        copy,
+                        edges: self.edges,
+                        root: &mut builder,
+                    },
+                )
+                .ok_or_else(|| unknown_operative(copy))?;
+            }
+            builder.execute_with_label(self.label)
+        }
+    }
+
+    struct IncorporateVisitor<'a, R: HasSlotEnum + Clone + std::fmt::Debug, TSchema: 'static>
+    where
+        <R as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug,
+    {
+        copy: &'a InstanceCopy,
+        edges: &'a [CopyEdge],
+        root: &'a mut SubgraphBuilder<R, TSchema>,
+    }
+
+    impl<R, TSchema> BuilderVisitor<TSchema> for IncorporateVisitor<'_, R, TSchema>
+    where
+        TSchema: EditRGSO + Send + Sync,
+        R: RIntoSchema<Schema = TSchema>
+            + HasSlotEnum
+            + Send
+            + Sync
+            + Clone
+            + std::fmt::Debug
+            + 'static,
+        <R as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug + Send + Sync,
+        RGSOConcreteBuilder<R, TSchema>: RProducable<RGSOConcrete<R, TSchema>>,
+    {
+        type Output = ();
+
+        fn visit<T>(self, mut builder: SubgraphBuilder<T, TSchema>)
+        where
+            T: RIntoSchema<Schema = TSchema>
+                + HasSlotEnum
+                + Send
+                + Sync
+                + Clone
+                + std::fmt::Debug
+                + 'static,
+            <T as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug + Send + Sync,
+            RGSOConcreteBuilder<T, TSchema>: RProducable<RGSOConcrete<T, TSchema>>,
+        {
+            fill(&mut builder, self.copy, self.edges);
+            self.root.incorporate(&builder);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        post_generation::plain::PlainGraphEnvironment,
+        testing::{
+            LABEL_OPERATIVE_ID, LINKS_SLOT_ID, NAME_FIELD_ID, NODE_OPERATIVE_ID, PAIR_OPERATIVE_ID,
+            PARTNER_SLOT_ID, RANK_FIELD_ID, TEST_SCHEMA, TEXT_FIELD_ID,
+        },
+    };
+
+    struct Fixture {
+        graph: PlainGraphEnvironment,
+        a: Uid,
+        b: Uid,
+        pair: Uid,
+    }
+
+    // a and b link to each other and the pair is partnered with a
+    fn fixture() -> Fixture {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let mut transaction = Transaction::new();
+        let [a, b] = ["a", "b"].map(|name| {
+            let id = transaction.create(NODE_OPERATIVE_ID);
+            transaction.set_field(id, NAME_FIELD_ID, PrimitiveValues::String(name.to_string()));
+            id
+        });
+        let pair = transaction.create(PAIR_OPERATIVE_ID);
+        transaction
+            .add_edge(a, LINKS_SLOT_ID, b)
+            .add_edge(b, LINKS_SLOT_ID, a)
+            .add_edge(pair, PARTNER_SLOT_ID, a);
+        graph.execute(transaction).unwrap();
+        Fixture { graph, a, b, pair }
+    }
+    fn plan(
+        graph: &PlainGraphEnvironment,
+        root_id: &Uid,
+        owning_slots: &[Uid],
+        add_to_holders: bool,
+    ) -> Result<DuplicatePlan, ElementCreationError> {
+        plan_duplicate(
+            &TEST_SCHEMA,
+            |id| graph.get(id).cloned(),
+            root_id,
+            owning_slots,
+            add_to_holders,
+        )
+    }
+    fn targets(graph: &PlainGraphEnvironment, host_id: &Uid, slot_id: &Uid) -> Vec<Uid> {
+        let mut targets = graph
+            .get(host_id)
+            .unwrap()
+            .outgoing_slots
+            .iter()
+            .filter(|slot_ref| slot_ref.slot_id == *slot_id)
+            .map(|slot_ref| slot_ref.target_instance_id)
+            .collect::<Vec<_>>();
+        targets.sort();
+        targets
+    }
+
+    #[test]
+    fn plan_rewires_edges_between_copies_and_keeps_the_others() {
+        let Fixture { graph, a, b, pair } = fixture();
+        let plan = plan(&graph, &pair, &[PARTNER_SLOT_ID], false).unwrap();
+
+        assert_eq!(
+            plan.copies
+                .iter()
+                .map(|copy| copy.original)
+                .collect::<Vec<_>>(),
+            vec![pair, a]
+        );
+        assert_eq!(
+            plan.copies[1].fields,
+            vec![(NAME_FIELD_ID, PrimitiveValues::String("a".to_string()))]
+        );
+        assert_eq!(
+            plan.edges,
+            vec![
+                CopyEdge {
+                    host: CopyEnd::Copy(pair),
+                    slot_id: PARTNER_SLOT_ID,
+                    target: CopyEnd::Copy(a),
+                },
+                CopyEdge {
+                    host: CopyEnd::Copy(a),
+                    slot_id: LINKS_SLOT_ID,
+                    target: CopyEnd::Existing(b),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_names_copies_by_their_originals() {
+        let Fixture {
+            mut graph,
+            a,
+            b,
+            pair,
+        } = fixture();
+        let result = graph.duplicate(&pair, &[PARTNER_SLOT_ID], false).unwrap();
+
+        assert_eq!(result.temp_id_map.len(), 2);
+        let pair_copy = result.temp_id_map[&temp_id(&pair)];
+        let a_copy = result.temp_id_map[&temp_id(&a)];
+        assert_eq!(targets(&graph, &pair_copy, &PARTNER_SLOT_ID), vec![a_copy]);
+        assert_eq!(targets(&graph, &a_copy, &LINKS_SLOT_ID), vec![b]);
+        assert_eq!(
+            graph.get(&a_copy).unwrap().fields[&NAME_FIELD_ID],
+            PrimitiveValues::String("a".to_string())
+        );
+        // The originals are untouched, apart from b being linked to by the copy
+        assert_eq!(targets(&graph, &pair, &PARTNER_SLOT_ID), vec![a]);
+        assert_eq!(targets(&graph, &b, &LINKS_SLOT_ID), vec![a]);
+        assert!(graph
+            .get(&b)
+            .unwrap()
+            .incoming_slots
+            .iter()
+            .any(|slot_ref| slot_ref.host_instance_id == a_copy));
+        assert!(graph.check_integrity().is_empty());
+        assert_eq!(graph.len(), 5);
+    }
+
+    #[test]
+    fn holders_only_get_the_copy_if_asked_and_within_bounds() {
+        let Fixture {
+            mut graph,
+            a,
+            b,
+            pair,
+        } = fixture();
+
+        // The pair can only hold a single partner
+        let error = graph.duplicate(&a, &[], true).unwrap_err();
+        assert!(matches!(
+            error.flatten().as_slice(),
+            [ElementCreationError::BoundCheckOutOfRange(bound)] if bound.instance_id == pair
+        ));
+        assert_eq!(graph.len(), 3);
+
+        let result = graph.duplicate(&a, &[], false).unwrap();
+        let a_copy = result.temp_id_map[&temp_id(&a)];
+        assert_eq!(targets(&graph, &b, &LINKS_SLOT_ID), vec![a]);
+        assert_eq!(targets(&graph, &pair, &PARTNER_SLOT_ID), vec![a]);
+        assert_eq!(targets(&graph, &a_copy, &LINKS_SLOT_ID), vec![b]);
+
+        // a can link to any number of nodes
+        let result = graph.duplicate(&b, &[], true).unwrap();
+        let b_copy = result.temp_id_map[&temp_id(&b)];
+        let mut expected = vec![b, b_copy];
+        expected.sort();
+        assert_eq!(targets(&graph, &a, &LINKS_SLOT_ID), expected);
+        assert!(graph.check_integrity().is_empty());
+    }
+
+    #[test]
+    fn copies_with_unique_values_are_refused_upfront() {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let mut transaction = Transaction::new();
+        let label = transaction.create(LABEL_OPERATIVE_ID);
+        transaction
+            .set_field(
+                label,
+                TEXT_FIELD_ID,
+                PrimitiveValues::String("x".to_string()),
+            )
+            .set_field(label, RANK_FIELD_ID, PrimitiveValues::Int(1));
+        graph.execute(transaction).unwrap();
+
+        let error = plan(&graph, &label, &[], false).unwrap_err();
+        assert!(matches!(
+            error.flatten().as_slice(),
+            [ElementCreationError::UniqueFieldViolation(unique)]
+                if unique.field_id == TEXT_FIELD_ID && unique.conflicting_id == label
+        ));
+        assert!(graph.duplicate(&label, &[], false).is_err());
+        assert_eq!(graph.len(), 1);
+    }
+
+    #[test]
+    fn plan_fails_for_missing_instances() {
+        let Fixture { graph, .. } = fixture();
+        assert!(matches!(
+            plan(&graph, &0, &[], false),
+            Err(ElementCreationError::NonexistentInstance { id: 0 })
+        ));
+    }
+}
@@ -0,0 +1,1230 @@
+//! Exports the instances of a graph to formats which other graph tooling can load, without a
+//! connection to that tooling: a Cypher script or `neo4j-admin` CSVs for Neo4j, GraphML, and RDF
+//! in Turtle.
+//!
+//! Names are taken from the schema rather than using raw ids: nodes are labelled with their
+//! operative (and its ancestors) and their template, properties are named after fields and
+//! relationships after slots. Every instance also carries its `id`, `operative_id` and
+//! `template_id` as UUID strings, so that the export can be matched back to the graph. Exporting
+//! fails with [`ExportError::ReservedFieldName`] if the template of an exported instance has a
+//! field of one of those names.
+//!
+//! The output only depends on the instances and the schema, so exports of the same graph can be
+//! diffed: nodes are ordered by id, properties by name, and each node's relationships by slot,
+//! keeping the order of the instances within a slot.
+use std::{collections::BTreeMap, fmt::Display, path::Path};
+
+use crate::{
+    common::{u128_to_string, Uid},
+    constraint_schema::{ConstraintSchema, LibraryOperative, LibraryTemplate, OperativeVariants},
+    post_generation::{SlotRef, StandaloneRGSOConcrete},
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+const RESERVED_PROPERTIES: [&str; 3] = ["id", "operative_id", "template_id"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportError {
+    UnknownTemplate {
+        instance_id: Uid,
+        template_id: Uid,
+    },
+    UnknownOperative {
+        instance_id: Uid,
+        operative_id: Uid,
+    },
+    UnknownSlot {
+        instance_id: Uid,
+        slot_id: Uid,
+    },
+    UnknownField {
+        instance_id: Uid,
+        field_id: Uid,
+    },
+    /// A field would be exported under the same name as one of the properties every node carries
+    ReservedFieldName {
+        template_id: Uid,
+        field_name: String,
+    },
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::UnknownTemplate {
+                instance_id,
+                template_id,
+            } => write!(
+                f,
+                "{}: template {} is not in the schema",
+                instance_id, template_id
+            ),
+            ExportError::UnknownOperative {
+                instance_id,
+                operative_id,
+            } => write!(
+                f,
+                "{}: operative {} is not in the schema",
+                instance_id, operative_id
+            ),
+            ExportError::UnknownSlot {
+                instance_id,
+                slot_id,
+            } => write!(
+                f,
+                "{}: slot {} is not defined on its template",
+                instance_id, slot_id
+            ),
+            ExportError::UnknownField {
+                instance_id,
+                field_id,
+            } => write!(
+                f,
+                "{}: field {} is not defined on its template",
+                instance_id, field_id
+            ),
+            ExportError::ReservedFieldName {
+                template_id,
+                field_name,
+            } => write!(
+                f,
+                "Template {} has a field named '{}', which is reserved for the exported node",
+                template_id, field_name
+            ),
+        }
+    }
+}
+
+/// The files for `neo4j-admin database import`, one node file per operative and a single
+/// relationship file. Node files are named after the operative and its id, as operative names
+/// needn't be unique or make valid file names
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Neo4jCsvExport {
+    /// Keyed by file name
+    pub node_files: BTreeMap<String, String>,
+    pub relationships: String,
+}
+
+impl Neo4jCsvExport {
+    pub const RELATIONSHIPS_FILE_NAME: &'static str = "relationships.csv";
+
+    /// Writes every file into `dir`, which must already exist
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>) -> std::io::Result<()> {
+        let dir = dir.as_ref();
+        for (file_name, contents) in &self.node_files {
+            std::fs::write(dir.join(file_name), contents)?;
+        }
+        std::fs::write(dir.join(Self::RELATIONSHIPS_FILE_NAME), &self.relationships)
+    }
+    /// The arguments to pass to `neo4j-admin database import full` for the files written by
+    /// [`Self::write_to_dir`]. List properties are separated by `;`, and strings may span lines
+    pub fn import_args(&self, dir: impl AsRef<Path>) -> Vec<String> {
+        let dir = dir.as_ref();
+        self.node_files
+            .keys()
+            .map(|file_name| format!("--nodes={}", dir.join(file_name).display()))
+            .chain([
+                format!(
+                    "--relationships={}",
+                    dir.join(Self::RELATIONSHIPS_FILE_NAME).display()
+                ),
+                "--array-delimiter=;".to_string(),
+                "--multiline-fields=true".to_string(),
+            ])
+            .collect()
+    }
+}
+
+/// A Cypher script which creates every instance, then every relationship between them
+pub fn to_cypher(
+    schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    instances: &[StandaloneRGSOConcrete],
+) -> Result<String, ExportError> {
+    let nodes = export_nodes(schema, instances)?;
+    let mut script = String::new();
+    for node in &nodes {
+        let labels = node
+            .labels
+            .iter()
+            .map(|label| format!(":{}", cypher_name(label)))
+            .collect::<String>();
+        let properties = node
+            .properties()
+            .map(|(name, value)| format!("{}: {}", cypher_name(name), cypher_value(value)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        script.push_str(&format!("CREATE ({} {{{}}});\n", labels, properties));
+    }
+    for node in &nodes {
+        for edge in &node.edges {
+            script.push_str(&format!(
+                "MATCH (a:{} {{id: \"{}\"}}), (b:{} {{id: \"{}\"}}) CREATE (a)-[:{} {{slot_id: \"{}\", position: {}}}]->(b);\n",
+                cypher_name(&node.labels[0]),
+                u128_to_string(edge.slot_ref.host_instance_id),
+                cypher_name(&edge.target_label),
+                u128_to_string(edge.slot_ref.target_instance_id),
+                cypher_name(&edge.slot_name),
+                u128_to_string(edge.slot_ref.slot_id),
+                edge.position
+            ));
+        }
+    }
+    Ok(script)
+}
+
+/// The CSV files for `neo4j-admin database import`, see [`Neo4jCsvExport::import_args`]
+pub fn to_neo4j_csv(
+    schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    instances: &[StandaloneRGSOConcrete],
+) -> Result<Neo4jCsvExport, ExportError> {
+    let nodes = export_nodes(schema, instances)?;
+    let mut export = Neo4jCsvExport::default();
+    // Instances of the same operative share a template, and so the same columns
+    let mut by_operative: BTreeMap<Uid, Vec<&ExportNode>> = BTreeMap::new();
+    nodes.iter().for_each(|node| {
+        by_operative
+            .entry(node.operative_id)
+            .or_default()
+            .push(node)
+    });
+    for (operative_id, nodes) in by_operative {
+        let file_name = format!(
+            "nodes_{}_{}.csv",
+            local_name(&nodes[0].labels[0]),
+            u128_to_string(operative_id)
+        );
+        let columns = nodes[0]
+            .template
+            .field_constraints
+            .values()
+            .map(|field| (field.tag.name.as_str(), &field.value_type))
+            .collect::<BTreeMap<_, _>>();
+        let mut file = ["id:ID", ":LABEL", "operative_id", "template_id"]
+            .into_iter()
+            .map(csv_cell)
+            .chain(
+                columns
+                    .iter()
+                    .map(|(name, value_type)| csv_cell(&csv_header(name, value_type))),
+            )
+            .collect::<Vec<_>>()
+            .join(",");
+        file.push('\n');
+        for node in nodes {
+            let row = [
+                u128_to_string(node.id),
+                node.labels.join(";"),
+                u128_to_string(node.operative_id),
+                u128_to_string(node.template.tag.id),
+            ]
+            .into_iter()
+            .chain(columns.keys().map(|name| {
+                node.fields
+                    .get(name)
+                    .map(|value| csv_value(value))
+                    .unwrap_or_default()
+            }))
+            .map(|cell| csv_cell(&cell))
+            .collect::<Vec<_>>()
+            .join(",");
+            file.push_str(&row);
+            file.push('\n');
+        }
+        export.node_files.insert(file_name, file);
+    }
+
+    export.relationships = ":START_ID,:END_ID,:TYPE,slot_id,position:int\n".to_string();
+    for node in &nodes {
+        for edge in &node.edges {
+            let row = [
+                u128_to_string(edge.slot_ref.host_instance_id),
+                u128_to_string(edge.slot_ref.target_instance_id),
+                edge.slot_name.clone(),
+                u128_to_string(edge.slot_ref.slot_id),
+                edge.position.to_string(),
+            ]
+            .iter()
+            .map(|cell| csv_cell(cell))
+            .collect::<Vec<_>>()
+            .join(",");
+            export.relationships.push_str(&row);
+            export.relationships.push('\n');
+        }
+    }
+    Ok(export)
+}
+
+/// A GraphML document with a node per instance and an edge per slotted instance. Each field of
+/// the exported templates is declared as a key, named after the field
+pub fn to_graphml(
+    schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    instances: &[StandaloneRGSOConcrete],
+) -> Result<String, ExportError> {
+    let nodes = export_nodes(schema, instances)?;
+    let mut field_keys = BTreeMap::new();
+    nodes.iter().for_each(|node| {
+        node.template.field_constraints.values().for_each(|field| {
+            field_keys.insert(
+                field_local_name(node.template, &field.tag.name),
+                (field.tag.name.as_str(), &field.value_type),
+            );
+        })
+    });
+
+    let mut document = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+    );
+    [
+        ("node", "labels", "string"),
+        ("node", "id", "string"),
+        ("node", "operative_id", "string"),
+        ("node", "template_id", "string"),
+        ("edge", "slot", "string"),
+        ("edge", "slot_id", "string"),
+        ("edge", "position", "int"),
+    ]
+    .into_iter()
+    .for_each(|(domain, name, value_type)| {
+        document.push_str(&format!(
+            "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>\n",
+            name, domain, name, value_type
+        ))
+    });
+    field_keys.iter().for_each(|(key, (name, value_type))| {
+        document.push_str(&format!(
+            "  <key id=\"field.{}\" for=\"node\" attr.name=\"{}\" attr.type=\"{}\"/>\n",
+            xml_escape(key),
+            xml_escape(name),
+            graphml_type(value_type)
+        ))
+    });
+    document.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+    for node in &nodes {
+        document.push_str(&format!("    <node id=\"{}\">\n", u128_to_string(node.id)));
+        let data = [
+            ("labels".to_string(), format!(":{}", node.labels.join(":"))),
+            ("id".to_string(), u128_to_string(node.id)),
+            (
+                "operative_id".to_string(),
+                u128_to_string(node.operative_id),
+            ),
+            (
+                "template_id".to_string(),
+                u128_to_string(node.template.tag.id),
+            ),
+        ]
+        .into_iter()
+        .chain(node.fields.iter().filter_map(|(name, value)| {
+            unwrap_option(value).map(|_| {
+                (
+                    format!("field.{}", field_local_name(node.template, name)),
+                    csv_value(value),
+                )
+            })
+        }));
+        data.for_each(|(key, value)| {
+            document.push_str(&format!(
+                "      <data key=\"{}\">{}</data>\n",
+                xml_escape(&key),
+                xml_escape(&value)
+            ))
+        });
+        document.push_str("    </node>\n");
+    }
+    let mut edge_count = 0;
+    for node in &nodes {
+        for edge in &node.edges {
+            document.push_str(&format!(
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">\n      <data key=\"slot\">{}</data>\n      <data key=\"slot_id\">{}</data>\n      <data key=\"position\">{}</data>\n    </edge>\n",
+                edge_count,
+                u128_to_string(edge.slot_ref.host_instance_id),
+                u128_to_string(edge.slot_ref.target_instance_id),
+                xml_escape(&edge.slot_name),
+                u128_to_string(edge.slot_ref.slot_id),
+                edge.position
+            ));
+            edge_count += 1;
+        }
+    }
+    document.push_str("  </graph>\n</graphml>\n");
+    Ok(document)
+}
+
+/// An RDF graph in Turtle, with IRIs under `base_iri` (e.g. `https://example.org/sentences/`).
+///
+/// The vocabulary is derived from the whole schema: templates and operatives become classes,
+/// with each operative a subclass of its parent operative or template, fields become datatype
+/// properties and slots object properties. Each instance is typed with its operative, and the
+/// values of list fields are written as one triple per item. Since RDF has no order, the position
+/// of an instance within its slot is lost.
+pub fn to_turtle(
+    schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    instances: &[StandaloneRGSOConcrete],
+    base_iri: &str,
+) -> Result<String, ExportError> {
+    let nodes = export_nodes(schema, instances)?;
+    let mut document = [
+        (
+            "rdf",
+            "http://www.w3.org/1999/02/22-rdf-syntax-ns#".to_string(),
+        ),
+        ("rdfs", "http://www.w3.org/2000/01/rdf-schema#".to_string()),
+        ("owl", "http://www.w3.org/2002/07/owl#".to_string()),
+        ("xsd", "http://www.w3.org/2001/XMLSchema#".to_string()),
+        ("template", format!("{}template/", base_iri)),
+        ("operative", format!("{}operative/", base_iri)),
+        ("field", format!("{}field/", base_iri)),
+        ("slot", format!("{}slot/", base_iri)),
+        ("instance", format!("{}instance/", base_iri)),
+    ]
+    .iter()
+    .map(|(prefix, iri)| format!("@prefix {}: <{}> .\n", prefix, iri_escape(iri)))
+    .collect::<String>();
+
+    document.push_str("\n# Vocabulary\n");
+    let mut templates = schema.template_library.values().collect::<Vec<_>>();
+    templates.sort_by(|a, b| (&a.tag.name, a.tag.id).cmp(&(&b.tag.name, b.tag.id)));
+    for template in templates {
+        document.push_str(&format!(
+            "\ntemplate:{} a owl:Class ;\n    rdfs:label {} .\n",
+            local_name(&template.tag.name),
+            turtle_string(&template.tag.name)
+        ));
+        let mut fields = template.field_constraints.values().collect::<Vec<_>>();
+        fields.sort_by(|a, b| a.tag.name.cmp(&b.tag.name));
+        for field in fields {
+            document.push_str(&format!(
+                "field:{} a owl:DatatypeProperty ;\n    rdfs:label {} ;\n    rdfs:domain template:{} ;\n    rdfs:range {} .\n",
+                field_local_name(template, &field.tag.name),
+                turtle_string(&field.tag.name),
+                local_name(&template.tag.name),
+                xsd_type(&field.value_type)
+            ));
+        }
+        let mut slots = template.operative_slots.values().collect::<Vec<_>>();
+        slots.sort_by(|a, b| a.tag.name.cmp(&b.tag.name));
+        for slot in slots {
+            let range = match &slot.operative_descriptor {
+                OperativeVariants::LibraryOperative(operative_id) => schema
+                    .operative_library
+                    .get(operative_id)
+                    .map(|operative| {
+                        format!(
+                            " ;\n    rdfs:range operative:{}",
+                            local_name(&operative.tag.name)
+                        )
+                    })
+                    .unwrap_or_default(),
+                OperativeVariants::TraitOperative(_) => String::new(),
+            };
+            document.push_str(&format!(
+                "slot:{} a owl:ObjectProperty ;\n    rdfs:label {} ;\n    rdfs:domain template:{}{} .\n",
+                slot_local_name(template, &slot.tag.name),
+                turtle_string(&slot.tag.name),
+                local_name(&template.tag.name),
+                range
+            ));
+        }
+    }
+    let mut operatives = schema.operative_library.values().collect::<Vec<_>>();
+    operatives.sort_by(|a, b| (&a.tag.name, a.tag.id).cmp(&(&b.tag.name, b.tag.id)));
+    for operative in operatives {
+        let parent = operative
+            .parent_operative_id
+            .and_then(|parent_id| schema.operative_library.get(&parent_id))
+            .map(|parent| format!("operative:{}", local_name(&parent.tag.name)))
+            .or_else(|| {
+                schema
+                    .template_library
+                    .get(&operative.template_id)
+                    .map(|template| format!("template:{}", local_name(&template.tag.name)))
+            });
+        document.push_str(&format!(
+            "\noperative:{} a owl:Class ;\n    rdfs:label {}",
+            local_name(&operative.tag.name),
+            turtle_string(&operative.tag.name)
+        ));
+        if let Some(parent) = parent {
+            document.push_str(&format!(" ;\n    rdfs:subClassOf {}", parent));
+        }
+        document.push_str(" .\n");
+    }
+
+    document.push_str("\n# Instances\n");
+    for node in &nodes {
+        let operative_name = &node.labels[0];
+        let mut statements = vec![format!("a operative:{}", local_name(operative_name))];
+        node.fields.iter().for_each(|(name, value)| {
+            let property = format!("field:{}", field_local_name(node.template, name));
+            turtle_literals(value)
+                .into_iter()
+                .for_each(|literal| statements.push(format!("{} {}", property, literal)));
+        });
+        node.edges.iter().for_each(|edge| {
+            statements.push(format!(
+                "slot:{} instance:{}",
+                slot_local_name(node.template, &edge.slot_name),
+                u128_to_string(edge.slot_ref.target_instance_id)
+            ))
+        });
+        document.push_str(&format!(
+            "\ninstance:{}\n    {} .\n",
+            u128_to_string(node.id),
+            statements.join(" ;\n    ")
+        ));
+    }
+    Ok(document)
+}
+
+// An instance with every id resolved against the schema
+struct ExportNode<'a> {
+    id: Uid,
+    operative_id: Uid,
+    template: &'a LibraryTemplate<PrimitiveTypes, PrimitiveValues>,
+    /// The operative first, then its ancestors, then the template
+    labels: Vec<String>,
+    fields: BTreeMap<&'a str, &'a PrimitiveValues>,
+    edges: Vec<ExportEdge<'a>>,
+}
+
+impl<'a> ExportNode<'a> {
+    // Values of `None` are left out, as graph databases don't store null properties
+    fn properties(&self) -> impl Iterator<Item = (&str, PrimitiveValues)> + '_ {
+        [
+            ("id", self.id),
+            ("operative_id", self.operative_id),
+            ("template_id", self.template.tag.id),
+        ]
+        .into_iter()
+        .map(|(name, id)| (name, PrimitiveValues::String(u128_to_string(id))))
+        .chain(
+            self.fields
+                .iter()
+                .filter_map(|(name, value)| unwrap_option(value).map(|value| (*name, value))),
+        )
+    }
+}
+
+struct ExportEdge<'a> {
+    slot_ref: &'a SlotRef,
+    slot_name: String,
+    target_label: String,
+    /// Index of the target within its slot
+    position: usize,
+}
+
+fn export_nodes<'a>(
+    schema: &'a ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    instances: &'a [StandaloneRGSOConcrete],
+) -> Result<Vec<ExportNode<'a>>, ExportError> {
+    let mut instances = instances.iter().collect::<Vec<_>>();
+    instances.sort_by_key(|instance| instance.id);
+    let operative_names = instances
+        .iter()
+        .map(|instance| {
+            schema
+                .operative_library
+                .get(&instance.operative)
+                .map(|operative| (instance.id, operative.tag.name.clone()))
+                .ok_or(ExportError::UnknownOperative {
+                    instance_id: instance.id,
+                    operative_id: instance.operative,
+                })
+        })
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+    instances
+        .into_iter()
+        .map(|instance| {
+            let template = schema.template_library.get(&instance.template).ok_or(
+                ExportError::UnknownTemplate {
+                    instance_id: instance.id,
+                    template_id: instance.template,
+                },
+            )?;
+            if let Some(field) = template
+                .field_constraints
+                .values()
+                .find(|field| RESERVED_PROPERTIES.contains(&field.tag.name.as_str()))
+            {
+                return Err(ExportError::ReservedFieldName {
+                    template_id: template.tag.id,
+                    field_name: field.tag.name.clone(),
+                });
+            }
+            let fields = instance
+                .fields
+                .iter()
+                .map(|(field_id, value)| {
+                    template
+                        .field_constraints
+                        .get(field_id)
+                        .map(|field| (field.tag.name.as_str(), value))
+                        .ok_or(ExportError::UnknownField {
+                            instance_id: instance.id,
+                            field_id: *field_id,
+                        })
+                })
+                .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+            // The stable sort keeps the order of the instances within each slot
+            let mut outgoing_slots = instance.outgoing_slots.iter().collect::<Vec<_>>();
+            outgoing_slots.sort_by_key(|slot_ref| slot_ref.slot_id);
+            let mut positions = BTreeMap::<Uid, usize>::new();
+            let edges = outgoing_slots
+                .into_iter()
+                .map(|slot_ref| {
+                    let slot = template.operative_slots.get(&slot_ref.slot_id).ok_or(
+                        ExportError::UnknownSlot {
+                            instance_id: instance.id,
+                            slot_id: slot_ref.slot_id,
+                        },
+                    )?;
+                    // Edges to instances outside of the export are dropped
+                    let Some(target_label) = operative_names.get(&slot_ref.target_instance_id)
+                    else {
+                        return Ok(None);
+                    };
+                    let position = positions.entry(slot_ref.slot_id).or_default();
+                    *position += 1;
+                    Ok(Some(ExportEdge {
+                        slot_ref,
+                        slot_name: slot.tag.name.clone(),
+                        target_label: target_label.clone(),
+                        position: *position - 1,
+                    }))
+                })
+                .filter_map(Result::transpose)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(ExportNode {
+                id: instance.id,
+                operative_id: instance.operative,
+                template,
+                labels: labels(schema, instance, template),
+                fields,
+                edges,
+            })
+        })
+        .collect()
+}
+
+fn labels(
+    schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    instance: &StandaloneRGSOConcrete,
+    template: &LibraryTemplate<PrimitiveTypes, PrimitiveValues>,
+) -> Vec<String> {
+    let mut labels = Vec::new();
+    let mut next_operative: Option<&LibraryOperative<PrimitiveTypes, PrimitiveValues>> =
+        schema.operative_library.get(&instance.operative);
+    while let Some(operative) = next_operative {
+        if !labels.contains(&operative.tag.name) {
+            labels.push(operative.tag.name.clone());
+        }
+        next_operative = operative
+            .parent_operative_id
+            .and_then(|parent_id| schema.operative_library.get(&parent_id));
+    }
+    if !labels.contains(&template.tag.name) {
+        labels.push(template.tag.name.clone());
+    }
+    labels
+}
+
+fn unwrap_option(value: &PrimitiveValues) -> Option<PrimitiveValues> {
+    match value {
+        PrimitiveValues::Option(inner) => inner.as_ref().as_ref().and_then(unwrap_option),
+        value => Some(value.clone()),
+    }
+}
+
+// Names are always quoted, as schema names may contain spaces or clash with keywords
+fn cypher_name(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+fn cypher_value(value: PrimitiveValues) -> String {
+    match value {
+        PrimitiveValues::Int(val) => val.to_string(),
+        PrimitiveValues::Bool(val) => val.to_string(),
+        PrimitiveValues::String(val) => cypher_string(&val),
+        PrimitiveValues::Option(val) => match *val {
+            Some(val) => cypher_value(val),
+            None => "null".to_string(),
+        },
+        PrimitiveValues::List(val) => format!(
+            "[{}]",
+            val.into_iter()
+                .map(cypher_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn cypher_string(val: &str) -> String {
+    let mut quoted = String::with_capacity(val.len() + 2);
+    quoted.push('"');
+    for c in val.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn csv_header(name: &str, value_type: &PrimitiveTypes) -> String {
+    fn type_name(value_type: &PrimitiveTypes) -> &'static str {
+        match value_type {
+            PrimitiveTypes::Int => "long",
+            PrimitiveTypes::Bool => "boolean",
+            PrimitiveTypes::Option(inner) => type_name(inner),
+            PrimitiveTypes::EmptyTuple | PrimitiveTypes::String | PrimitiveTypes::List(_) => {
+                "string"
+            }
+        }
+    }
+    match value_type {
+        PrimitiveTypes::List(inner) => format!("{}:{}[]", name, type_name(inner)),
+        value_type => format!("{}:{}", name, type_name(value_type)),
+    }
+}
+
+// Empty for `None`, which the importer leaves unset
+fn csv_value(value: &PrimitiveValues) -> String {
+    match unwrap_option(value) {
+        Some(PrimitiveValues::List(val)) => val.iter().map(csv_value).collect::<Vec<_>>().join(";"),
+        Some(val) => val.to_string(),
+        None => String::new(),
+    }
+}
+
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+// Local names are made of letters, digits, `_` and `-`, so that they can be written as prefixed
+// names. Fields and slots are qualified with their template, as names are only unique within it
+fn local_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn field_local_name(
+    template: &LibraryTemplate<PrimitiveTypes, PrimitiveValues>,
+    field_name: &str,
+) -> String {
+    format!(
+        "{}.{}",
+        local_name(&template.tag.name),
+        local_name(field_name)
+    )
+}
+
+fn slot_local_name(
+    template: &LibraryTemplate<PrimitiveTypes, PrimitiveValues>,
+    slot_name: &str,
+) -> String {
+    format!(
+        "{}.{}",
+        local_name(&template.tag.name),
+        local_name(slot_name)
+    )
+}
+
+fn xsd_type(value_type: &PrimitiveTypes) -> &'static str {
+    match value_type {
+        PrimitiveTypes::Int => "xsd:integer",
+        PrimitiveTypes::Bool => "xsd:boolean",
+        PrimitiveTypes::Option(inner) | PrimitiveTypes::List(inner) => xsd_type(inner),
+        PrimitiveTypes::EmptyTuple | PrimitiveTypes::String => "xsd:string",
+    }
+}
+
+// One literal per list item, none for `None`
+fn turtle_literals(value: &PrimitiveValues) -> Vec<String> {
+    match value {
+        PrimitiveValues::Int(val) => vec![val.to_string()],
+        PrimitiveValues::Bool(val) => vec![val.to_string()],
+        PrimitiveValues::String(val) => vec![turtle_string(val)],
+        PrimitiveValues::Option(val) => val
+            .as_ref()
+            .as_ref()
+            .map(turtle_literals)
+            .unwrap_or_default(),
+        PrimitiveValues::List(val) => val.iter().flat_map(turtle_literals).collect(),
+    }
+}
+
+// Turtle accepts the same escapes as Cypher within double quotes
+fn turtle_string(val: &str) -> String {
+    cypher_string(val)
+}
+
+fn iri_escape(iri: &str) -> String {
+    iri.chars()
+        .flat_map(|c| match c {
+            '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\' | ' ' => {
+                format!("%{:02X}", c as u32).chars().collect::<Vec<_>>()
+            }
+            c => vec![c],
+        })
+        .collect()
+}
+
+fn graphml_type(value_type: &PrimitiveTypes) -> &'static str {
+    match value_type {
+        PrimitiveTypes::Int => "long",
+        PrimitiveTypes::Bool => "boolean",
+        PrimitiveTypes::Option(inner) => graphml_type(inner),
+        PrimitiveTypes::EmptyTuple | PrimitiveTypes::String | PrimitiveTypes::List(_) => "string",
+    }
+}
+
+fn xml_escape(val: &str) -> String {
+    val.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::testing::{
+        LABEL_OPERATIVE_ID, LABEL_TEMPLATE_ID, LINKS_SLOT_ID, NAME_FIELD_ID, NODE_OPERATIVE_ID,
+        NODE_TEMPLATE_ID, PAIR_OPERATIVE_ID, PAIR_TEMPLATE_ID, PARTNER_SLOT_ID, RANK_FIELD_ID,
+        TEST_SCHEMA, TEXT_FIELD_ID,
+    };
+
+    fn instance(
+        id: Uid,
+        operative: Uid,
+        template: Uid,
+        fields: &[(Uid, PrimitiveValues)],
+        outgoing: &[(Uid, Uid)],
+    ) -> StandaloneRGSOConcrete {
+        StandaloneRGSOConcrete {
+            id,
+            fields: fields.iter().cloned().collect::<HashMap<_, _>>(),
+            outgoing_slots: outgoing
+                .iter()
+                .map(|(slot_id, target_instance_id)| SlotRef {
+                    host_instance_id: id,
+                    slot_id: *slot_id,
+                    target_instance_id: *target_instance_id,
+                })
+                .collect(),
+            incoming_slots: Vec::new(),
+            operative,
+            template,
+        }
+    }
+    fn string(value: &str) -> PrimitiveValues {
+        PrimitiveValues::String(value.to_string())
+    }
+    // Node 1 links to nodes 2 and 3 in that order, the pair is partnered with node 1 and the
+    // label's text needs escaping. Incoming edges aren't exported, so they are left out
+    fn instances() -> Vec<StandaloneRGSOConcrete> {
+        let node = |id: Uid, name: &str, links: &[Uid]| {
+            instance(
+                id,
+                NODE_OPERATIVE_ID,
+                NODE_TEMPLATE_ID,
+                &[(NAME_FIELD_ID, string(name))],
+                &links
+                    .iter()
+                    .map(|target| (LINKS_SLOT_ID, *target))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        vec![
+            node(3, "c", &[]),
+            node(1, "a", &[3, 2]),
+            node(2, "b", &[]),
+            instance(
+                4,
+                PAIR_OPERATIVE_ID,
+                PAIR_TEMPLATE_ID,
+                &[],
+                &[(PARTNER_SLOT_ID, 1)],
+            ),
+            instance(
+                5,
+                LABEL_OPERATIVE_ID,
+                LABEL_TEMPLATE_ID,
+                &[
+                    (TEXT_FIELD_ID, string("say \"hi\", <then>\n& go")),
+                    (RANK_FIELD_ID, PrimitiveValues::Int(2)),
+                ],
+                &[],
+            ),
+        ]
+    }
+
+    #[test]
+    fn cypher_creates_nodes_then_relationships() {
+        let script = to_cypher(&TEST_SCHEMA, &instances()).unwrap();
+        let expected = r#"CREATE (:`Node` {`id`: "00000000-0000-0000-0000-000000000001", `operative_id`: "da6dd688-a044-5b91-9e1a-4ab419ef98f5", `template_id`: "4b65c225-cfb7-5929-90e2-9a55805cf900", `name`: "a"});
+CREATE (:`Node` {`id`: "00000000-0000-0000-0000-000000000002", `operative_id`: "da6dd688-a044-5b91-9e1a-4ab419ef98f5", `template_id`: "4b65c225-cfb7-5929-90e2-9a55805cf900", `name`: "b"});
+CREATE (:`Node` {`id`: "00000000-0000-0000-0000-000000000003", `operative_id`: "da6dd688-a044-5b91-9e1a-4ab419ef98f5", `template_id`: "4b65c225-cfb7-5929-90e2-9a55805cf900", `name`: "c"});
+CREATE (:`Pair` {`id`: "00000000-0000-0000-0000-000000000004", `operative_id`: "5292deca-831a-5349-a77a-a7c481438b22", `template_id`: "12f439cc-9d4a-5b5a-8bb1-f845759a0634"});
+CREATE (:`Label` {`id`: "00000000-0000-0000-0000-000000000005", `operative_id`: "2e9bdb3a-1390-698c-e668-ffa76f6243a6", `template_id`: "8cafcc2b-bab0-2f03-62ef-5910f58b3ce3", `rank`: 2, `text`: "say \"hi\", <then>\n& go"});
+MATCH (a:`Node` {id: "00000000-0000-0000-0000-000000000001"}), (b:`Node` {id: "00000000-0000-0000-0000-000000000003"}) CREATE (a)-[:`links` {slot_id: "1bd37aea-069b-5064-a313-54ea34473b10", position: 0}]->(b);
+MATCH (a:`Node` {id: "00000000-0000-0000-0000-000000000001"}), (b:`Node` {id: "00000000-0000-0000-0000-000000000002"}) CREATE (a)-[:`links` {slot_id: "1bd37aea-069b-5064-a313-54ea34473b10", position: 1}]->(b);
+MATCH (a:`Pair` {id: "00000000-0000-0000-0000-000000000004"}), (b:`Node` {id: "00000000-0000-0000-0000-000000000001"}) CREATE (a)-[:`partner` {slot_id: "d13fe3ba-2c49-5efc-a075-ddb967d9deee", position: 0}]->(b);
+"#;
+        assert_eq!(script, expected);
+    }
+
+    #[test]
+    fn neo4j_csv_has_a_node_file_per_operative() {
+        let export = to_neo4j_csv(&TEST_SCHEMA, &instances()).unwrap();
+        let expected_nodes = [
+            (
+                "nodes_Label_2e9bdb3a-1390-698c-e668-ffa76f6243a6.csv",
+                "id:ID,:LABEL,operative_id,template_id,rank:long,text:string
+00000000-0000-0000-0000-000000000005,Label,2e9bdb3a-1390-698c-e668-ffa76f6243a6,8cafcc2b-bab0-2f03-62ef-5910f58b3ce3,2,\"say \"\"hi\"\", <then>
+& go\"
+",
+            ),
+            (
+                "nodes_Node_da6dd688-a044-5b91-9e1a-4ab419ef98f5.csv",
+                "id:ID,:LABEL,operative_id,template_id,name:string
+00000000-0000-0000-0000-000000000001,Node,da6dd688-a044-5b91-9e1a-4ab419ef98f5,4b65c225-cfb7-5929-90e2-9a55805cf900,a
+00000000-0000-0000-0000-000000000002,Node,da6dd688-a044-5b91-9e1a-4ab419ef98f5,4b65c225-cfb7-5929-90e2-9a55805cf900,b
+00000000-0000-0000-0000-000000000003,Node,da6dd688-a044-5b91-9e1a-4ab419ef98f5,4b65c225-cfb7-5929-90e2-9a55805cf900,c
+",
+            ),
+            (
+                "nodes_Pair_5292deca-831a-5349-a77a-a7c481438b22.csv",
+                "id:ID,:LABEL,operative_id,template_id
+00000000-0000-0000-0000-000000000004,Pair,5292deca-831a-5349-a77a-a7c481438b22,12f439cc-9d4a-5b5a-8bb1-f845759a0634
+",
+            ),
+        ]
+        .map(|(file_name, contents)| (file_name.to_string(), contents.to_string()));
+        assert_eq!(export.node_files, BTreeMap::from(expected_nodes));
+        assert_eq!(
+            export.relationships,
+            ":START_ID,:END_ID,:TYPE,slot_id,position:int
+00000000-0000-0000-0000-000000000001,00000000-0000-0000-0000-000000000003,links,1bd37aea-069b-5064-a313-54ea34473b10,0
+00000000-0000-0000-0000-000000000001,00000000-0000-0000-0000-000000000002,links,1bd37aea-069b-5064-a313-54ea34473b10,1
+00000000-0000-0000-0000-000000000004,00000000-0000-0000-0000-000000000001,partner,d13fe3ba-2c49-5efc-a075-ddb967d9deee,0
+"
+        );
+        assert_eq!(
+            export.import_args("out"),
+            vec![
+                "--nodes=out/nodes_Label_2e9bdb3a-1390-698c-e668-ffa76f6243a6.csv",
+                "--nodes=out/nodes_Node_da6dd688-a044-5b91-9e1a-4ab419ef98f5.csv",
+                "--nodes=out/nodes_Pair_5292deca-831a-5349-a77a-a7c481438b22.csv",
+                "--relationships=out/relationships.csv",
+                "--array-delimiter=;",
+                "--multiline-fields=true",
+            ]
+        );
+    }
+
+    #[test]
+    fn edges_to_instances_outside_the_export_are_dropped() {
+        let instances = instances()
+            .into_iter()
+            .filter(|instance| instance.id != 2)
+            .collect::<Vec<_>>();
+        let export = to_neo4j_csv(&TEST_SCHEMA, &instances).unwrap();
+        assert_eq!(export.relationships.lines().count(), 3);
+        assert!(!to_cypher(&TEST_SCHEMA, &instances)
+            .unwrap()
+            .contains("00000000-0000-0000-0000-000000000002"));
+    }
+
+    #[test]
+    fn fields_named_like_the_node_properties_are_rejected() {
+        let mut schema = TEST_SCHEMA.clone();
+        schema
+            .template_library
+            .get_mut(&NODE_TEMPLATE_ID)
+            .unwrap()
+            .field_constraints
+            .get_mut(&NAME_FIELD_ID)
+            .unwrap()
+            .tag
+            .name = "id".to_string();
+        let expected = ExportError::ReservedFieldName {
+            template_id: NODE_TEMPLATE_ID,
+            field_name: "id".to_string(),
+        };
+        assert_eq!(to_cypher(&schema, &instances()), Err(expected.clone()));
+        assert_eq!(to_neo4j_csv(&schema, &instances()), Err(expected.clone()));
+        assert_eq!(to_graphml(&schema, &instances()), Err(expected.clone()));
+        assert_eq!(
+            to_turtle(&schema, &instances(), "http://example.org/graph#"),
+            Err(expected)
+        );
+        // Templates without exported instances don't matter
+        let pairs = instances()
+            .into_iter()
+            .filter(|instance| instance.operative == PAIR_OPERATIVE_ID)
+            .map(|mut pair| {
+                pair.outgoing_slots.clear();
+                pair
+            })
+            .collect::<Vec<_>>();
+        assert!(to_cypher(&schema, &pairs).is_ok());
+    }
+
+    #[test]
+    fn instances_outside_the_schema_are_rejected() {
+        let mut instances = instances();
+        instances[0].fields.insert(LINKS_SLOT_ID, string("x"));
+        assert_eq!(
+            to_cypher(&TEST_SCHEMA, &instances),
+            Err(ExportError::UnknownField {
+                instance_id: 3,
+                field_id: LINKS_SLOT_ID,
+            })
+        );
+        instances[0].operative = 0;
+        assert_eq!(
+            to_cypher(&TEST_SCHEMA, &instances),
+            Err(ExportError::UnknownOperative {
+                instance_id: 3,
+                operative_id: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn graphml_declares_typed_keys_and_escapes_values() {
+        let document = to_graphml(&TEST_SCHEMA, &instances()).unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="labels" for="node" attr.name="labels" attr.type="string"/>
+  <key id="id" for="node" attr.name="id" attr.type="string"/>
+  <key id="operative_id" for="node" attr.name="operative_id" attr.type="string"/>
+  <key id="template_id" for="node" attr.name="template_id" attr.type="string"/>
+  <key id="slot" for="edge" attr.name="slot" attr.type="string"/>
+  <key id="slot_id" for="edge" attr.name="slot_id" attr.type="string"/>
+  <key id="position" for="edge" attr.name="position" attr.type="int"/>
+  <key id="field.Label.rank" for="node" attr.name="rank" attr.type="long"/>
+  <key id="field.Label.text" for="node" attr.name="text" attr.type="string"/>
+  <key id="field.Node.name" for="node" attr.name="name" attr.type="string"/>
+  <graph id="G" edgedefault="directed">
+    <node id="00000000-0000-0000-0000-000000000001">
+      <data key="labels">:Node</data>
+      <data key="id">00000000-0000-0000-0000-000000000001</data>
+      <data key="operative_id">da6dd688-a044-5b91-9e1a-4ab419ef98f5</data>
+      <data key="template_id">4b65c225-cfb7-5929-90e2-9a55805cf900</data>
+      <data key="field.Node.name">a</data>
+    </node>
+    <node id="00000000-0000-0000-0000-000000000002">
+      <data key="labels">:Node</data>
+      <data key="id">00000000-0000-0000-0000-000000000002</data>
+      <data key="operative_id">da6dd688-a044-5b91-9e1a-4ab419ef98f5</data>
+      <data key="template_id">4b65c225-cfb7-5929-90e2-9a55805cf900</data>
+      <data key="field.Node.name">b</data>
+    </node>
+    <node id="00000000-0000-0000-0000-000000000003">
+      <data key="labels">:Node</data>
+      <data key="id">00000000-0000-0000-0000-000000000003</data>
+      <data key="operative_id">da6dd688-a044-5b91-9e1a-4ab419ef98f5</data>
+      <data key="template_id">4b65c225-cfb7-5929-90e2-9a55805cf900</data>
+      <data key="field.Node.name">c</data>
+    </node>
+    <node id="00000000-0000-0000-0000-000000000004">
+      <data key="labels">:Pair</data>
+      <data key="id">00000000-0000-0000-0000-000000000004</data>
+      <data key="operative_id">5292deca-831a-5349-a77a-a7c481438b22</data>
+      <data key="template_id">12f439cc-9d4a-5b5a-8bb1-f845759a0634</data>
+    </node>
+    <node id="00000000-0000-0000-0000-000000000005">
+      <data key="labels">:Label</data>
+      <data key="id">00000000-0000-0000-0000-000000000005</data>
+      <data key="operative_id">2e9bdb3a-1390-698c-e668-ffa76f6243a6</data>
+      <data key="template_id">8cafcc2b-bab0-2f03-62ef-5910f58b3ce3</data>
+      <data key="field.Label.rank">2</data>
+      <data key="field.Label.text">say &quot;hi&quot;, &lt;then&gt;
+&amp; go</data>
+    </node>
+    <edge id="e0" source="00000000-0000-0000-0000-000000000001" target="00000000-0000-0000-0000-000000000003">
+      <data key="slot">links</data>
+      <data key="slot_id">1bd37aea-069b-5064-a313-54ea34473b10</data>
+      <data key="position">0</data>
+    </edge>
+    <edge id="e1" source="00000000-0000-0000-0000-000000000001" target="00000000-0000-0000-0000-000000000002">
+      <data key="slot">links</data>
+      <data key="slot_id">1bd37aea-069b-5064-a313-54ea34473b10</data>
+      <data key="position">1</data>
+    </edge>
+    <edge id="e2" source="00000000-0000-0000-0000-000000000004" target="00000000-0000-0000-0000-000000000001">
+      <data key="slot">partner</data>
+      <data key="slot_id">d13fe3ba-2c49-5efc-a075-ddb967d9deee</data>
+      <data key="position">0</data>
+    </edge>
+  </graph>
+</graphml>
+"#;
+        assert_eq!(document, expected);
+    }
+
+    #[test]
+    fn turtle_describes_the_schema_and_the_instances() {
+        let document = to_turtle(&TEST_SCHEMA, &instances(), "https://example.org/graph/").unwrap();
+        let expected = r#"@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix owl: <http://www.w3.org/2002/07/owl#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+@prefix template: <https://example.org/graph/template/> .
+@prefix operative: <https://example.org/graph/operative/> .
+@prefix field: <https://example.org/graph/field/> .
+@prefix slot: <https://example.org/graph/slot/> .
+@prefix instance: <https://example.org/graph/instance/> .
+
+# Vocabulary
+
+template:Label a owl:Class ;
+    rdfs:label "Label" .
+field:Label.rank a owl:DatatypeProperty ;
+    rdfs:label "rank" ;
+    rdfs:domain template:Label ;
+    rdfs:range xsd:integer .
+field:Label.text a owl:DatatypeProperty ;
+    rdfs:label "text" ;
+    rdfs:domain template:Label ;
+    rdfs:range xsd:string .
+
+template:Node a owl:Class ;
+    rdfs:label "Node" .
+field:Node.name a owl:DatatypeProperty ;
+    rdfs:label "name" ;
+    rdfs:domain template:Node ;
+    rdfs:range xsd:string .
+slot:Node.links a owl:ObjectProperty ;
+    rdfs:label "links" ;
+    rdfs:domain template:Node ;
+    rdfs:range operative:Node .
+
+template:Pair a owl:Class ;
+    rdfs:label "Pair" .
+slot:Pair.partner a owl:ObjectProperty ;
+    rdfs:label "partner" ;
+    rdfs:domain template:Pair ;
+    rdfs:range operative:Node .
+
+operative:Label a owl:Class ;
+    rdfs:label "Label" ;
+    rdfs:subClassOf template:Label .
+
+operative:Node a owl:Class ;
+    rdfs:label "Node" ;
+    rdfs:subClassOf template:Node .
+
+operative:Pair a owl:Class ;
+    rdfs:label "Pair" ;
+    rdfs:subClassOf template:Pair .
+
+# Instances
+
+instance:00000000-0000-0000-0000-000000000001
+    a operative:Node ;
+    field:Node.name "a" ;
+    slot:Node.links instance:00000000-0000-0000-0000-000000000003 ;
+    slot:Node.links instance:00000000-0000-0000-0000-000000000002 .
+
+instance:00000000-0000-0000-0000-000000000002
+    a operative:Node ;
+    field:Node.name "b" .
+
+instance:00000000-0000-0000-0000-000000000003
+    a operative:Node ;
+    field:Node.name "c" .
+
+instance:00000000-0000-0000-0000-000000000004
+    a operative:Pair ;
+    slot:Pair.partner instance:00000000-0000-0000-0000-000000000001 .
+
+instance:00000000-0000-0000-0000-000000000005
+    a operative:Label ;
+    field:Label.rank 2 ;
+    field:Label.text "say \"hi\", <then>\n& go" .
+"#;
+        assert_eq!(document, expected);
+    }
+
+    #[test]
+    fn values_are_escaped_for_each_format() {
+        assert_eq!(
+            xml_escape(r#"<a href="x">&amp;</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;amp;&lt;/a&gt;"
+        );
+        assert_eq!(
+            turtle_string("say \"hi\"\tto C:\\\r\n"),
+            r#""say \"hi\"\tto C:\\\r\n""#
+        );
+        assert_eq!(
+            iri_escape("https://example.org/my graph/<a>{b}|c^d`e\\f\"g"),
+            "https://example.org/my%20graph/%3Ca%3E%7Bb%7D%7Cc%5Ed%60e%5Cf%22g"
+        );
+        assert_eq!(
+            to_turtle(&TEST_SCHEMA, &[], "https://example.org/my graph/")
+                .unwrap()
+                .lines()
+                .nth(4),
+            Some("@prefix template: <https://example.org/my%20graph/template/> .")
+        );
+        assert_eq!(local_name("Word Form (plural)"), "Word_Form__plural_");
+    }
+
+    #[test]
+    fn types_map_onto_graphml_and_xsd_types() {
+        let cases = [
+            (PrimitiveTypes::Int, "long", "xsd:integer"),
+            (PrimitiveTypes::Bool, "boolean", "xsd:boolean"),
+            (PrimitiveTypes::String, "string", "xsd:string"),
+            (PrimitiveTypes::EmptyTuple, "string", "xsd:string"),
+            (
+                PrimitiveTypes::Option(Box::new(PrimitiveTypes::Int)),
+                "long",
+                "xsd:integer",
+            ),
+            // GraphML has no lists, so they are exported as separated strings
+            (
+                PrimitiveTypes::List(Box::new(PrimitiveTypes::Bool)),
+                "string",
+                "xsd:boolean",
+            ),
+        ];
+        cases.iter().for_each(|(value_type, graphml, xsd)| {
+            assert_eq!(graphml_type(value_type), *graphml, "{:?}", value_type);
+            assert_eq!(xsd_type(value_type), *xsd, "{:?}", value_type);
+        });
+        assert_eq!(
+            turtle_literals(&PrimitiveValues::List(vec![
+                PrimitiveValues::Int(1),
+                PrimitiveValues::Option(Box::new(None)),
+                PrimitiveValues::Option(Box::new(Some(PrimitiveValues::Bool(true)))),
+            ])),
+            vec!["1", "true"]
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use crate::{
+    common::Uid,
+    constraint_schema::{ConstraintSchema, FieldIndex},
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+/// Lookup tables from value to instance for every field which the schema marks as `Indexed` or
+/// `Unique`.
+/// Only values stored on the instances themselves are indexed, fields locked by an operative are not.
+/// The tables share structure between clones, so copying the index for a snapshot is cheap.
+#[derive(Debug, Clone, Default)]
+pub struct FieldValueIndex {
+    index_kinds: HashMap<Uid, FieldIndex>,
+    values: im::HashMap<Uid, im::HashMap<PrimitiveValues, im::HashSet<Uid>>>,
+}
+
+impl FieldValueIndex {
+    pub fn new(constraint_schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>) -> Self {
+        let index_kinds = constraint_schema
+            .template_library
+            .values()
+            .flat_map(|template| template.field_constraints.iter())
+            .filter(|(_, field)| field.index != FieldIndex::Unindexed)
+            .map(|(field_id, field)| (*field_id, field.index))
+            .collect::<HashMap<_, _>>();
+        Self {
+            values: index_kinds
+                .keys()
+                .map(|field_id| (*field_id, im::HashMap::new()))
+                .collect(),
+            index_kinds,
+        }
+    }
+    pub fn is_indexed(&self, field_id: &Uid) -> bool {
+        self.index_kinds.contains_key(field_id)
+    }
+    pub fn is_unique(&self, field_id: &Uid) -> bool {
+        self.index_kinds.get(field_id) == Some(&FieldIndex::Unique)
+    }
+    pub fn unique_field_ids(&self) -> Vec<Uid> {
+        self.index_kinds
+            .iter()
+            .filter(|(_, kind)| **kind == FieldIndex::Unique)
+            .map(|(field_id, _)| *field_id)
+            .collect()
+    }
+    /// Ids of all instances which hold the given value, in ascending order
+    pub fn lookup(&self, field_id: &Uid, value: &PrimitiveValues) -> Vec<Uid> {
+        let mut ids = self
+            .values
+            .get(field_id)
+            .and_then(|values| values.get(value))
+            .map(|ids| ids.iter().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        ids.sort();
+        ids
+    }
+    pub fn insert(&mut self, instance_id: Uid, field_id: Uid, value: PrimitiveValues) {
+        if let Some(values) = self.values.get_mut(&field_id) {
+            values.entry(value).or_default().insert(instance_id);
+        }
+    }
+    pub fn remove(&mut self, instance_id: &Uid, field_id: &Uid, value: &PrimitiveValues) {
+        if let Some(values) = self.values.get_mut(field_id) {
+            if let Some(ids) = values.get_mut(value) {
+                ids.remove(instance_id);
+                if ids.is_empty() {
+                    values.remove(value);
+                }
+            }
+        }
+    }
+    pub fn insert_instance(
+        &mut self,
+        instance_id: Uid,
+        fields: impl IntoIterator<Item = (Uid, PrimitiveValues)>,
+    ) {
+        fields
+            .into_iter()
+            .for_each(|(field_id, value)| self.insert(instance_id, field_id, value));
+    }
+    pub fn remove_instance(
+        &mut self,
+        instance_id: &Uid,
+        fields: impl IntoIterator<Item = (Uid, PrimitiveValues)>,
+    ) {
+        fields
+            .into_iter()
+            .for_each(|(field_id, value)| self.remove(instance_id, &field_id, &value));
+    }
+    pub fn clear(&mut self) {
+        self.values.iter_mut().for_each(|(_, values)| values.clear());
+    }
+}
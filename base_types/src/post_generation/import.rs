@@ -0,0 +1,1047 @@
+//! Imports CSV tables or arbitrary JSON into the graph, as described by an [`ImportMapping`].
+//!
+//! Each [`EntityMapping`] turns the records of one source into instances of one operative: columns
+//! are mapped to the operative's fields by name, and links fill the operative's slots with the
+//! instances created for another entity, looked up by that entity's key column. The whole import
+//! is carried out as a single transaction, so it is either applied completely or not at all.
+//!
+//! Instances are named `{entity}:{key}` (or `{entity}#{row}` if the entity has no key) in the
+//! resulting [`crate::post_generation::ExecutionResult`]'s temp ids.
+//!
+//! A mapping for sentences and their words, read from two CSV tables, could look like:
+//!
+//! ```json
+//! {
+//!   "entities": [
+//!     { "name": "sentence", "operative": "Sentence", "source": "sentences", "key": "id",
+//!       "links": [{ "slot": "Elements", "column": "words", "target": "word", "separator": " " }] },
+//!     { "name": "word", "operative": "Word", "source": "words", "key": "id",
+//!       "fields": { "Display": "text" } }
+//!   ]
+//! }
+//! ```
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    common::Uid,
+    constraint_schema::ConstraintSchema,
+    constraint_schema_item::ConstraintSchemaItem,
+    post_generation::{plain::Transaction, ElementCreationError},
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+/// Separator between the items of a list field given as a single cell
+pub const LIST_SEPARATOR: char = ';';
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportMapping {
+    pub entities: Vec<EntityMapping>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EntityMapping {
+    /// Referred to by links and used in the temp ids of the created instances
+    pub name: String,
+    /// Name of the operative to instantiate
+    pub operative: String,
+    /// For CSV input the name of the table, for JSON input a JSON pointer (e.g. `/data/words`) to
+    /// an array of objects
+    pub source: String,
+    /// Column identifying each record, which links from other entities refer to
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Field name to column
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+    #[serde(default)]
+    pub links: Vec<LinkMapping>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LinkMapping {
+    /// Name of the slot to fill
+    pub slot: String,
+    /// Column holding the keys of the records to slot
+    pub column: String,
+    /// Name of the entity the keys belong to
+    pub target: String,
+    /// Splits a single cell into several keys. JSON arrays are always treated as several keys
+    #[serde(default)]
+    pub separator: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ImportInput {
+    /// Table name to CSV text, whose first line is the header
+    Csv(BTreeMap<String, String>),
+    Json(Value),
+}
+
+#[derive(Debug, Clone)]
+pub enum ImportError {
+    DuplicateEntity(String),
+    UnknownEntity {
+        entity: String,
+        target: String,
+    },
+    UnknownOperative {
+        entity: String,
+        operative: String,
+    },
+    UnknownField {
+        entity: String,
+        field: String,
+    },
+    /// The field's value is locked by the operative
+    LockedField {
+        entity: String,
+        field: String,
+    },
+    UnknownSlot {
+        entity: String,
+        slot: String,
+    },
+    MissingSource {
+        entity: String,
+        source: String,
+    },
+    MalformedCsv {
+        table: String,
+        line: usize,
+        message: String,
+    },
+    InvalidValue {
+        entity: String,
+        row: usize,
+        column: String,
+        message: String,
+    },
+    DuplicateKey {
+        entity: String,
+        key: String,
+    },
+    UnknownKey {
+        entity: String,
+        row: usize,
+        target: String,
+        key: String,
+    },
+    Invalid(ElementCreationError),
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::DuplicateEntity(entity) => {
+                write!(f, "entity '{}' is mapped more than once", entity)
+            }
+            ImportError::UnknownEntity { entity, target } => {
+                write!(f, "{}: links to unknown entity '{}'", entity, target)
+            }
+            ImportError::UnknownOperative { entity, operative } => {
+                write!(
+                    f,
+                    "{}: operative '{}' is not in the schema",
+                    entity, operative
+                )
+            }
+            ImportError::UnknownField { entity, field } => {
+                write!(f, "{}: operative has no field '{}'", entity, field)
+            }
+            ImportError::LockedField { entity, field } => {
+                write!(
+                    f,
+                    "{}: field '{}' is locked by the operative",
+                    entity, field
+                )
+            }
+            ImportError::UnknownSlot { entity, slot } => {
+                write!(f, "{}: operative has no slot '{}'", entity, slot)
+            }
+            ImportError::MissingSource { entity, source } => {
+                write!(f, "{}: source '{}' is not in the input", entity, source)
+            }
+            ImportError::MalformedCsv {
+                table,
+                line,
+                message,
+            } => write!(f, "{}, line {}: {}", table, line, message),
+            ImportError::InvalidValue {
+                entity,
+                row,
+                column,
+                message,
+            } => write!(
+                f,
+                "{} row {}, column '{}': {}",
+                entity, row, column, message
+            ),
+            ImportError::DuplicateKey { entity, key } => {
+                write!(f, "{}: key '{}' appears more than once", entity, key)
+            }
+            ImportError::UnknownKey {
+                entity,
+                row,
+                target,
+                key,
+            } => write!(
+                f,
+                "{} row {}: no {} with key '{}'",
+                entity, row, target, key
+            ),
+            ImportError::Invalid(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<ElementCreationError> for ImportError {
+    fn from(value: ElementCreationError) -> Self {
+        ImportError::Invalid(value)
+    }
+}
+
+/// One instance to create, with the input already resolved against the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    pub temp_id: String,
+    pub operative_id: Uid,
+    pub fields: Vec<(Uid, PrimitiveValues)>,
+    /// Slot id to the temp id of the instance to slot
+    pub links: Vec<(Uid, String)>,
+}
+
+type Record = serde_json::Map<String, Value>;
+
+/// Resolves the mapping and input into the instances to create, without touching a graph
+pub fn prepare_import(
+    schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    mapping: &ImportMapping,
+    input: &ImportInput,
+) -> Result<Vec<ImportRow>, ImportError> {
+    let mut entity_records = Vec::new();
+    // Entity name to key to temp id
+    let mut keys: HashMap<&str, HashMap<String, String>> = HashMap::new();
+    for entity in &mapping.entities {
+        if keys.contains_key(entity.name.as_str()) {
+            return Err(ImportError::DuplicateEntity(entity.name.clone()));
+        }
+        let records = read_source(entity, input)?;
+        let mut entity_keys = HashMap::new();
+        let temp_ids = records
+            .iter()
+            .enumerate()
+            .map(|(index, record)| {
+                let Some(key_column) = &entity.key else {
+                    return Ok(format!("{}#{}", entity.name, index + 1));
+                };
+                let key = match cell(record, key_column) {
+                    Value::Null => Err("missing key".to_string()),
+                    value => key_string(value),
+                }
+                .map_err(|message| ImportError::InvalidValue {
+                    entity: entity.name.clone(),
+                    row: index + 1,
+                    column: key_column.clone(),
+                    message,
+                })?;
+                let temp_id = format!("{}:{}", entity.name, key);
+                if entity_keys.insert(key.clone(), temp_id.clone()).is_some() {
+                    return Err(ImportError::DuplicateKey {
+                        entity: entity.name.clone(),
+                        key,
+                    });
+                }
+                Ok(temp_id)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        keys.insert(&entity.name, entity_keys);
+        entity_records.push((entity, records, temp_ids));
+    }
+
+    let mut rows = Vec::new();
+    for (entity, records, temp_ids) in entity_records {
+        let (operative_id, operative) = schema
+            .operative_library
+            .iter()
+            .find(|(_, operative)| operative.tag.name == entity.operative)
+            .ok_or_else(|| ImportError::UnknownOperative {
+                entity: entity.name.clone(),
+                operative: entity.operative.clone(),
+            })?;
+        let template = schema
+            .template_library
+            .get(&operative.template_id)
+            .ok_or_else(|| ImportError::UnknownOperative {
+                entity: entity.name.clone(),
+                operative: entity.operative.clone(),
+            })?;
+        let locked_fields = operative
+            .get_locked_fields_digest(schema)
+            .map(|digest| digest.locked_fields)
+            .unwrap_or_default();
+        let fields = entity
+            .fields
+            .iter()
+            .map(|(field_name, column)| {
+                let field = template
+                    .field_constraints
+                    .values()
+                    .find(|field| &field.tag.name == field_name)
+                    .ok_or_else(|| ImportError::UnknownField {
+                        entity: entity.name.clone(),
+                        field: field_name.clone(),
+                    })?;
+                if locked_fields.contains_key(&field.tag.id) {
+                    return Err(ImportError::LockedField {
+                        entity: entity.name.clone(),
+                        field: field_name.clone(),
+                    });
+                }
+                Ok((field.tag.id, &field.value_type, column))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let links = entity
+            .links
+            .iter()
+            .map(|link| {
+                let slot = template
+                    .operative_slots
+                    .values()
+                    .find(|slot| slot.tag.name == link.slot)
+                    .ok_or_else(|| ImportError::UnknownSlot {
+                        entity: entity.name.clone(),
+                        slot: link.slot.clone(),
+                    })?;
+                let target_keys =
+                    keys.get(link.target.as_str())
+                        .ok_or_else(|| ImportError::UnknownEntity {
+                            entity: entity.name.clone(),
+                            target: link.target.clone(),
+                        })?;
+                Ok::<_, ImportError>((slot.tag.id, link, target_keys))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (index, (record, temp_id)) in records.iter().zip(temp_ids).enumerate() {
+            let row = index + 1;
+            let invalid = |column: &String, message: String| ImportError::InvalidValue {
+                entity: entity.name.clone(),
+                row,
+                column: column.clone(),
+                message,
+            };
+            let field_values = fields
+                .iter()
+                .map(|(field_id, value_type, column)| {
+                    convert_value(cell(record, column), value_type)
+                        .map(|value| (*field_id, value))
+                        .map_err(|message| invalid(column, message))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut slotted = Vec::new();
+            for (slot_id, link, target_keys) in &links {
+                let link_keys = link_keys(cell(record, &link.column), link.separator.as_deref())
+                    .map_err(|message| invalid(&link.column, message))?;
+                for key in link_keys {
+                    let target_temp_id =
+                        target_keys
+                            .get(&key)
+                            .ok_or_else(|| ImportError::UnknownKey {
+                                entity: entity.name.clone(),
+                                row,
+                                target: link.target.clone(),
+                                key: key.clone(),
+                            })?;
+                    slotted.push((*slot_id, target_temp_id.clone()));
+                }
+            }
+            rows.push(ImportRow {
+                temp_id,
+                operative_id: *operative_id,
+                fields: field_values,
+                links: slotted,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+/// The transaction creating the rows, for a [`crate::post_generation::plain::PlainGraphEnvironment`]
+pub fn import_transaction(rows: &[ImportRow]) -> Transaction {
+    let mut transaction = Transaction::with_label("Import");
+    let ids = rows
+        .iter()
+        .map(|row| {
+            let id = transaction.create(row.operative_id);
+            transaction.set_temp_id(id, row.temp_id.clone());
+            row.fields.iter().for_each(|(field_id, value)| {
+                transaction.set_field(id, *field_id, value.clone());
+            });
+            (row.temp_id.as_str(), id)
+        })
+        .collect::<HashMap<_, _>>();
+    rows.iter().for_each(|row| {
+        row.links.iter().for_each(|(slot_id, target)| {
+            transaction.add_edge(ids[row.temp_id.as_str()], *slot_id, ids[target.as_str()]);
+        });
+    });
+    transaction
+}
+
+#[cfg(feature = "reactive")]
+pub use reactive_import::import_into_graph;
+
+#[cfg(feature = "reactive")]
+mod reactive_import {
+    use super::*;
+    use crate::post_generation::{
+        reactive::{
+            hidden::EditRGSO, BlueprintId, BuildByOperative, BuilderVisitor, HasSlotEnum,
+            RBaseGraphEnvironment, RGSOConcrete, RGSOConcreteBuilder, RIntoSchema, RProducable,
+            SubgraphBuilder, TempAddIncomingSlotRef,
+        },
+        ExecutionResult,
+    };
+
+    /// Imports into a reactive graph, building every row with its operative's builder and
+    /// executing them together
+    pub fn import_into_graph<TSchema>(
+        graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<TSchema>>>,
+        mapping: &ImportMapping,
+        input: &ImportInput,
+    ) -> Result<ExecutionResult, ImportError>
+    where
+        TSchema: BuildByOperative + EditRGSO + Send + Sync,
+    {
+        let graph = graph.into();
+        let rows = prepare_import(graph.constraint_schema, mapping, input)?;
+        let Some((first, rest)) = rows.split_first() else {
+            return Ok(ExecutionResult {
+                temp_id_map: HashMap::new(),
+            });
+        };
+        TSchema::build_by_operative(
+            &first.operative_id,
+            graph.clone(),
+            RootVisitor {
+                row: first,
+                rest,
+                graph: &graph,
+            },
+        )
+        .unwrap_or_else(|| Err(unknown_operative(first)))
+    }
+
+    // Only if the graph's constraint schema isn't the one its schema was generated from
+    fn unknown_operative(row: &ImportRow) -> ImportError {
+        ImportError::UnknownOperative {
+            entity: row.temp_id.clone(),
+            operative: row.operative_id.to_string(),
+        }
+    }
+
+    // Temp ids have to be set before any temporary edges are added
+    fn fill<T, TSchema>(builder: &mut SubgraphBuilder<T, TSchema>, row: &ImportRow)
+    where
+        TSchema: EditRGSO + Send + Sync + 'static,
+        T: RIntoSchema<Schema = TSchema>
+            + HasSlotEnum
+            + Send
+            + Sync
+            + Clone
+            + std::fmt::Debug
+            + 'static,
+        <T as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug + Send + Sync,
+        RGSOConcreteBuilder<T, TSchema>: RProducable<RGSOConcrete<T, TSchema>>,
+    {
+        builder.set_temp_id(&row.temp_id);
+        row.fields.iter().for_each(|(field_id, value)| {
+            builder.edit_field(*field_id, value.clone());
+        });
+        row.links.iter().for_each(|(slot_id, target)| {
+            builder.temp_add_incoming(
+                target,
+                TempAddIncomingSlotRef {
+                    host_instance_id: BlueprintId::Temporary(row.temp_id.clone()),
+                    slot_id: *slot_id,
+                },
+            );
+            builder.add_outgoing::<T>(slot_id, BlueprintId::Temporary(target.clone()), None);
+        });
+    }
+
+    struct RootVisitor<'a, TSchema: 'static> {
+        row: &'a ImportRow,
+        rest: &'a [ImportRow],
+        graph: &'a std::sync::Arc<RBaseGraphEnvironment<TSchema>>,
+    }
+
+    impl<TSchema> BuilderVisitor<TSchema> for RootVisitor<'_, TSchema>
+    where
+        TSchema: BuildByOperative + EditRGSO + Send + Sync,
+    {
+        type Output = Result<ExecutionResult, ImportError>;
+
+        fn visit<T>(self, mut builder: SubgraphBuilder<T, TSchema>) -> Self::Output
+        where
+            T: RIntoSchema<Schema = TSchema>
+                + HasSlotEnum
+                + Send
+                + Sync
+                + Clone
+                + std::fmt::Debug
+                + 'static,
+            <T as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug + Send + Sync,
+            RGSOConcreteBuilder<T, TSchema>: RProducable<RGSOConcrete<T, TSchema>>,
+        {
+            fill(&mut builder, self.row);
+            for row in self.rest {
+                TSchema::build_by_operative(
+                    &row.operative_id,
+                    self.graph.clone(),
+                    IncorporateVisitor {
+                        row,
+                        root: &mut builder,
+                    },
+                )
+                .ok_or_else(|| unknown_operative(row))?;
+            }
+            Ok(builder.execute_with_label("Import")?)
+        }
+    }
+
+    struct IncorporateVisitor<'a, R: HasSlotEnum + Clone + std::fmt::Debug, TSchema: 'static>
+    where
+        <R as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug,
+    {
+        row: &'a ImportRow,
+        root: &'a mut SubgraphBuilder<R, TSchema>,
+    }
+
+    impl<R, TSchema> BuilderVisitor<TSchema> for IncorporateVisitor<'_, R, TSchema>
+    where
+        TSchema: EditRGSO + Send + Sync,
+        R: RIntoSchema<Schema = TSchema>
+            + HasSlotEnum
+            + Send
+            + Sync
+            + Clone
+            + std::fmt::Debug
+            + 'static,
+        <R as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug + Send + Sync,
+        RGSOConcreteBuilder<R, TSchema>: RProducable<RGSOConcrete<R, TSchema>>,
+    {
+        type Output = ();
+
+        fn visit<T>(self, mut builder: SubgraphBuilder<T, TSchema>)
+        where
+            T: RIntoSchema<Schema = TSchema>
+                + HasSlotEnum
+                + Send
+                + Sync
+                + Clone
+                + std::fmt::Debug
+                + 'static,
+            <T as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug + Send + Sync,
+            RGSOConcreteBuilder<T, TSchema>: RProducable<RGSOConcrete<T, TSchema>>,
+        {
+            fill(&mut builder, self.row);
+            self.root.incorporate(&builder);
+        }
+    }
+}
+
+fn read_source(entity: &EntityMapping, input: &ImportInput) -> Result<Vec<Record>, ImportError> {
+    let missing = || ImportError::MissingSource {
+        entity: entity.name.clone(),
+        source: entity.source.clone(),
+    };
+    match input {
+        ImportInput::Csv(tables) => {
+            let text = tables.get(&entity.source).ok_or_else(missing)?;
+            parse_csv(text).map_err(|(line, message)| ImportError::MalformedCsv {
+                table: entity.source.clone(),
+                line,
+                message,
+            })
+        }
+        ImportInput::Json(value) => value
+            .pointer(&entity.source)
+            .and_then(Value::as_array)
+            .ok_or_else(missing)?
+            .iter()
+            .enumerate()
+            .map(|(index, record)| {
+                record
+                    .as_object()
+                    .cloned()
+                    .ok_or_else(|| ImportError::InvalidValue {
+                        entity: entity.name.clone(),
+                        row: index + 1,
+                        column: entity.source.clone(),
+                        message: "record is not an object".to_string(),
+                    })
+            })
+            .collect(),
+    }
+}
+
+// Columns starting with '/' are JSON pointers into the record, anything else is a key or header
+fn cell<'a>(record: &'a Record, column: &str) -> &'a Value {
+    let value = if column.starts_with('/') {
+        column
+            .split('/')
+            .skip(1)
+            .try_fold(None::<&Value>, |current, token| {
+                let token = token.replace("~1", "/").replace("~0", "~");
+                match current {
+                    None => record.get(&token),
+                    Some(Value::Object(object)) => object.get(&token),
+                    Some(Value::Array(items)) => {
+                        token.parse::<usize>().ok().and_then(|i| items.get(i))
+                    }
+                    Some(_) => None,
+                }
+                .map(Some)
+            })
+            .flatten()
+    } else {
+        record.get(column)
+    };
+    value.unwrap_or(&Value::Null)
+}
+
+fn key_string(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(val) => Ok(val.clone()),
+        Value::Number(val) => Ok(val.to_string()),
+        Value::Bool(val) => Ok(val.to_string()),
+        _ => Err(format!("{} can't be used as a key", value)),
+    }
+}
+
+fn link_keys(value: &Value, separator: Option<&str>) -> Result<Vec<String>, String> {
+    match value {
+        Value::Null => Ok(Vec::new()),
+        Value::Array(items) => items.iter().map(key_string).collect(),
+        Value::String(val) => Ok(match separator {
+            Some(separator) => val
+                .split(separator)
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string)
+                .collect(),
+            None if val.is_empty() => Vec::new(),
+            None => vec![val.clone()],
+        }),
+        value => key_string(value).map(|key| vec![key]),
+    }
+}
+
+fn convert_value(value: &Value, value_type: &PrimitiveTypes) -> Result<PrimitiveValues, String> {
+    match value_type {
+        PrimitiveTypes::Option(inner) => match value {
+            Value::Null => Ok(PrimitiveValues::Option(Box::new(None))),
+            Value::String(val) if val.is_empty() => Ok(PrimitiveValues::Option(Box::new(None))),
+            value => Ok(PrimitiveValues::Option(Box::new(Some(convert_value(
+                value, inner,
+            )?)))),
+        },
+        PrimitiveTypes::List(inner) => match value {
+            Value::Null => Ok(PrimitiveValues::List(Vec::new())),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| convert_value(item, inner))
+                .collect::<Result<_, _>>()
+                .map(PrimitiveValues::List),
+            Value::String(val) if val.is_empty() => Ok(PrimitiveValues::List(Vec::new())),
+            Value::String(val) => val
+                .split(LIST_SEPARATOR)
+                .map(|item| convert_value(&Value::String(item.to_string()), inner))
+                .collect::<Result<_, _>>()
+                .map(PrimitiveValues::List),
+            value => Ok(PrimitiveValues::List(vec![convert_value(value, inner)?])),
+        },
+        _ if value.is_null() => Err("missing value".to_string()),
+        PrimitiveTypes::String => Ok(PrimitiveValues::String(match value {
+            Value::String(val) => val.clone(),
+            value => value.to_string(),
+        })),
+        PrimitiveTypes::Int => match value {
+            Value::Number(val) => val.as_u64().and_then(|val| u32::try_from(val).ok()),
+            Value::String(val) => val.trim().parse().ok(),
+            _ => None,
+        }
+        .map(PrimitiveValues::Int)
+        .ok_or_else(|| format!("{} is not an Int", value)),
+        PrimitiveTypes::Bool => match value {
+            Value::Bool(val) => Some(*val),
+            Value::String(val) => match val.trim().to_lowercase().as_str() {
+                "true" | "1" => Some(true),
+                "false" | "0" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+        .map(PrimitiveValues::Bool)
+        .ok_or_else(|| format!("{} is not a Bool", value)),
+        PrimitiveTypes::EmptyTuple => {
+            Err("fields of type EmptyTuple can't be imported".to_string())
+        }
+    }
+}
+
+/// Parses RFC 4180 CSV, returning the line and reason on failure
+fn parse_csv(text: &str) -> Result<Vec<Record>, (usize, String)> {
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    let mut cell = String::new();
+    let mut line_number = 1;
+    let mut chars = text.chars().peekable();
+    let mut quoted = false;
+    let mut at_cell_start = true;
+    while let Some(char) = chars.next() {
+        if quoted {
+            match char {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    cell.push('"');
+                }
+                '"' => quoted = false,
+                '\n' => {
+                    line_number += 1;
+                    cell.push(char);
+                }
+                _ => cell.push(char),
+            }
+            continue;
+        }
+        match char {
+            '"' if at_cell_start => {
+                quoted = true;
+                at_cell_start = false;
+            }
+            '"' => return Err((line_number, "unexpected quote".to_string())),
+            ',' => {
+                line.push(std::mem::take(&mut cell));
+                at_cell_start = true;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                line.push(std::mem::take(&mut cell));
+                lines.push((line_number, std::mem::take(&mut line)));
+                line_number += 1;
+                at_cell_start = true;
+            }
+            _ => {
+                cell.push(char);
+                at_cell_start = false;
+            }
+        }
+    }
+    if quoted {
+        return Err((line_number, "unterminated quote".to_string()));
+    }
+    if !at_cell_start || !cell.is_empty() || !line.is_empty() {
+        line.push(cell);
+        lines.push((line_number, line));
+    }
+    // Blank lines, including a trailing one, hold no record
+    lines.retain(|(_, line)| !(line.len() == 1 && line[0].is_empty()));
+
+    let mut lines = lines.into_iter();
+    let Some((_, header)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    lines
+        .map(|(line_number, line)| {
+            if line.len() != header.len() {
+                return Err((
+                    line_number,
+                    format!("expected {} cells, found {}", header.len(), line.len()),
+                ));
+            }
+            Ok(header
+                .iter()
+                .cloned()
+                .zip(line.into_iter().map(Value::String))
+                .collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        post_generation::plain::PlainGraphEnvironment,
+        testing::{LINKS_SLOT_ID, NAME_FIELD_ID, PARTNER_SLOT_ID, RANK_FIELD_ID, TEST_SCHEMA},
+    };
+
+    fn mapping(mapping: Value) -> ImportMapping {
+        serde_json::from_value(mapping).unwrap()
+    }
+    fn csv(tables: &[(&str, &str)]) -> ImportInput {
+        ImportInput::Csv(
+            tables
+                .iter()
+                .map(|(name, text)| (name.to_string(), text.to_string()))
+                .collect(),
+        )
+    }
+    fn node_mapping() -> ImportMapping {
+        mapping(json!({
+            "entities": [{
+                "name": "node", "operative": "Node", "source": "nodes", "key": "id",
+                "fields": { "name": "name" },
+                "links": [{ "slot": "links", "column": "links", "target": "node", "separator": " " }]
+            }]
+        }))
+    }
+    fn name(graph: &PlainGraphEnvironment, id: &Uid) -> PrimitiveValues {
+        graph.get(id).unwrap().fields[&NAME_FIELD_ID].clone()
+    }
+    fn targets(graph: &PlainGraphEnvironment, host_id: &Uid, slot_id: &Uid) -> Vec<Uid> {
+        let mut targets = graph
+            .get(host_id)
+            .unwrap()
+            .outgoing_slots
+            .iter()
+            .filter(|slot_ref| slot_ref.slot_id == *slot_id)
+            .map(|slot_ref| slot_ref.target_instance_id)
+            .collect::<Vec<_>>();
+        targets.sort();
+        targets
+    }
+    fn sorted(mut ids: Vec<Uid>) -> Vec<Uid> {
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn parse_csv_handles_quoted_and_multiline_cells() {
+        let records =
+            parse_csv("id,text\r\n1,\"a, \"\"quoted\"\" cell\"\n2,\"two\nlines\"\n\n3,\n").unwrap();
+        let texts = records
+            .iter()
+            .map(|record| (record["id"].clone(), record["text"].clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                (json!("1"), json!("a, \"quoted\" cell")),
+                (json!("2"), json!("two\nlines")),
+                (json!("3"), json!("")),
+            ]
+        );
+        assert!(parse_csv("").unwrap().is_empty());
+        assert!(parse_csv("id,text").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_csv_reports_the_line_of_malformed_records() {
+        assert_eq!(
+            parse_csv("id,text\n1,\"open").unwrap_err(),
+            (2, "unterminated quote".to_string())
+        );
+        assert_eq!(
+            parse_csv("id,text\n1,a\"b").unwrap_err(),
+            (2, "unexpected quote".to_string())
+        );
+        // Lines are counted within quoted cells as well
+        assert_eq!(
+            parse_csv("id,text\n1,\"two\nlines\"\n2").unwrap_err(),
+            (4, "expected 2 cells, found 1".to_string())
+        );
+    }
+
+    #[test]
+    fn csv_rows_are_linked_by_their_keys() {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let result = graph
+            .import(
+                &node_mapping(),
+                &csv(&[(
+                    "nodes",
+                    "id,name,links\n1,a,2  3\n2,\"b, quoted\",\n3,c,1\n",
+                )]),
+            )
+            .unwrap();
+
+        let [a, b, c] = ["node:1", "node:2", "node:3"].map(|temp_id| result.temp_id_map[temp_id]);
+        assert_eq!(result.temp_id_map.len(), 3);
+        assert_eq!(
+            name(&graph, &b),
+            PrimitiveValues::String("b, quoted".to_string())
+        );
+        assert_eq!(targets(&graph, &a, &LINKS_SLOT_ID), sorted(vec![b, c]));
+        assert!(targets(&graph, &b, &LINKS_SLOT_ID).is_empty());
+        assert_eq!(targets(&graph, &c, &LINKS_SLOT_ID), vec![a]);
+        assert!(graph.check_integrity().is_empty());
+    }
+
+    #[test]
+    fn json_sources_are_read_through_pointers() {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let input = ImportInput::Json(json!({
+            "data": {
+                "nodes": [
+                    { "id": 1, "details": { "name": "a" }, "links": [2] },
+                    { "id": 2, "details": { "name": "b" }, "links": "1" }
+                ],
+                "pairs": [{ "partner": 2 }]
+            }
+        }));
+        let mapping = mapping(json!({
+            "entities": [
+                {
+                    "name": "node", "operative": "Node", "source": "/data/nodes", "key": "id",
+                    "fields": { "name": "/details/name" },
+                    "links": [{ "slot": "links", "column": "links", "target": "node" }]
+                },
+                {
+                    "name": "pair", "operative": "Pair", "source": "/data/pairs",
+                    "links": [{ "slot": "partner", "column": "partner", "target": "node" }]
+                }
+            ]
+        }));
+        let result = graph.import(&mapping, &input).unwrap();
+
+        let a = result.temp_id_map["node:1"];
+        let b = result.temp_id_map["node:2"];
+        let pair = result.temp_id_map["pair#1"];
+        assert_eq!(name(&graph, &a), PrimitiveValues::String("a".to_string()));
+        assert_eq!(targets(&graph, &a, &LINKS_SLOT_ID), vec![b]);
+        assert_eq!(targets(&graph, &b, &LINKS_SLOT_ID), vec![a]);
+        assert_eq!(targets(&graph, &pair, &PARTNER_SLOT_ID), vec![b]);
+    }
+
+    #[test]
+    fn values_which_dont_fit_their_field_are_reported() {
+        let mapping = mapping(json!({
+            "entities": [{
+                "name": "label", "operative": "Label", "source": "labels",
+                "fields": { "text": "text", "rank": "rank" }
+            }]
+        }));
+        let error = |table: &str| {
+            prepare_import(&TEST_SCHEMA, &mapping, &csv(&[("labels", table)])).unwrap_err()
+        };
+        assert!(matches!(
+            error("text,rank\nx,1\ny,high\n"),
+            ImportError::InvalidValue { entity, row: 2, column, message }
+                if entity == "label" && column == "rank" && message == "\"high\" is not an Int"
+        ));
+        assert!(matches!(
+            error("text,rank\nx,-1\n"),
+            ImportError::InvalidValue { row: 1, .. }
+        ));
+        // A column missing from the table has no value
+        assert!(matches!(
+            error("text\nx\n"),
+            ImportError::InvalidValue { column, message, .. }
+                if column == "rank" && message == "missing value"
+        ));
+
+        let rows = prepare_import(
+            &TEST_SCHEMA,
+            &mapping,
+            &csv(&[("labels", "text,rank\nx, 7\n")]),
+        )
+        .unwrap();
+        assert_eq!(rows[0].temp_id, "label#1");
+        assert!(rows[0]
+            .fields
+            .contains(&(RANK_FIELD_ID, PrimitiveValues::Int(7))));
+    }
+
+    #[test]
+    fn mappings_must_match_the_schema_and_input() {
+        let input = csv(&[("nodes", "id,name,links\n1,a,\n1,b,\n")]);
+        let error = |json: Value| prepare_import(&TEST_SCHEMA, &mapping(json), &input).unwrap_err();
+        let entity = |changes: Value| {
+            let mut entity = json!({ "name": "node", "operative": "Node", "source": "nodes" });
+            entity
+                .as_object_mut()
+                .unwrap()
+                .extend(changes.as_object().unwrap().clone());
+            json!({ "entities": [entity] })
+        };
+        assert!(matches!(
+            error(entity(json!({ "operative": "Sentence" }))),
+            ImportError::UnknownOperative { operative, .. } if operative == "Sentence"
+        ));
+        assert!(matches!(
+            error(entity(json!({ "fields": { "display": "name" } }))),
+            ImportError::UnknownField { field, .. } if field == "display"
+        ));
+        assert!(matches!(
+            error(entity(json!({ "links": [{ "slot": "words", "column": "links", "target": "node" }] }))),
+            ImportError::UnknownSlot { slot, .. } if slot == "words"
+        ));
+        assert!(matches!(
+            error(entity(json!({ "links": [{ "slot": "links", "column": "links", "target": "word" }] }))),
+            ImportError::UnknownEntity { target, .. } if target == "word"
+        ));
+        assert!(matches!(
+            error(entity(json!({ "source": "words" }))),
+            ImportError::MissingSource { source, .. } if source == "words"
+        ));
+        assert!(matches!(
+            error(entity(json!({ "key": "id" }))),
+            ImportError::DuplicateKey { key, .. } if key == "1"
+        ));
+        assert!(matches!(
+            error(json!({ "entities": [
+                { "name": "node", "operative": "Node", "source": "nodes" },
+                { "name": "node", "operative": "Node", "source": "nodes" }
+            ] })),
+            ImportError::DuplicateEntity(entity) if entity == "node"
+        ));
+    }
+
+    #[test]
+    fn links_to_unknown_keys_are_rejected() {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let error = graph
+            .import(
+                &node_mapping(),
+                &csv(&[("nodes", "id,name,links\n1,a,2\n2,b,1 9\n")]),
+            )
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ImportError::UnknownKey { entity, row: 2, target, key }
+                if entity == "node" && target == "node" && key == "9"
+        ));
+        assert!(graph.is_empty());
+    }
+
+    #[test]
+    fn an_import_is_undone_in_one_step() {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        graph
+            .import(
+                &node_mapping(),
+                &csv(&[("nodes", "id,name,links\n1,a,2\n2,b,1\n3,c,\n")]),
+            )
+            .unwrap();
+        assert_eq!(graph.len(), 3);
+
+        let undone = graph.undo().unwrap().unwrap();
+        assert_eq!(undone.label.as_deref(), Some("Import"));
+        assert_eq!(undone.deleted_instances.len(), 3);
+        assert!(graph.is_empty());
+        assert!(graph.undo().unwrap().is_none());
+        graph.redo().unwrap();
+        assert_eq!(graph.len(), 3);
+    }
+}
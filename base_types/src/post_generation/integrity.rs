@@ -0,0 +1,707 @@
+//! Checks a set of [`StandaloneRGSOConcrete`]s against the schema before they are loaded into a
+//! graph, e.g. a snapshot which was edited by hand or written by another tool.
+//!
+//! Every instance is checked on its own (operative, fields and slots) and the edges are checked
+//! across instances: each [`SlotRef`] has to be listed both in its host's `outgoing_slots` and in
+//! its target's `incoming_slots`. All problems are collected into an [`IntegrityReport`] rather
+//! than stopping at the first one.
+//!
+//! In repair mode, edges to or from instances which don't exist are dropped and edges listed by
+//! only one of their ends are added to the other, before the instances are checked.
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{
+    common::Uid,
+    constraint_schema::ConstraintSchema,
+    constraint_schema_item::ConstraintSchemaItem,
+    post_generation::{
+        field_index::FieldValueIndex,
+        validation::{check_field_value_type, slot_accepts_operative},
+        ActiveSlot, BoundCheckError, ElementCreationError, RequiredFieldError, SlotRef,
+        StandaloneRGSOConcrete, UniqueFieldError,
+    },
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+#[derive(Debug, Clone)]
+pub enum IntegrityProblem {
+    /// The instance breaks a rule of the schema
+    Invalid(ElementCreationError),
+    DuplicateInstance {
+        id: Uid,
+    },
+    /// The edge refers to an instance which isn't part of the graph
+    DanglingEdge {
+        listed_on: Uid,
+        slot_ref: SlotRef,
+        missing_id: Uid,
+    },
+    /// The edge is listed by one of its ends but not by the other
+    MissingMirror {
+        listed_on: Uid,
+        slot_ref: SlotRef,
+    },
+    /// The edge is listed on an instance which is neither its host nor its target
+    MisplacedEdge {
+        listed_on: Uid,
+        slot_ref: SlotRef,
+    },
+}
+
+impl Display for IntegrityProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityProblem::Invalid(error) => write!(f, "{}", error),
+            IntegrityProblem::DuplicateInstance { id } => {
+                write!(f, "{}: instance appears more than once", id)
+            }
+            IntegrityProblem::DanglingEdge {
+                listed_on,
+                slot_ref,
+                missing_id,
+            } => write!(
+                f,
+                "{}: edge {} -[{}]-> {} refers to missing instance {}",
+                listed_on,
+                slot_ref.host_instance_id,
+                slot_ref.slot_id,
+                slot_ref.target_instance_id,
+                missing_id
+            ),
+            IntegrityProblem::MissingMirror {
+                listed_on,
+                slot_ref,
+            } => write!(
+                f,
+                "{}: edge {} -[{}]-> {} is not listed by its other end",
+                listed_on, slot_ref.host_instance_id, slot_ref.slot_id, slot_ref.target_instance_id
+            ),
+            IntegrityProblem::MisplacedEdge {
+                listed_on,
+                slot_ref,
+            } => write!(
+                f,
+                "{}: edge {} -[{}]-> {} does not belong to this instance",
+                listed_on, slot_ref.host_instance_id, slot_ref.slot_id, slot_ref.target_instance_id
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LoadError {
+    Malformed(String),
+    /// The report lists the problems which prevented loading
+    Invalid(IntegrityReport),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Malformed(message) => write!(f, "Graph data is malformed: {}", message),
+            LoadError::Invalid(report) => {
+                write!(f, "Graph data does not conform to the schema:\n{}", report)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    /// Problems which are left in the instances
+    pub problems: Vec<IntegrityProblem>,
+    /// Problems which were fixed in repair mode
+    pub repaired: Vec<IntegrityProblem>,
+}
+
+impl IntegrityReport {
+    /// Whether the instances can be loaded
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+impl Display for IntegrityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.problems
+            .iter()
+            .try_for_each(|problem| writeln!(f, "{}", problem))?;
+        self.repaired
+            .iter()
+            .try_for_each(|problem| writeln!(f, "repaired: {}", problem))
+    }
+}
+
+/// Checks the instances, first repairing their edges if `repair` is set
+pub fn check_instances(
+    constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    mut instances: Vec<StandaloneRGSOConcrete>,
+    repair: bool,
+) -> (Vec<StandaloneRGSOConcrete>, IntegrityReport) {
+    let mut report = IntegrityReport::default();
+    if repair {
+        report.repaired = repair_edges(&mut instances);
+    }
+    report.problems = find_problems(constraint_schema, &instances);
+    (instances, report)
+}
+
+/// Lists every problem with the instances
+pub fn find_problems(
+    constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    instances: &[StandaloneRGSOConcrete],
+) -> Vec<IntegrityProblem> {
+    let mut problems = Vec::new();
+    let mut by_id = HashMap::new();
+    instances.iter().for_each(|instance| {
+        if by_id.insert(instance.id, instance).is_some() {
+            problems.push(IntegrityProblem::DuplicateInstance { id: instance.id });
+        }
+    });
+    problems.extend(edge_problems(instances, &by_id));
+    instances.iter().for_each(|instance| {
+        problems.extend(
+            instance_errors(constraint_schema, instance, &by_id)
+                .into_iter()
+                .map(IntegrityProblem::Invalid),
+        )
+    });
+    problems.extend(
+        unique_field_errors(constraint_schema, instances)
+            .into_iter()
+            .map(IntegrityProblem::Invalid),
+    );
+    problems
+}
+
+/// Drops edges which refer to missing instances or are listed on the wrong instance, and lists
+/// each remaining edge on both of its ends. Returns the problems which were fixed
+pub fn repair_edges(instances: &mut [StandaloneRGSOConcrete]) -> Vec<IntegrityProblem> {
+    let by_id = instances
+        .iter()
+        .map(|instance| (instance.id, instance))
+        .collect::<HashMap<_, _>>();
+    let repaired = edge_problems(instances, &by_id);
+    let ids = by_id.keys().copied().collect::<HashSet<_>>();
+
+    let mut missing_outgoing = Vec::new();
+    let mut missing_incoming = Vec::new();
+    repaired.iter().for_each(|problem| {
+        if let IntegrityProblem::MissingMirror {
+            listed_on,
+            slot_ref,
+        } = problem
+        {
+            let missing = match *listed_on == slot_ref.host_instance_id {
+                true => &mut missing_incoming,
+                false => &mut missing_outgoing,
+            };
+            if !missing.contains(slot_ref) {
+                missing.push(slot_ref.clone());
+            }
+        }
+    });
+    instances.iter_mut().for_each(|instance| {
+        let id = instance.id;
+        let belongs = |slot_ref: &SlotRef| {
+            ids.contains(&slot_ref.host_instance_id) && ids.contains(&slot_ref.target_instance_id)
+        };
+        instance
+            .outgoing_slots
+            .retain(|slot_ref| slot_ref.host_instance_id == id && belongs(slot_ref));
+        instance
+            .incoming_slots
+            .retain(|slot_ref| slot_ref.target_instance_id == id && belongs(slot_ref));
+        instance.outgoing_slots.extend(
+            missing_outgoing
+                .iter()
+                .filter(|slot_ref| slot_ref.host_instance_id == id)
+                .cloned(),
+        );
+        instance.incoming_slots.extend(
+            missing_incoming
+                .iter()
+                .filter(|slot_ref| slot_ref.target_instance_id == id)
+                .cloned(),
+        );
+    });
+    repaired
+}
+
+fn edge_problems(
+    instances: &[StandaloneRGSOConcrete],
+    by_id: &HashMap<Uid, &StandaloneRGSOConcrete>,
+) -> Vec<IntegrityProblem> {
+    let mut problems = Vec::new();
+    instances.iter().for_each(|instance| {
+        let listed = instance
+            .outgoing_slots
+            .iter()
+            .map(|slot_ref| (slot_ref, true))
+            .chain(
+                instance
+                    .incoming_slots
+                    .iter()
+                    .map(|slot_ref| (slot_ref, false)),
+            );
+        for (slot_ref, is_outgoing) in listed {
+            let (own_end, other_end) = match is_outgoing {
+                true => (slot_ref.host_instance_id, slot_ref.target_instance_id),
+                false => (slot_ref.target_instance_id, slot_ref.host_instance_id),
+            };
+            if own_end != instance.id {
+                problems.push(IntegrityProblem::MisplacedEdge {
+                    listed_on: instance.id,
+                    slot_ref: slot_ref.clone(),
+                });
+                continue;
+            }
+            let Some(other) = by_id.get(&other_end) else {
+                problems.push(IntegrityProblem::DanglingEdge {
+                    listed_on: instance.id,
+                    slot_ref: slot_ref.clone(),
+                    missing_id: other_end,
+                });
+                continue;
+            };
+            let mirrors = match is_outgoing {
+                true => &other.incoming_slots,
+                false => &other.outgoing_slots,
+            };
+            if !mirrors.contains(slot_ref) {
+                problems.push(IntegrityProblem::MissingMirror {
+                    listed_on: instance.id,
+                    slot_ref: slot_ref.clone(),
+                });
+            }
+        }
+    });
+    problems
+}
+
+fn instance_errors(
+    constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    instance: &StandaloneRGSOConcrete,
+    by_id: &HashMap<Uid, &StandaloneRGSOConcrete>,
+) -> Vec<ElementCreationError> {
+    let Some(operative) = constraint_schema.operative_library.get(&instance.operative) else {
+        return vec![ElementCreationError::NonexistentOperative {
+            instance_id: instance.id,
+            operative_id: instance.operative,
+        }];
+    };
+    let template = match constraint_schema.template_library.get(&instance.template) {
+        Some(template) if operative.template_id == instance.template => template,
+        _ => {
+            return vec![ElementCreationError::NonexistentTemplate {
+                instance_id: instance.id,
+                template_id: instance.template,
+            }]
+        }
+    };
+    let mut errors = Vec::new();
+    let locked_fields = operative
+        .get_locked_fields_digest(constraint_schema)
+        .map(|digest| digest.locked_fields)
+        .unwrap_or_default();
+    template
+        .field_constraints
+        .iter()
+        .filter(|(field_id, _)| !locked_fields.contains_key(field_id))
+        .filter(|(field_id, _)| !instance.fields.contains_key(field_id))
+        .for_each(|(field_id, field)| {
+            errors.push(
+                RequiredFieldError {
+                    instance_id: instance.id,
+                    temp_id: None,
+                    operative_id: operative.tag.id,
+                    operative_name: operative.tag.name.clone(),
+                    field_id: *field_id,
+                    field_name: field.tag.name.clone(),
+                }
+                .into(),
+            )
+        });
+    instance.fields.iter().for_each(|(field_id, value)| {
+        if let Err(error) = check_field_value_type(instance.id, template, field_id, value) {
+            errors.push(error);
+        }
+    });
+
+    instance
+        .outgoing_slots
+        .iter()
+        .filter(|slot_ref| slot_ref.host_instance_id == instance.id)
+        .for_each(|slot_ref| {
+            let Some(slot) = template.operative_slots.get(&slot_ref.slot_id) else {
+                return errors.push(ElementCreationError::NonexistentSlot {
+                    instance_id: instance.id,
+                    slot_id: slot_ref.slot_id,
+                });
+            };
+            // Missing targets are reported as dangling edges
+            let Some(target_operative) = by_id
+                .get(&slot_ref.target_instance_id)
+                .and_then(|target| constraint_schema.operative_library.get(&target.operative))
+            else {
+                return;
+            };
+            if !slot_accepts_operative(constraint_schema, slot, target_operative) {
+                let mut expected = constraint_schema
+                    .operative_library
+                    .values()
+                    .filter(|operative| slot_accepts_operative(constraint_schema, slot, operative))
+                    .map(|operative| operative.tag.name.clone())
+                    .collect::<Vec<_>>();
+                expected.sort();
+                errors.push(ElementCreationError::OutgoingElementIsWrongType {
+                    host_id: instance.id,
+                    slot_id: slot.tag.id,
+                    target_id: slot_ref.target_instance_id,
+                    expected,
+                    received: target_operative.tag.name.clone(),
+                });
+            }
+        });
+    template.operative_slots.iter().for_each(|(slot_id, slot)| {
+        let active_slot = ActiveSlot {
+            slot,
+            slotted_instances: instance
+                .outgoing_slots
+                .iter()
+                .filter(|slot_ref| {
+                    slot_ref.slot_id == *slot_id && slot_ref.host_instance_id == instance.id
+                })
+                .map(|slot_ref| slot_ref.target_instance_id)
+                .collect(),
+        };
+        if !active_slot.check_current_conformity() {
+            errors.push(
+                BoundCheckError {
+                    instance_id: instance.id,
+                    temp_id: None,
+                    operative_id: operative.tag.id,
+                    operative_name: operative.tag.name.clone(),
+                    slot_id: *slot_id,
+                    slot_name: slot.tag.name.clone(),
+                    bounds: slot.bounds.clone(),
+                    attempted: active_slot.slotted_instances.len(),
+                }
+                .into(),
+            );
+        }
+    });
+    errors
+}
+
+fn unique_field_errors(
+    constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    instances: &[StandaloneRGSOConcrete],
+) -> Vec<ElementCreationError> {
+    let mut index = FieldValueIndex::new(constraint_schema);
+    let mut errors = Vec::new();
+    for instance in instances {
+        for (field_id, value) in &instance.fields {
+            if !index.is_unique(field_id) {
+                continue;
+            }
+            if let Some(conflicting_id) = index.lookup(field_id, value).first() {
+                errors.push(
+                    UniqueFieldError {
+                        instance_id: instance.id,
+                        temp_id: None,
+                        field_id: *field_id,
+                        field_name: constraint_schema
+                            .template_library
+                            .get(&instance.template)
+                            .and_then(|template| template.field_constraints.get(field_id))
+                            .map(|field| field.tag.name.clone())
+                            .unwrap_or_default(),
+                        value: value.clone(),
+                        conflicting_id: *conflicting_id,
+                    }
+                    .into(),
+                );
+                continue;
+            }
+            index.insert(instance.id, *field_id, value.clone());
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        LABEL_OPERATIVE_ID, LABEL_TEMPLATE_ID, LINKS_SLOT_ID, NAME_FIELD_ID, NODE_OPERATIVE_ID,
+        NODE_TEMPLATE_ID, PAIR_OPERATIVE_ID, PAIR_TEMPLATE_ID, PARTNER_SLOT_ID, RANK_FIELD_ID,
+        TEST_SCHEMA, TEXT_FIELD_ID,
+    };
+
+    fn instance(
+        id: Uid,
+        operative: Uid,
+        template: Uid,
+        fields: Vec<(Uid, PrimitiveValues)>,
+    ) -> StandaloneRGSOConcrete {
+        StandaloneRGSOConcrete {
+            id,
+            fields: fields.into_iter().collect(),
+            outgoing_slots: Vec::new(),
+            incoming_slots: Vec::new(),
+            operative,
+            template,
+        }
+    }
+    fn node(id: Uid, name: &str) -> StandaloneRGSOConcrete {
+        let name = PrimitiveValues::String(name.to_string());
+        instance(
+            id,
+            NODE_OPERATIVE_ID,
+            NODE_TEMPLATE_ID,
+            vec![(NAME_FIELD_ID, name)],
+        )
+    }
+    fn label(id: Uid, text: &str) -> StandaloneRGSOConcrete {
+        let fields = vec![
+            (TEXT_FIELD_ID, PrimitiveValues::String(text.to_string())),
+            (RANK_FIELD_ID, PrimitiveValues::Int(1)),
+        ];
+        instance(id, LABEL_OPERATIVE_ID, LABEL_TEMPLATE_ID, fields)
+    }
+    fn edge(host_instance_id: Uid, slot_id: Uid, target_instance_id: Uid) -> SlotRef {
+        SlotRef {
+            host_instance_id,
+            target_instance_id,
+            slot_id,
+        }
+    }
+    fn get(instances: &mut [StandaloneRGSOConcrete], id: Uid) -> &mut StandaloneRGSOConcrete {
+        instances
+            .iter_mut()
+            .find(|instance| instance.id == id)
+            .unwrap()
+    }
+    // Lists the edge on both of its ends
+    fn connect(instances: &mut [StandaloneRGSOConcrete], slot_ref: SlotRef) {
+        get(instances, slot_ref.host_instance_id)
+            .outgoing_slots
+            .push(slot_ref.clone());
+        get(instances, slot_ref.target_instance_id)
+            .incoming_slots
+            .push(slot_ref);
+    }
+    // Node 1 links to node 2, pair 3 is partnered with node 1 and label 4 stands on its own
+    fn instances() -> Vec<StandaloneRGSOConcrete> {
+        let mut instances = vec![
+            node(1, "a"),
+            node(2, "b"),
+            instance(3, PAIR_OPERATIVE_ID, PAIR_TEMPLATE_ID, Vec::new()),
+            label(4, "first"),
+        ];
+        connect(&mut instances, edge(1, LINKS_SLOT_ID, 2));
+        connect(&mut instances, edge(3, PARTNER_SLOT_ID, 1));
+        instances
+    }
+    fn edges(instances: &[StandaloneRGSOConcrete]) -> Vec<(Uid, Vec<SlotRef>, Vec<SlotRef>)> {
+        instances
+            .iter()
+            .map(|instance| {
+                (
+                    instance.id,
+                    instance.outgoing_slots.clone(),
+                    instance.incoming_slots.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn consistent_instances_have_no_problems() {
+        let mut instances = instances();
+        assert!(find_problems(&TEST_SCHEMA, &instances).is_empty());
+        let before = edges(&instances);
+        assert!(repair_edges(&mut instances).is_empty());
+        assert_eq!(edges(&instances), before);
+    }
+
+    #[test]
+    fn edges_listed_by_one_end_are_mirrored_onto_the_other() {
+        let mut instances = instances();
+        get(&mut instances, 2).incoming_slots.clear();
+        get(&mut instances, 3).outgoing_slots.clear();
+
+        let problems = find_problems(&TEST_SCHEMA, &instances);
+        assert!(problems.iter().any(|problem| matches!(
+            problem,
+            IntegrityProblem::MissingMirror { listed_on: 1, slot_ref }
+                if *slot_ref == edge(1, LINKS_SLOT_ID, 2)
+        )));
+        assert!(problems.iter().any(|problem| matches!(
+            problem,
+            IntegrityProblem::MissingMirror { listed_on: 1, slot_ref }
+                if *slot_ref == edge(3, PARTNER_SLOT_ID, 1)
+        )));
+        // Without its outgoing edge the pair has no partner
+        assert!(problems.iter().any(|problem| matches!(
+            problem,
+            IntegrityProblem::Invalid(ElementCreationError::BoundCheckOutOfRange(error))
+                if error.instance_id == 3 && error.slot_id == PARTNER_SLOT_ID
+        )));
+
+        assert_eq!(repair_edges(&mut instances).len(), 2);
+        assert_eq!(edges(&instances), edges(&self::instances()));
+        assert!(find_problems(&TEST_SCHEMA, &instances).is_empty());
+    }
+
+    #[test]
+    fn edges_to_missing_or_unrelated_instances_are_dropped() {
+        let mut instances = instances();
+        get(&mut instances, 1)
+            .outgoing_slots
+            .push(edge(1, LINKS_SLOT_ID, 9));
+        get(&mut instances, 4)
+            .incoming_slots
+            .push(edge(1, LINKS_SLOT_ID, 2));
+
+        let problems = find_problems(&TEST_SCHEMA, &instances);
+        assert!(matches!(
+            problems.as_slice(),
+            [
+                IntegrityProblem::DanglingEdge {
+                    listed_on: 1,
+                    missing_id: 9,
+                    ..
+                },
+                IntegrityProblem::MisplacedEdge { listed_on: 4, .. },
+            ]
+        ));
+        assert_eq!(repair_edges(&mut instances).len(), 2);
+        assert_eq!(edges(&instances), edges(&self::instances()));
+    }
+
+    #[test]
+    fn missing_required_fields_are_reported() {
+        let mut instances = instances();
+        get(&mut instances, 2).fields.clear();
+        get(&mut instances, 4).fields.remove(&RANK_FIELD_ID);
+
+        let problems = find_problems(&TEST_SCHEMA, &instances);
+        let missing = problems
+            .iter()
+            .map(|problem| match problem {
+                IntegrityProblem::Invalid(ElementCreationError::RequiredFieldIsEmpty(error)) => {
+                    (error.instance_id, error.field_id)
+                }
+                problem => panic!("unexpected problem: {}", problem),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(missing, vec![(2, NAME_FIELD_ID), (4, RANK_FIELD_ID)]);
+    }
+
+    #[test]
+    fn unique_values_and_ids_may_only_appear_once() {
+        let mut instances = instances();
+        instances.push(label(5, "second"));
+        instances.push(label(6, "first"));
+        instances.push(node(2, "b again"));
+
+        let problems = find_problems(&TEST_SCHEMA, &instances);
+        assert!(problems
+            .iter()
+            .any(|problem| matches!(problem, IntegrityProblem::DuplicateInstance { id: 2 })));
+        let clashes = problems
+            .iter()
+            .filter_map(|problem| match problem {
+                IntegrityProblem::Invalid(ElementCreationError::UniqueFieldViolation(error)) => {
+                    Some((error.instance_id, error.field_id, error.conflicting_id))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(clashes, vec![(6, TEXT_FIELD_ID, 4)]);
+    }
+
+    #[test]
+    fn repairing_twice_changes_nothing_more() {
+        let mut instances = instances();
+        get(&mut instances, 2).incoming_slots.clear();
+        get(&mut instances, 2)
+            .outgoing_slots
+            .push(edge(2, LINKS_SLOT_ID, 9));
+        get(&mut instances, 4)
+            .outgoing_slots
+            .push(edge(3, PARTNER_SLOT_ID, 1));
+
+        assert_eq!(repair_edges(&mut instances).len(), 3);
+        let repaired = edges(&instances);
+        assert!(repair_edges(&mut instances).is_empty());
+        assert_eq!(edges(&instances), repaired);
+    }
+
+    #[test]
+    fn instances_are_only_repaired_if_asked() {
+        let broken = || {
+            let mut instances = instances();
+            get(&mut instances, 2).incoming_slots.clear();
+            instances
+        };
+        let (_, report) = check_instances(&TEST_SCHEMA, broken(), false);
+        assert!(!report.is_valid());
+        assert!(report.repaired.is_empty());
+
+        let (instances, report) = check_instances(&TEST_SCHEMA, broken(), true);
+        assert!(report.is_valid());
+        assert_eq!(report.repaired.len(), 1);
+        assert_eq!(edges(&instances), edges(&self::instances()));
+    }
+
+    #[cfg(feature = "reactive")]
+    #[test]
+    fn validated_graphs_report_problems_instead_of_panicking() {
+        use crate::{
+            post_generation::journal::instances_to_json,
+            testing::{name, TestSchema},
+            utils::initialize_graph_validated,
+        };
+        let load = |instances: &[StandaloneRGSOConcrete], repair: bool| {
+            let json = instances_to_json(instances, Default::default()).unwrap();
+            initialize_graph_validated::<TestSchema>(&TEST_SCHEMA, &json, repair)
+        };
+
+        assert!(matches!(
+            initialize_graph_validated::<TestSchema>(&TEST_SCHEMA, "[{", false),
+            Err(LoadError::Malformed(_))
+        ));
+
+        let mut broken = instances();
+        get(&mut broken, 1)
+            .outgoing_slots
+            .push(edge(1, LINKS_SLOT_ID, 9));
+        get(&mut broken, 2).fields.clear();
+        let Err(LoadError::Invalid(report)) = load(&broken, false) else {
+            panic!("broken instances were loaded");
+        };
+        assert_eq!(report.problems.len(), 2);
+        // Repairing drops the dangling edge but can't fill in the name
+        let Err(LoadError::Invalid(report)) = load(&broken, true) else {
+            panic!("instances without a name were loaded");
+        };
+        assert!(matches!(
+            report.problems.as_slice(),
+            [IntegrityProblem::Invalid(
+                ElementCreationError::RequiredFieldIsEmpty(_)
+            )]
+        ));
+        assert_eq!(report.repaired.len(), 1);
+
+        get(&mut broken, 2).fields = node(2, "b").fields;
+        let (graph, report) = load(&broken, true).unwrap();
+        assert_eq!(report.repaired.len(), 1);
+        assert_eq!(name(&graph, &2).as_deref(), Some("b"));
+    }
+}
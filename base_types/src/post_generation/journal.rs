@@ -0,0 +1,491 @@
+use std::{
+    ffi::OsString,
+    fmt::Display,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "reactive")]
+use crate::post_generation::reactive::{from_reactive::FromStandalone, Blueprint, SharedGraph};
+use crate::post_generation::{
+    ElementCreationError, IdFormat, StandaloneBlueprint, StandaloneRGSOConcrete,
+    StrStandaloneRGSOConcrete,
+};
+
+#[derive(Debug)]
+pub enum JournalError {
+    Io(std::io::Error),
+    Serialization(String),
+    MalformedSnapshot(String),
+    InvalidSnapshot(ElementCreationError),
+    MalformedEntry {
+        line: usize,
+        message: String,
+    },
+    Replay {
+        entry: usize,
+        error: Box<ElementCreationError>,
+    },
+    /// The journal was started by compacting into another snapshot than the one it is read with
+    SnapshotMismatch,
+}
+
+impl Display for JournalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JournalError::Io(error) => write!(f, "Journal could not be accessed: {}", error),
+            JournalError::Serialization(message) => {
+                write!(f, "Journal entry could not be serialized: {}", message)
+            }
+            JournalError::MalformedSnapshot(message) => {
+                write!(f, "Snapshot is malformed: {}", message)
+            }
+            JournalError::InvalidSnapshot(error) => {
+                write!(f, "Snapshot does not conform to the schema:\n{}", error)
+            }
+            JournalError::MalformedEntry { line, message } => {
+                write!(
+                    f,
+                    "Journal entry on line {} is malformed: {}",
+                    line, message
+                )
+            }
+            JournalError::Replay { entry, error } => {
+                write!(
+                    f,
+                    "Journal entry {} could not be replayed:\n{}",
+                    entry, error
+                )
+            }
+            JournalError::SnapshotMismatch => {
+                write!(f, "Journal doesn't continue the given snapshot")
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for JournalError {
+    fn from(value: std::io::Error) -> Self {
+        JournalError::Io(value)
+    }
+}
+
+/// First line of a journal started by a compaction, naming the snapshot it continues
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JournalHeader {
+    snapshot: String,
+}
+
+/// Identifies the contents of a snapshot, so that a journal can tell whether it continues it
+pub fn snapshot_fingerprint(json: &str) -> String {
+    // FNV-1a, which unlike the std hashers is stable across Rust versions
+    let hash = json.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Append-only file holding one [`StandaloneBlueprint`] per line, in the order they were committed
+#[derive(Debug)]
+pub struct BlueprintJournal {
+    path: PathBuf,
+    file: File,
+}
+
+impl BlueprintJournal {
+    /// Opens the journal at the given path, creating it if it doesn't exist yet. A last entry which
+    /// was cut short is removed, so that the next one starts on a line of its own
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, JournalError> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let contents = std::fs::read(&path)?;
+        let complete = complete_length(&contents);
+        if complete < contents.len() {
+            file.set_len(complete as u64)?;
+        }
+        Ok(Self { path, file })
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    pub fn append(&mut self, blueprint: &StandaloneBlueprint) -> Result<(), JournalError> {
+        let mut line = serde_json::to_string(blueprint)
+            .map_err(|error| JournalError::Serialization(error.to_string()))?;
+        line.push('\n');
+        let length = self.file.metadata()?.len();
+        if let Err(error) = self
+            .file
+            .write_all(line.as_bytes())
+            .and_then(|_| self.file.flush())
+        {
+            // A partially written line would make every later entry unreadable
+            let _ = self.file.set_len(length);
+            return Err(error.into());
+        }
+        Ok(())
+    }
+    /// Discards every entry, e.g. once they have been folded into a snapshot
+    pub fn truncate(&mut self) -> Result<(), JournalError> {
+        self.file.set_len(0)?;
+        Ok(())
+    }
+    /// Reads all entries of the journal at the given path. A missing file is an empty journal.
+    /// A last line without its newline is skipped: the process stopped while appending it, before
+    /// the blueprint was applied.
+    pub fn read(path: impl AsRef<Path>) -> Result<Vec<StandaloneBlueprint>, JournalError> {
+        Ok(Self::read_with_header(path)?.1)
+    }
+    /// The entries to replay over the snapshot with the given fingerprint (`None` if there is no
+    /// snapshot). A compaction which was interrupted after writing its snapshot is completed, as
+    /// the entries of the old journal are all contained in the snapshot.
+    pub fn recover(
+        path: impl AsRef<Path>,
+        snapshot: Option<&str>,
+    ) -> Result<Vec<StandaloneBlueprint>, JournalError> {
+        let path = path.as_ref();
+        let next_path = next_path(path);
+        let (next_header, _) = Self::read_with_header(&next_path)?;
+        if next_header.is_some() && next_header.as_deref() == snapshot {
+            std::fs::rename(&next_path, path)?;
+            return Ok(Vec::new());
+        }
+        let (header, entries) = Self::read_with_header(path)?;
+        match header {
+            Some(header) if Some(header.as_str()) != snapshot => {
+                Err(JournalError::SnapshotMismatch)
+            }
+            _ => Ok(entries),
+        }
+    }
+    fn read_with_header(
+        path: impl AsRef<Path>,
+    ) -> Result<(Option<String>, Vec<StandaloneBlueprint>), JournalError> {
+        let contents = match std::fs::read(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok((None, Vec::new()))
+            }
+            Err(error) => return Err(error.into()),
+        };
+        let complete = std::str::from_utf8(&contents[..complete_length(&contents)])
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+        let mut header = None;
+        let mut entries = Vec::new();
+        for (index, line) in complete.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if index == 0 {
+                if let Ok(JournalHeader { snapshot }) = serde_json::from_str(line) {
+                    header = Some(snapshot);
+                    continue;
+                }
+            }
+            entries.push(serde_json::from_str(line).map_err(|error| {
+                JournalError::MalformedEntry {
+                    line: index + 1,
+                    message: error.to_string(),
+                }
+            })?);
+        }
+        Ok((header, entries))
+    }
+    /// Writes the empty journal which continues the snapshot with the given fingerprint next to
+    /// this one, to take its place with [`Self::finish_rotation`] once the snapshot is written
+    #[cfg(feature = "reactive")]
+    pub(crate) fn prepare_rotation(&self, snapshot: &str) -> Result<(), JournalError> {
+        let mut header = serde_json::to_string(&JournalHeader {
+            snapshot: snapshot.to_string(),
+        })
+        .map_err(|error| JournalError::Serialization(error.to_string()))?;
+        header.push('\n');
+        let mut file = File::create(next_path(&self.path))?;
+        file.write_all(header.as_bytes())?;
+        file.sync_all()?;
+        Ok(())
+    }
+    #[cfg(feature = "reactive")]
+    pub(crate) fn finish_rotation(&mut self) -> Result<(), JournalError> {
+        std::fs::rename(next_path(&self.path), &self.path)?;
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        Ok(())
+    }
+}
+
+// The length of the lines which were written in full. Anything after the last newline was cut
+// short while it was being appended
+fn complete_length(contents: &[u8]) -> usize {
+    contents
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |index| index + 1)
+}
+
+fn next_path(path: &Path) -> PathBuf {
+    let mut next_path = OsString::from(path);
+    next_path.push(".next");
+    next_path.into()
+}
+
+#[cfg(feature = "reactive")]
+/// A journal attached to a graph environment, see
+/// [`crate::post_generation::reactive::RBaseGraphEnvironment::attach_journal`]
+#[derive(Debug)]
+pub struct JournalWriter<TSchema> {
+    pub(crate) journal: BlueprintJournal,
+    to_standalone: fn(TSchema) -> StandaloneRGSOConcrete,
+}
+#[cfg(feature = "reactive")]
+impl<TSchema: Clone> JournalWriter<TSchema> {
+    pub(crate) fn new(journal: BlueprintJournal) -> Self
+    where
+        TSchema: Into<StandaloneRGSOConcrete>,
+    {
+        Self {
+            journal,
+            to_standalone: TSchema::into,
+        }
+    }
+    pub(crate) fn write(&mut self, blueprints: &[Blueprint<TSchema>]) -> Result<(), JournalError> {
+        blueprints.iter().try_for_each(|blueprint| {
+            self.journal.append(&StandaloneBlueprint::from_blueprint(
+                blueprint,
+                self.to_standalone,
+            ))
+        })
+    }
+    pub(crate) fn into_journal(self) -> BlueprintJournal {
+        self.journal
+    }
+}
+
+#[cfg(feature = "reactive")]
+/// Applies each blueprint to the graph in order, without adding them to the undo history
+pub fn replay<TSchema>(
+    graph: &SharedGraph<TSchema>,
+    blueprints: Vec<StandaloneBlueprint>,
+) -> Result<(), JournalError>
+where
+    TSchema: crate::post_generation::reactive::hidden::EditRGSO<Schema = TSchema>
+        + FromStandalone<Schema = TSchema>
+        + Send
+        + Sync,
+{
+    blueprints
+        .into_iter()
+        .enumerate()
+        .try_for_each(|(index, standalone)| {
+            Blueprint::from_standalone(standalone, std::sync::Arc::clone(graph).into())
+                .and_then(|blueprint| graph.process_blueprint(blueprint))
+                .map_err(|error| JournalError::Replay {
+                    entry: index + 1,
+                    error: Box::new(error),
+                })
+        })
+}
+
+/// Writes the given instances as a snapshot, replacing the file at `path` only once the new
+/// snapshot is complete
+pub fn write_snapshot(
+    path: impl AsRef<Path>,
+    instances: &[StandaloneRGSOConcrete],
+) -> Result<(), JournalError> {
+    write_snapshot_as(path, instances, IdFormat::Numeric)
+}
+/// Same as [`write_snapshot`], writing ids in the given format
+pub fn write_snapshot_as(
+    path: impl AsRef<Path>,
+    instances: &[StandaloneRGSOConcrete],
+    format: IdFormat,
+) -> Result<(), JournalError> {
+    let json = instances_to_json(instances, format)
+        .map_err(|error| JournalError::Serialization(error.to_string()))?;
+    write_snapshot_json(path, &json)
+}
+pub(crate) fn write_snapshot_json(path: impl AsRef<Path>, json: &str) -> Result<(), JournalError> {
+    let path = path.as_ref();
+    let temp_path = path.with_extension("tmp");
+    let mut file = File::create(&temp_path)?;
+    file.write_all(json.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+pub fn instances_to_json(
+    instances: &[StandaloneRGSOConcrete],
+    format: IdFormat,
+) -> serde_json::Result<String> {
+    match format {
+        IdFormat::Numeric => serde_json::to_string(instances),
+        IdFormat::Uuid => serde_json::to_string(
+            &instances
+                .iter()
+                .cloned()
+                .map(StrStandaloneRGSOConcrete::from)
+                .collect::<Vec<_>>(),
+        ),
+    }
+}
+/// Reads instances written in either [`IdFormat`]
+pub fn instances_from_json(json: &str) -> serde_json::Result<Vec<StandaloneRGSOConcrete>> {
+    from_json_in_either_format(json, |instances: Vec<StrStandaloneRGSOConcrete>| {
+        instances.into_iter().map(Into::into).collect()
+    })
+}
+// Reads JSON written in either `IdFormat`, `from_uuid` converts what was read with UUID ids
+pub(crate) fn from_json_in_either_format<T, TUuid>(
+    json: &str,
+    from_uuid: impl FnOnce(TUuid) -> T,
+) -> serde_json::Result<T>
+where
+    T: serde::de::DeserializeOwned,
+    TUuid: serde::de::DeserializeOwned,
+{
+    // A u128 can't be told apart from a string without reading it as one or the other, so each
+    // format is tried in turn. If neither fits, the error which got further is the relevant one
+    let numeric_error = match serde_json::from_str(json) {
+        Ok(value) => return Ok(value),
+        Err(error) => error,
+    };
+    match serde_json::from_str::<TUuid>(json) {
+        Ok(value) => Ok(from_uuid(value)),
+        Err(uuid_error)
+            if (uuid_error.line(), uuid_error.column())
+                > (numeric_error.line(), numeric_error.column()) =>
+        {
+            Err(uuid_error)
+        }
+        Err(_) => Err(numeric_error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A directory of its own for each test
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "base_types_journal_{}_{}",
+            std::process::id(),
+            test
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    fn labelled(label: &str) -> StandaloneBlueprint {
+        StandaloneBlueprint {
+            label: Some(label.to_string()),
+            ..Default::default()
+        }
+    }
+    fn labels(path: &Path) -> Vec<String> {
+        BlueprintJournal::read(path)
+            .unwrap()
+            .into_iter()
+            .filter_map(|blueprint| blueprint.label)
+            .collect()
+    }
+    // As if the process stopped while appending another entry
+    fn tear(path: &Path) {
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(br#"{"added_instances":[{"id":12"#).unwrap();
+    }
+
+    #[test]
+    fn entries_cut_short_are_skipped_and_then_removed() {
+        let path = temp_dir("entries_cut_short").join("journal");
+        let mut journal = BlueprintJournal::open(&path).unwrap();
+        journal.append(&labelled("first")).unwrap();
+        journal.append(&labelled("second")).unwrap();
+        drop(journal);
+        tear(&path);
+        assert_eq!(labels(&path), vec!["first", "second"]);
+
+        let mut journal = BlueprintJournal::open(&path).unwrap();
+        journal.append(&labelled("third")).unwrap();
+        assert_eq!(labels(&path), vec!["first", "second", "third"]);
+    }
+
+    #[test]
+    fn malformed_entries_before_the_last_line_fail_the_read() {
+        let path = temp_dir("malformed_entries").join("journal");
+        let mut journal = BlueprintJournal::open(&path).unwrap();
+        journal.append(&labelled("first")).unwrap();
+        tear(&path);
+        drop(journal);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"\n").unwrap();
+        assert!(matches!(
+            BlueprintJournal::read(&path),
+            Err(JournalError::MalformedEntry { line: 2, .. })
+        ));
+    }
+
+    #[cfg(feature = "reactive")]
+    mod reactive {
+        use super::*;
+        use crate::{
+            post_generation::reactive::SharedGraph,
+            testing::{create_node, link, normalized, rename, TestSchema, TEST_SCHEMA},
+            utils::initialize_graph_from_journal,
+        };
+
+        fn load(dir: &Path) -> SharedGraph<TestSchema> {
+            initialize_graph_from_journal(
+                &TEST_SCHEMA,
+                dir.join("snapshot.json"),
+                dir.join("journal"),
+            )
+            .unwrap()
+        }
+
+        #[test]
+        fn graphs_are_replayed_up_to_an_entry_cut_short() {
+            let dir = temp_dir("replayed_up_to_an_entry_cut_short");
+            let graph = load(&dir);
+            let a = create_node(&graph, "a").unwrap();
+            let b = create_node(&graph, "b").unwrap();
+            link(&graph, &a, &b).unwrap();
+            let expected = normalized(&graph);
+            drop(graph);
+            tear(&dir.join("journal"));
+
+            let graph = load(&dir);
+            assert_eq!(normalized(&graph), expected);
+            rename(&graph, &b, "c").unwrap();
+            let expected = normalized(&graph);
+            drop(graph);
+            assert_eq!(normalized(&load(&dir)), expected);
+        }
+
+        #[test]
+        fn compacted_graphs_load_the_same() {
+            let dir = temp_dir("compacted_graphs_load_the_same");
+            let graph = load(&dir);
+            let a = create_node(&graph, "a").unwrap();
+            let b = create_node(&graph, "b").unwrap();
+            link(&graph, &a, &b).unwrap();
+            rename(&graph, &a, "c").unwrap();
+            let expected = normalized(&graph);
+
+            graph.compact_journal(dir.join("snapshot.json")).unwrap();
+            assert!(BlueprintJournal::read(dir.join("journal"))
+                .unwrap()
+                .is_empty());
+            assert_eq!(normalized(&load(&dir)), expected);
+
+            // The graph keeps journaling on top of the new snapshot
+            link(&graph, &b, &a).unwrap();
+            let expected = normalized(&graph);
+            drop(graph);
+            assert_eq!(normalized(&load(&dir)), expected);
+        }
+    }
+}
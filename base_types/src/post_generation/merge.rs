@@ -0,0 +1,555 @@
+//! Merging of blueprints committed on other replicas of the same graph, so that replicas which
+//! have received each other's blueprints end up in the same state.
+//!
+//! Every blueprint committed on a replica is stamped with a Lamport clock. Field edits are
+//! resolved last-writer-wins by that stamp, each edge is added or removed by whichever change to
+//! it has the latest stamp, and deleting an instance wins over any concurrent change to it.
+//! Blueprints from the same replica must be merged in the order they were committed.
+use std::collections::{HashMap, HashSet};
+
+use leptos::prelude::GetUntracked;
+use serde::{Deserialize, Serialize};
+
+use crate::common::Uid;
+use crate::post_generation::{
+    change_set::ChangeOrigin,
+    reactive::{
+        from_reactive::FromStandalone, hidden::EditRGSO, Blueprint, RBaseGraphEnvironment,
+        RGraphEnvironment, SharedGraph,
+    },
+    BoundCheckError, ElementCreationError, HistoryFieldEdit, SlotRef, StandaloneBlueprint,
+    StandaloneRGSOConcrete,
+};
+
+/// Orders the blueprints of all replicas, ties between replicas are broken by their id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Stamp {
+    pub clock: u64,
+    pub replica_id: Uid,
+}
+
+/// A blueprint committed on one replica, as it is sent to the others
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicatedBlueprint {
+    pub stamp: Stamp,
+    pub blueprint: StandaloneBlueprint,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MergeReport {
+    /// Number of blueprints which hadn't been merged before
+    pub merged: usize,
+    /// Slots which the merge has left outside of their bounds, e.g. because two replicas each
+    /// filled the last place in the same slot
+    pub bound_violations: Vec<ElementCreationError>,
+}
+
+/// The merge state of a graph environment, see [`RBaseGraphEnvironment::attach_replica`]
+#[derive(Debug)]
+pub struct Replica<TSchema> {
+    replica_id: Uid,
+    clock: u64,
+    outgoing: Vec<ReplicatedBlueprint>,
+    // The latest clock merged from each other replica
+    merged_clocks: HashMap<Uid, u64>,
+    field_stamps: HashMap<(Uid, Uid), Stamp>,
+    edge_stamps: HashMap<SlotRef, Stamp>,
+    deleted: HashSet<Uid>,
+    to_standalone: fn(TSchema) -> StandaloneRGSOConcrete,
+}
+
+impl<TSchema: Clone> Replica<TSchema> {
+    fn new(replica_id: Uid) -> Self
+    where
+        TSchema: Into<StandaloneRGSOConcrete>,
+    {
+        Self {
+            replica_id,
+            clock: 0,
+            outgoing: Vec::new(),
+            merged_clocks: HashMap::new(),
+            field_stamps: HashMap::new(),
+            edge_stamps: HashMap::new(),
+            deleted: HashSet::new(),
+            to_standalone: TSchema::into,
+        }
+    }
+    pub fn replica_id(&self) -> Uid {
+        self.replica_id
+    }
+    /// Stamps blueprints committed on this replica and queues them to be sent to the others
+    pub(crate) fn record_local(&mut self, blueprints: &[Blueprint<TSchema>]) {
+        blueprints.iter().for_each(|blueprint| {
+            self.clock += 1;
+            let stamp = Stamp {
+                clock: self.clock,
+                replica_id: self.replica_id,
+            };
+            let blueprint = StandaloneBlueprint::from_blueprint(blueprint, self.to_standalone);
+            self.record(&blueprint, stamp);
+            self.outgoing.push(ReplicatedBlueprint { stamp, blueprint });
+        });
+    }
+    // Remembers the latest stamp of every field and edge the blueprint changes
+    fn record(&mut self, blueprint: &StandaloneBlueprint, stamp: Stamp) {
+        let held_edges = blueprint
+            .added_instances
+            .iter()
+            .chain(blueprint.deleted_instances.iter())
+            .flat_map(|instance| instance.outgoing_slots.iter());
+        let updated_edges = blueprint
+            .add_outgoing_updates
+            .iter()
+            .chain(blueprint.remove_outgoing_updates.iter())
+            .map(|(_, slot_ref)| slot_ref);
+        held_edges.chain(updated_edges).for_each(|slot_ref| {
+            let latest = self.edge_stamps.entry(slot_ref.clone()).or_insert(stamp);
+            *latest = (*latest).max(stamp);
+        });
+        blueprint.field_updates.iter().for_each(|(id, field_edit)| {
+            let latest = self
+                .field_stamps
+                .entry((*id, field_edit.field_id))
+                .or_insert(stamp);
+            *latest = (*latest).max(stamp);
+        });
+        self.deleted.extend(
+            blueprint
+                .deleted_instances
+                .iter()
+                .map(|instance| instance.id),
+        );
+    }
+    fn is_latest_edge_change(&self, slot_ref: &SlotRef, stamp: Stamp) -> bool {
+        self.edge_stamps
+            .get(slot_ref)
+            .is_none_or(|latest| stamp > *latest)
+    }
+
+    // The part of a blueprint from another replica which still applies to this one
+    fn resolve(
+        &self,
+        remote: &ReplicatedBlueprint,
+        local: impl Fn(&Uid) -> Option<StandaloneRGSOConcrete>,
+    ) -> StandaloneBlueprint {
+        let stamp = remote.stamp;
+        let remote = &remote.blueprint;
+        let deleted = remote
+            .deleted_instances
+            .iter()
+            .filter_map(|instance| local(&instance.id))
+            .collect::<Vec<_>>();
+        let deleted_ids = deleted
+            .iter()
+            .map(|instance| instance.id)
+            .collect::<HashSet<_>>();
+        let added = remote
+            .added_instances
+            .iter()
+            .filter(|instance| !self.deleted.contains(&instance.id))
+            .filter(|instance| local(&instance.id).is_none())
+            .cloned()
+            .collect::<Vec<_>>();
+        let added_ids = added
+            .iter()
+            .map(|instance| instance.id)
+            .collect::<HashSet<_>>();
+        let exists = |id: &Uid| {
+            added_ids.contains(id)
+                || (!deleted_ids.contains(id) && !self.deleted.contains(id) && local(id).is_some())
+        };
+
+        let mut resolved = StandaloneBlueprint {
+            added_instances: added
+                .into_iter()
+                .map(|mut instance| {
+                    // Edges to instances which have been deleted here are dropped
+                    instance
+                        .outgoing_slots
+                        .retain(|slot_ref| exists(&slot_ref.target_instance_id));
+                    instance
+                        .incoming_slots
+                        .retain(|slot_ref| exists(&slot_ref.host_instance_id));
+                    instance
+                })
+                .collect(),
+            label: remote.label.clone(),
+            ..Default::default()
+        };
+        // The other side of each edge held by an added instance
+        let held_edges = resolved
+            .added_instances
+            .iter()
+            .flat_map(|instance| {
+                let incoming = instance
+                    .incoming_slots
+                    .iter()
+                    .filter(|slot_ref| !added_ids.contains(&slot_ref.host_instance_id))
+                    .map(|slot_ref| (true, slot_ref.clone()));
+                let outgoing = instance
+                    .outgoing_slots
+                    .iter()
+                    .filter(|slot_ref| !added_ids.contains(&slot_ref.target_instance_id))
+                    .map(|slot_ref| (false, slot_ref.clone()));
+                incoming.chain(outgoing).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        held_edges
+            .into_iter()
+            .for_each(|(is_incoming, slot_ref)| match is_incoming {
+                true => push_unique(
+                    &mut resolved.add_outgoing_updates,
+                    (slot_ref.host_instance_id, slot_ref),
+                ),
+                false => push_unique(
+                    &mut resolved.add_incoming_updates,
+                    (slot_ref.target_instance_id, slot_ref),
+                ),
+            });
+
+        // Deleted instances are detached from whatever they are slotted into here, which may
+        // differ from what they were slotted into on the other replica
+        deleted.iter().for_each(|instance| {
+            instance
+                .incoming_slots
+                .iter()
+                .filter(|slot_ref| !deleted_ids.contains(&slot_ref.host_instance_id))
+                .for_each(|slot_ref| {
+                    push_unique(
+                        &mut resolved.remove_outgoing_updates,
+                        (slot_ref.host_instance_id, slot_ref.clone()),
+                    )
+                });
+            instance
+                .outgoing_slots
+                .iter()
+                .filter(|slot_ref| !deleted_ids.contains(&slot_ref.target_instance_id))
+                .for_each(|slot_ref| {
+                    push_unique(
+                        &mut resolved.remove_incoming_updates,
+                        (slot_ref.target_instance_id, slot_ref.clone()),
+                    )
+                });
+        });
+        resolved.deleted_instances = deleted;
+
+        let is_slotted = |slot_ref: &SlotRef| {
+            local(&slot_ref.host_instance_id)
+                .is_some_and(|host| host.outgoing_slots.contains(slot_ref))
+        };
+        remote
+            .add_outgoing_updates
+            .iter()
+            .map(|(_, slot_ref)| slot_ref)
+            .filter(|slot_ref| !added_ids.contains(&slot_ref.host_instance_id))
+            .filter(|slot_ref| {
+                exists(&slot_ref.host_instance_id) && exists(&slot_ref.target_instance_id)
+            })
+            .filter(|slot_ref| self.is_latest_edge_change(slot_ref, stamp))
+            .filter(|slot_ref| !is_slotted(slot_ref))
+            .for_each(|slot_ref| {
+                push_unique(
+                    &mut resolved.add_outgoing_updates,
+                    (slot_ref.host_instance_id, slot_ref.clone()),
+                );
+                // Added instances already list their incoming edges
+                if !added_ids.contains(&slot_ref.target_instance_id) {
+                    push_unique(
+                        &mut resolved.add_incoming_updates,
+                        (slot_ref.target_instance_id, slot_ref.clone()),
+                    );
+                }
+            });
+        remote
+            .remove_outgoing_updates
+            .iter()
+            .map(|(_, slot_ref)| slot_ref)
+            .filter(|slot_ref| !deleted_ids.contains(&slot_ref.host_instance_id))
+            .filter(|slot_ref| self.is_latest_edge_change(slot_ref, stamp))
+            .filter(|slot_ref| is_slotted(slot_ref))
+            .for_each(|slot_ref| {
+                push_unique(
+                    &mut resolved.remove_outgoing_updates,
+                    (slot_ref.host_instance_id, slot_ref.clone()),
+                );
+                if !deleted_ids.contains(&slot_ref.target_instance_id) {
+                    push_unique(
+                        &mut resolved.remove_incoming_updates,
+                        (slot_ref.target_instance_id, slot_ref.clone()),
+                    );
+                }
+            });
+
+        resolved.field_updates = remote
+            .field_updates
+            .iter()
+            .filter(|(id, _)| !added_ids.contains(id) && exists(id))
+            .filter(|(id, field_edit)| {
+                self.field_stamps
+                    .get(&(*id, field_edit.field_id))
+                    .is_none_or(|latest| stamp > *latest)
+            })
+            .filter_map(|(id, field_edit)| {
+                let prev_value = local(id)?.fields.get(&field_edit.field_id)?.clone();
+                (prev_value != field_edit.new_value).then(|| {
+                    (
+                        *id,
+                        HistoryFieldEdit {
+                            prev_value,
+                            ..field_edit.clone()
+                        },
+                    )
+                })
+            })
+            .collect();
+        resolved
+    }
+}
+
+fn push_unique<T: PartialEq>(items: &mut Vec<T>, item: T) {
+    if !items.contains(&item) {
+        items.push(item);
+    }
+}
+
+impl<TSchema: Send + Sync + Clone + Into<StandaloneRGSOConcrete> + 'static>
+    RBaseGraphEnvironment<TSchema>
+{
+    /// Makes this graph a replica which can exchange blueprints with other replicas of the same
+    /// graph, see [`crate::post_generation::merge`]. Every blueprint committed from now on,
+    /// including undos and redos, is queued for [`Self::take_replicated`]
+    pub fn attach_replica(&self, replica_id: Uid) {
+        *self.replica.lock().unwrap() = Some(Replica::new(replica_id));
+    }
+    pub fn detach_replica(&self) -> Option<Replica<TSchema>> {
+        self.replica.lock().unwrap().take()
+    }
+    /// The blueprints committed on this replica since the last call, to be merged into the others
+    pub fn take_replicated(&self) -> Vec<ReplicatedBlueprint> {
+        self.replica
+            .lock()
+            .unwrap()
+            .as_mut()
+            .map(|replica| std::mem::take(&mut replica.outgoing))
+            .unwrap_or_default()
+    }
+}
+
+impl<TSchema> SharedGraph<TSchema>
+where
+    TSchema: EditRGSO<Schema = TSchema>
+        + FromStandalone<Schema = TSchema>
+        + Into<StandaloneRGSOConcrete>
+        + Send
+        + Sync
+        + 'static,
+{
+    /// Applies the blueprints of other replicas which haven't been merged yet. Merged blueprints
+    /// are announced to subscribers and journaled, but don't enter the undo history.
+    ///
+    /// Fails with [`ElementCreationError::NoReplicaAttached`] if no replica has been attached to
+    /// the graph
+    pub fn merge(
+        &self,
+        blueprints: impl IntoIterator<Item = ReplicatedBlueprint>,
+    ) -> Result<MergeReport, ElementCreationError> {
+        let mut report = MergeReport::default();
+        let mut touched_hosts = HashSet::new();
+        for remote in blueprints {
+            let applied = {
+                let mut replica = self.replica.lock().unwrap();
+                let replica = replica
+                    .as_mut()
+                    .ok_or(ElementCreationError::NoReplicaAttached)?;
+                let Stamp { clock, replica_id } = remote.stamp;
+                if replica_id == replica.replica_id
+                    || replica
+                        .merged_clocks
+                        .get(&replica_id)
+                        .is_some_and(|merged| *merged >= clock)
+                {
+                    continue;
+                }
+                let resolved = replica.resolve(&remote, |id| self.get(id).map(Into::into));
+                touched_hosts.extend(
+                    resolved
+                        .added_instances
+                        .iter()
+                        .map(|instance| instance.id)
+                        .chain(resolved.add_outgoing_updates.iter().map(|(id, _)| *id))
+                        .chain(resolved.remove_outgoing_updates.iter().map(|(id, _)| *id)),
+                );
+                let applied = if resolved.is_empty() {
+                    None
+                } else {
+                    let blueprint = Blueprint::from_standalone(resolved, self.clone())?;
+                    self.process_blueprint(blueprint.clone())?;
+                    Some(blueprint)
+                };
+                replica.record(&remote.blueprint, remote.stamp);
+                replica.merged_clocks.insert(replica_id, clock);
+                replica.clock = replica.clock.max(clock);
+                report.merged += 1;
+                applied
+            };
+            if let Some(blueprint) = applied {
+                self.committed(std::slice::from_ref(&blueprint), ChangeOrigin::Merge);
+            }
+        }
+        let mut touched_hosts = touched_hosts.into_iter().collect::<Vec<_>>();
+        touched_hosts.sort();
+        report.bound_violations = touched_hosts
+            .iter()
+            .filter_map(|id| self.get(id))
+            .flat_map(|host| {
+                host.outgoing_slots()
+                    .into_iter()
+                    .filter(|(_, slot)| !slot.check_current_conformity())
+                    .map(|(slot_id, slot)| {
+                        BoundCheckError {
+                            instance_id: *host.get_id(),
+                            temp_id: None,
+                            operative_id: host.operative().tag.id,
+                            operative_name: host.operative().tag.name.clone(),
+                            slot_id: *slot_id,
+                            slot_name: slot.slot.tag.name.clone(),
+                            bounds: slot.slot.bounds.clone(),
+                            attempted: slot.slotted_instances.get_untracked().len(),
+                        }
+                        .into()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{post_generation::reactive::RGSO, testing::*};
+
+    // Two replicas which have merged each other's setup
+    fn replicas(setup: impl FnOnce(&SharedGraph<TestSchema>)) -> [SharedGraph<TestSchema>; 2] {
+        let a = initialize_graph();
+        let b = initialize_graph();
+        a.attach_replica(1);
+        b.attach_replica(2);
+        setup(&a);
+        b.merge(a.take_replicated()).unwrap();
+        assert_eq!(normalized(&a), normalized(&b));
+        [a, b]
+    }
+
+    // Sends each replica's blueprints to the other one, merging them on `first` before `second`
+    fn exchange(
+        first: &SharedGraph<TestSchema>,
+        second: &SharedGraph<TestSchema>,
+    ) -> [MergeReport; 2] {
+        let from_first = first.take_replicated();
+        let from_second = second.take_replicated();
+        [
+            first.merge(from_second).unwrap(),
+            second.merge(from_first).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn concurrent_field_edits_resolve_to_the_latest_stamp() {
+        for swapped in [false, true] {
+            let mut node_id = 0;
+            let [a, b] = replicas(|graph| node_id = create_node(graph, "node").unwrap());
+            rename(&a, &node_id, "from a").unwrap();
+            rename(&b, &node_id, "from b").unwrap();
+            match swapped {
+                false => exchange(&a, &b),
+                true => exchange(&b, &a),
+            };
+            // Both edits carry the same clock, so the replica with the larger id wins
+            assert_eq!(name(&a, &node_id).as_deref(), Some("from b"));
+            assert_eq!(normalized(&a), normalized(&b));
+        }
+    }
+
+    #[test]
+    fn concurrent_edge_changes_merge_as_sets() {
+        for swapped in [false, true] {
+            let mut ids = Vec::new();
+            let [a, b] = replicas(|graph| {
+                ids = ["host", "w", "x", "y"]
+                    .map(|name| create_node(graph, name).unwrap())
+                    .to_vec();
+                link(graph, &ids[0], &ids[1]).unwrap();
+            });
+            let [host, w, x, y] = [ids[0], ids[1], ids[2], ids[3]];
+            link(&a, &host, &x).unwrap();
+            unlink(&b, &host, &w).unwrap();
+            link(&b, &host, &y).unwrap();
+            let reports = match swapped {
+                false => exchange(&a, &b),
+                true => exchange(&b, &a),
+            };
+            assert!(reports
+                .iter()
+                .all(|report| report.bound_violations.is_empty()));
+            let mut links = a
+                .get(&host)
+                .unwrap()
+                .slot_by_id(LINKS_SLOT_ID)
+                .unwrap()
+                .slotted_instances
+                .get_untracked();
+            links.sort();
+            let mut expected = vec![x, y];
+            expected.sort();
+            assert_eq!(links, expected);
+            assert_eq!(normalized(&a), normalized(&b));
+        }
+    }
+
+    #[test]
+    fn concurrently_filled_single_slot_is_reported() {
+        for swapped in [false, true] {
+            let mut ids = Vec::new();
+            let [a, b] = replicas(|graph| {
+                ids = ["first", "from a", "from b"]
+                    .map(|name| create_node(graph, name).unwrap())
+                    .to_vec();
+                ids.push(create_pair(graph, &ids[0]).unwrap());
+            });
+            let [_, from_a, from_b, pair] = [ids[0], ids[1], ids[2], ids[3]];
+            repartner(&a, &pair, &from_a).unwrap();
+            repartner(&b, &pair, &from_b).unwrap();
+            let reports = match swapped {
+                false => exchange(&a, &b),
+                true => exchange(&b, &a),
+            };
+            for report in reports {
+                assert_eq!(report.merged, 1);
+                match report.bound_violations.as_slice() {
+                    [ElementCreationError::BoundCheckOutOfRange(error)] => {
+                        assert_eq!(error.instance_id, pair);
+                        assert_eq!(error.slot_id, PARTNER_SLOT_ID);
+                        assert_eq!(error.attempted, 2);
+                    }
+                    violations => panic!("unexpected bound violations {:?}", violations),
+                }
+            }
+            assert_eq!(normalized(&a), normalized(&b));
+        }
+    }
+
+    #[test]
+    fn merging_into_a_graph_without_replica_fails() {
+        let [a, _] = replicas(|graph| {
+            create_node(graph, "node").unwrap();
+        });
+        create_node(&a, "another").unwrap();
+        let unattached = initialize_graph();
+        assert!(matches!(
+            unattached.merge(a.take_replicated()),
+            Err(ElementCreationError::NoReplicaAttached)
+        ));
+    }
+}
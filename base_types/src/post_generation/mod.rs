@@ -1,9 +1,28 @@
 pub mod non_reactive;
 mod tests;
 
-// #[cfg(feature = "reactive")]
+#[cfg(feature = "reactive")]
 pub mod reactive;
-// #[cfg(feature = "reactive")]
 pub mod type_level;
+#[cfg(feature = "serde")]
+pub mod bundle;
+pub mod change_set;
+pub mod concurrent;
+pub mod duplicate;
+pub mod export;
+pub mod field_index;
+#[cfg(feature = "serde")]
+pub mod import;
+pub mod integrity;
+#[cfg(feature = "serde")]
+pub mod journal;
+#[cfg(feature = "reactive")]
+pub mod merge;
+pub mod plain;
+pub mod query;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_store;
+pub mod store;
+mod validation;
 
 pub use non_reactive::*;
@@ -1,14 +1,11 @@
 use std::fmt;
 
 use std::{any::Any, cell::RefCell, collections::HashMap, marker::PhantomData, rc::Rc};
-use strum_macros::Display;
 
 use crate::common::StrUid;
 use crate::{
-    common::{ConstraintTraits, Uid},
-    constraint_schema::{
-        ConstraintSchema, LibraryOperative, LibraryTemplate, OperativeSlot, SlotBounds,
-    },
+    common::Uid,
+    constraint_schema::{LibraryOperative, LibraryTemplate, OperativeSlot, SlotBounds},
     primitives::{PrimitiveTypes, PrimitiveValues},
 };
 
@@ -16,16 +13,296 @@ pub type LibOp = LibraryOperative<PrimitiveTypes, PrimitiveValues>;
 pub type LibTemplate = LibraryTemplate<PrimitiveTypes, PrimitiveValues>;
 
 type Error = ElementCreationError;
-#[derive(Debug, Display, Clone)]
+#[derive(Debug, Clone)]
 pub enum ElementCreationError {
-    RequiredFieldIsEmpty,
-    BoundCheckOutOfRange(String),
-    OutgoingElementIsWrongType { expected: String, recieved: String },
-    OutgoingElementDoesntExist { id: Uid },
-    NonexistentTempId { temp_id: String },
+    RequiredFieldIsEmpty(Box<RequiredFieldError>),
+    BoundCheckOutOfRange(Box<BoundCheckError>),
+    OutgoingElementIsWrongType {
+        host_id: Uid,
+        slot_id: Uid,
+        target_id: Uid,
+        // Names of the operatives which the slot accepts
+        expected: Vec<String>,
+        received: String,
+    },
+    OutgoingElementDoesntExist {
+        host_id: Uid,
+        slot_id: Uid,
+        target_id: Uid,
+    },
+    NonexistentEdge {
+        host_id: Uid,
+        slot_id: Uid,
+        target_id: Uid,
+    },
+    // The instance was changed by another commit after the transaction read it
+    Conflict {
+        instance_id: Uid,
+        read_version: u64,
+        current_version: u64,
+    },
+    NonexistentTempId {
+        temp_id: String,
+    },
+    NonexistentInstance {
+        id: Uid,
+    },
+    // The instance was added while one with the same id already exists
+    DuplicateInstance {
+        id: Uid,
+    },
+    NonexistentSlot {
+        instance_id: Uid,
+        slot_id: Uid,
+    },
+    NonexistentField {
+        instance_id: Uid,
+        field_id: Uid,
+    },
+    NonexistentOperative {
+        instance_id: Uid,
+        operative_id: Uid,
+    },
+    NonexistentTemplate {
+        instance_id: Uid,
+        template_id: Uid,
+    },
+    // The instance was expected to be of an operative or one of its subclasses
+    WrongOperative {
+        instance_id: Uid,
+        expected: Uid,
+        found: Uid,
+    },
+    FieldValueTypeMismatch {
+        instance_id: Uid,
+        field_id: Uid,
+        field_name: String,
+        expected: PrimitiveTypes,
+        received: PrimitiveValues,
+    },
+    UniqueFieldViolation(Box<UniqueFieldError>),
+    // The blueprint couldn't be written to the attached journal or store, and wasn't applied
+    Persistence {
+        message: String,
+    },
+    // Undoing or redoing a step failed partway, and so did rolling back the blueprints of the step
+    // which were already applied. The graph is left holding part of the step
+    RollbackFailed {
+        error: Box<ElementCreationError>,
+        rollback_errors: Vec<ElementCreationError>,
+    },
+    // Blueprints of other replicas were merged into a graph which isn't a replica itself
+    NoReplicaAttached,
     DeletionError,
     Stack(Vec<ElementCreationError>),
 }
+// The larger variants are boxed, so that results carrying an ElementCreationError stay small
+#[derive(Debug, Clone)]
+pub struct RequiredFieldError {
+    pub instance_id: Uid,
+    pub temp_id: Option<String>,
+    pub operative_id: Uid,
+    pub operative_name: String,
+    pub field_id: Uid,
+    pub field_name: String,
+}
+#[derive(Debug, Clone)]
+pub struct BoundCheckError {
+    pub instance_id: Uid,
+    pub temp_id: Option<String>,
+    pub operative_id: Uid,
+    pub operative_name: String,
+    pub slot_id: Uid,
+    pub slot_name: String,
+    pub bounds: SlotBounds,
+    pub attempted: usize,
+}
+#[derive(Debug, Clone)]
+pub struct UniqueFieldError {
+    pub instance_id: Uid,
+    pub temp_id: Option<String>,
+    pub field_id: Uid,
+    pub field_name: String,
+    pub value: PrimitiveValues,
+    pub conflicting_id: Uid,
+}
+impl From<RequiredFieldError> for ElementCreationError {
+    fn from(value: RequiredFieldError) -> Self {
+        ElementCreationError::RequiredFieldIsEmpty(Box::new(value))
+    }
+}
+impl From<BoundCheckError> for ElementCreationError {
+    fn from(value: BoundCheckError) -> Self {
+        ElementCreationError::BoundCheckOutOfRange(Box::new(value))
+    }
+}
+impl From<UniqueFieldError> for ElementCreationError {
+    fn from(value: UniqueFieldError) -> Self {
+        ElementCreationError::UniqueFieldViolation(Box::new(value))
+    }
+}
+impl ElementCreationError {
+    /// All non-`Stack` errors contained in this error, in order
+    pub fn flatten(&self) -> Vec<&ElementCreationError> {
+        match self {
+            ElementCreationError::Stack(errors) => {
+                errors.iter().flat_map(|error| error.flatten()).collect()
+            }
+            error => vec![error],
+        }
+    }
+}
+fn fmt_instance(instance_id: &Uid, temp_id: &Option<String>) -> String {
+    match temp_id {
+        Some(temp_id) => format!("'{}' ({})", temp_id, instance_id),
+        None => instance_id.to_string(),
+    }
+}
+impl fmt::Display for ElementCreationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElementCreationError::RequiredFieldIsEmpty(error) => write!(
+                f,
+                "{} {}: required field '{}' is empty",
+                error.operative_name,
+                fmt_instance(&error.instance_id, &error.temp_id),
+                error.field_name
+            ),
+            ElementCreationError::BoundCheckOutOfRange(error) => write!(
+                f,
+                "{} {}: slot '{}' would hold {} instances, bounds are {:?}",
+                error.operative_name,
+                fmt_instance(&error.instance_id, &error.temp_id),
+                error.slot_name,
+                error.attempted,
+                error.bounds
+            ),
+            ElementCreationError::OutgoingElementIsWrongType {
+                host_id,
+                target_id,
+                expected,
+                received,
+                ..
+            } => write!(
+                f,
+                "{}: cannot slot {} of type {}, expected one of [{}]",
+                host_id,
+                target_id,
+                received,
+                expected.join(", ")
+            ),
+            ElementCreationError::OutgoingElementDoesntExist {
+                host_id, target_id, ..
+            } => write!(
+                f,
+                "{}: cannot slot {}, no such instance exists",
+                host_id, target_id
+            ),
+            ElementCreationError::NonexistentEdge {
+                host_id, target_id, ..
+            } => write!(
+                f,
+                "{}: cannot remove {} from the slot, it isn't slotted there",
+                host_id, target_id
+            ),
+            ElementCreationError::Conflict {
+                instance_id,
+                read_version,
+                current_version,
+            } => write!(
+                f,
+                "{}: changed by another commit since it was read (version {}, now {})",
+                instance_id, read_version, current_version
+            ),
+            ElementCreationError::NonexistentTempId { temp_id } => {
+                write!(f, "No instance with temp id '{}'", temp_id)
+            }
+            ElementCreationError::NonexistentInstance { id } => {
+                write!(f, "No instance with id {}", id)
+            }
+            ElementCreationError::DuplicateInstance { id } => {
+                write!(f, "An instance with id {} already exists", id)
+            }
+            ElementCreationError::NonexistentSlot {
+                instance_id,
+                slot_id,
+            } => write!(f, "{}: no slot with id {}", instance_id, slot_id),
+            ElementCreationError::NonexistentField {
+                instance_id,
+                field_id,
+            } => write!(f, "{}: no field with id {}", instance_id, field_id),
+            ElementCreationError::NonexistentOperative {
+                instance_id,
+                operative_id,
+            } => write!(f, "{}: no operative with id {}", instance_id, operative_id),
+            ElementCreationError::NonexistentTemplate {
+                instance_id,
+                template_id,
+            } => write!(f, "{}: no template with id {}", instance_id, template_id),
+            ElementCreationError::WrongOperative {
+                instance_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}: is of operative {}, which isn't {} or one of its subclasses",
+                instance_id, found, expected
+            ),
+            ElementCreationError::FieldValueTypeMismatch {
+                instance_id,
+                field_name,
+                expected,
+                received,
+                ..
+            } => write!(
+                f,
+                "{}: field '{}' expects a value of type {:?}, received {:?}",
+                instance_id, field_name, expected, received
+            ),
+            ElementCreationError::UniqueFieldViolation(error) => write!(
+                f,
+                "{}: '{}' must be unique, but {} already holds '{}'",
+                fmt_instance(&error.instance_id, &error.temp_id),
+                error.field_name,
+                error.conflicting_id,
+                error.value
+            ),
+            ElementCreationError::Persistence { message } => {
+                write!(
+                    f,
+                    "Could not persist the changes, they weren't applied: {}",
+                    message
+                )
+            }
+            ElementCreationError::RollbackFailed {
+                error,
+                rollback_errors,
+            } => write!(
+                f,
+                "{}\nRolling back the step failed as well, the graph holds part of it: {}",
+                error,
+                rollback_errors
+                    .iter()
+                    .map(|error| error.to_string())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+            ElementCreationError::NoReplicaAttached => {
+                write!(f, "A replica must be attached before merging")
+            }
+            ElementCreationError::DeletionError => write!(f, "Deletion failed"),
+            ElementCreationError::Stack(errors) => write!(
+                f,
+                "{}",
+                errors
+                    .iter()
+                    .map(|error| error.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+        }
+    }
+}
 impl std::error::Error for ElementCreationError {}
 
 #[derive(Debug, Clone)]
@@ -38,46 +315,6 @@ pub enum TaggedAction {
 pub type HistoryStack<TSchema> = Vec<Vec<HistoryItem<TSchema>>>;
 pub type HistoryRef<TSchema> = Rc<RefCell<HistoryContainer<TSchema>>>;
 
-// #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug, Clone)]
-pub struct BaseGraphEnvironment<TSchema> {
-    pub created_instances: HashMap<Uid, TSchema>,
-    // #[serde(skip)]
-    pub constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
-}
-impl<TSchema: 'static> BaseGraphEnvironment<TSchema> {
-    pub fn new(
-        constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
-    ) -> Self {
-        Self {
-            created_instances: HashMap::new(),
-            constraint_schema,
-        }
-    }
-}
-
-pub trait GraphEnvironment {
-    type Types: ConstraintTraits;
-    type Values: ConstraintTraits;
-    type Schema: 'static;
-
-    fn get(&self, id: &Uid) -> Option<&Self::Schema>;
-    fn create_connection(&mut self, connection: ConnectionAction) -> Result<(), Error>;
-    fn instantiate_element<T>(
-        &mut self,
-        element: InstantiableWrapper<GSOConcrete<T>, Self::Schema>,
-    ) -> Result<Uid, Error>
-    where
-        GSOConcrete<T>: Instantiable<Schema = Self::Schema>,
-        Self: Sized,
-        T: std::fmt::Debug + Clone + 'static;
-    fn get_mut(&mut self, id: &Uid) -> Option<&mut Self::Schema>;
-    fn get_constraint_schema(&self) -> &ConstraintSchema<Self::Types, Self::Values>;
-    fn delete(&mut self, id: &Uid) -> Result<(), Error>;
-    fn undo(&mut self);
-    fn redo(&mut self);
-}
-
 #[derive(Debug, Clone)]
 pub enum HistoryItem<TSchema> {
     RemoveChildFromSlot(SlotRef),
@@ -90,6 +327,7 @@ pub enum HistoryItem<TSchema> {
     EditField(HistoryFieldEdit),
     BlockActionMarker,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct HistoryFieldEdit {
     pub instance_id: Uid,
@@ -212,6 +450,115 @@ pub struct StandaloneRGSOConcrete {
     pub operative: Uid,
     pub template: Uid,
 }
+/// How ids are written when instances are serialized. Reading accepts either
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdFormat {
+    /// Bare u128 numbers, as the derived serialization of [`StandaloneRGSOConcrete`] writes them
+    #[default]
+    Numeric,
+    /// Canonical UUID strings, which survive JavaScript and other tools that read numbers as
+    /// doubles
+    Uuid,
+}
+
+/// [`StandaloneRGSOConcrete`] with its ids as UUID strings, see [`IdFormat::Uuid`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct StrStandaloneRGSOConcrete {
+    pub id: StrUid,
+    pub fields: std::collections::HashMap<StrUid, PrimitiveValues>,
+    pub outgoing_slots: Vec<StrSlotRef>,
+    pub incoming_slots: Vec<StrSlotRef>,
+    pub operative: StrUid,
+    pub template: StrUid,
+}
+impl From<StandaloneRGSOConcrete> for StrStandaloneRGSOConcrete {
+    fn from(value: StandaloneRGSOConcrete) -> Self {
+        Self {
+            id: value.id.into(),
+            fields: value
+                .fields
+                .into_iter()
+                .map(|(field_id, field_value)| (field_id.into(), field_value))
+                .collect(),
+            outgoing_slots: value.outgoing_slots.into_iter().map(Into::into).collect(),
+            incoming_slots: value.incoming_slots.into_iter().map(Into::into).collect(),
+            operative: value.operative.into(),
+            template: value.template.into(),
+        }
+    }
+}
+impl From<StrStandaloneRGSOConcrete> for StandaloneRGSOConcrete {
+    fn from(value: StrStandaloneRGSOConcrete) -> Self {
+        Self {
+            id: value.id.into(),
+            fields: value
+                .fields
+                .into_iter()
+                .map(|(field_id, field_value)| (field_id.into(), field_value))
+                .collect(),
+            outgoing_slots: value.outgoing_slots.into_iter().map(Into::into).collect(),
+            incoming_slots: value.incoming_slots.into_iter().map(Into::into).collect(),
+            operative: value.operative.into(),
+            template: value.template.into(),
+        }
+    }
+}
+
+/// Normalizes a blueprint so that it can be written to disk, sent over the network or applied to a
+/// [`crate::post_generation::plain::PlainGraphEnvironment`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default)]
+pub struct StandaloneBlueprint {
+    pub added_instances: Vec<StandaloneRGSOConcrete>,
+    pub deleted_instances: Vec<StandaloneRGSOConcrete>,
+    pub add_outgoing_updates: Vec<(Uid, SlotRef)>,
+    pub remove_outgoing_updates: Vec<(Uid, SlotRef)>,
+    pub add_incoming_updates: Vec<(Uid, SlotRef)>,
+    pub remove_incoming_updates: Vec<(Uid, SlotRef)>,
+    pub field_updates: Vec<(Uid, HistoryFieldEdit)>,
+    pub label: Option<String>,
+}
+
+impl StandaloneBlueprint {
+    /// The blueprint which undoes this one
+    pub fn reverse(self) -> Self {
+        Self {
+            added_instances: self.deleted_instances,
+            deleted_instances: self.added_instances,
+            add_outgoing_updates: self.remove_outgoing_updates,
+            remove_outgoing_updates: self.add_outgoing_updates,
+            add_incoming_updates: self.remove_incoming_updates,
+            remove_incoming_updates: self.add_incoming_updates,
+            field_updates: self
+                .field_updates
+                .into_iter()
+                .map(|(id, field_update)| (id, field_update.reverse()))
+                .collect(),
+            label: self.label,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.added_instances.is_empty()
+            && self.deleted_instances.is_empty()
+            && self.add_outgoing_updates.is_empty()
+            && self.remove_outgoing_updates.is_empty()
+            && self.add_incoming_updates.is_empty()
+            && self.remove_incoming_updates.is_empty()
+            && self.field_updates.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExecutionResult {
+    pub temp_id_map: std::collections::HashMap<String, Uid>,
+}
+impl ExecutionResult {
+    pub fn get_final_id(&self, temp_id: &str) -> Option<&Uid> {
+        self.temp_id_map.get(temp_id)
+    }
+}
+
 #[derive(Clone)]
 /// Struct which abstracts all common parts of a generated schema object
 pub struct GSOConcrete<T> {
@@ -364,3 +711,148 @@ where
     type Schema;
     fn into_schema(instantiable: GSOConcrete<Self>) -> Self::Schema;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn required_field(temp_id: Option<&str>) -> ElementCreationError {
+        RequiredFieldError {
+            instance_id: 5,
+            temp_id: temp_id.map(str::to_string),
+            operative_id: 1,
+            operative_name: "Node".to_string(),
+            field_id: 2,
+            field_name: "name".to_string(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn errors_describe_the_instance_and_what_was_wrong() {
+        assert_eq!(
+            required_field(None).to_string(),
+            "Node 5: required field 'name' is empty"
+        );
+        assert_eq!(
+            required_field(Some("new node")).to_string(),
+            "Node 'new node' (5): required field 'name' is empty"
+        );
+        let bounds: ElementCreationError = BoundCheckError {
+            instance_id: 6,
+            temp_id: None,
+            operative_id: 3,
+            operative_name: "Pair".to_string(),
+            slot_id: 4,
+            slot_name: "partner".to_string(),
+            bounds: SlotBounds::Single,
+            attempted: 2,
+        }
+        .into();
+        assert_eq!(
+            bounds.to_string(),
+            "Pair 6: slot 'partner' would hold 2 instances, bounds are Single"
+        );
+        let unique: ElementCreationError = UniqueFieldError {
+            instance_id: 7,
+            temp_id: Some("label".to_string()),
+            field_id: 8,
+            field_name: "text".to_string(),
+            value: PrimitiveValues::String("x".to_string()),
+            conflicting_id: 9,
+        }
+        .into();
+        assert_eq!(
+            unique.to_string(),
+            "'label' (7): 'text' must be unique, but 9 already holds 'x'"
+        );
+        let wrong_type = ElementCreationError::OutgoingElementIsWrongType {
+            host_id: 6,
+            slot_id: 4,
+            target_id: 7,
+            expected: vec!["Node".to_string(), "Subnode".to_string()],
+            received: "Label".to_string(),
+        };
+        assert_eq!(
+            wrong_type.to_string(),
+            "6: cannot slot 7 of type Label, expected one of [Node, Subnode]"
+        );
+        let mismatch = ElementCreationError::FieldValueTypeMismatch {
+            instance_id: 5,
+            field_id: 2,
+            field_name: "name".to_string(),
+            expected: PrimitiveTypes::String,
+            received: PrimitiveValues::Int(1),
+        };
+        assert_eq!(
+            mismatch.to_string(),
+            "5: field 'name' expects a value of type String, received Int(1)"
+        );
+    }
+
+    #[test]
+    fn stacked_errors_are_listed_in_order() {
+        let stack = ElementCreationError::Stack(vec![
+            required_field(None),
+            ElementCreationError::Stack(vec![
+                ElementCreationError::NonexistentInstance { id: 3 },
+                required_field(Some("new node")),
+            ]),
+        ]);
+        assert_eq!(
+            stack.to_string(),
+            "Node 5: required field 'name' is empty\n\
+             No instance with id 3\n\
+             Node 'new node' (5): required field 'name' is empty"
+        );
+        assert!(matches!(
+            stack.flatten().as_slice(),
+            [
+                ElementCreationError::RequiredFieldIsEmpty(first),
+                ElementCreationError::NonexistentInstance { id: 3 },
+                ElementCreationError::RequiredFieldIsEmpty(last),
+            ] if first.temp_id.is_none() && last.temp_id.as_deref() == Some("new node")
+        ));
+    }
+
+    #[cfg(feature = "reactive")]
+    #[test]
+    fn failed_commits_point_at_the_offending_field_and_slot() {
+        use crate::{
+            post_generation::reactive::RBuildable,
+            testing::{
+                initialize_graph, Node, Pair, NAME_FIELD_ID, NODE_OPERATIVE_ID, PAIR_OPERATIVE_ID,
+                PARTNER_SLOT_ID,
+            },
+        };
+        let graph = initialize_graph();
+        let node = Node::initiate_build(graph.clone());
+        let error = node.execute().unwrap_err();
+        let [ElementCreationError::RequiredFieldIsEmpty(error)] = error.flatten().as_slice() else {
+            panic!("expected only the name to be missing: {}", error);
+        };
+        assert_eq!(error.instance_id, *node.get_id());
+        assert_eq!(
+            (error.operative_id, error.operative_name.as_str()),
+            (NODE_OPERATIVE_ID, "Node")
+        );
+        assert_eq!(
+            (error.field_id, error.field_name.as_str()),
+            (NAME_FIELD_ID, "name")
+        );
+
+        let pair = Pair::initiate_build(graph.clone());
+        let error = pair.execute().unwrap_err();
+        let [ElementCreationError::BoundCheckOutOfRange(error)] = error.flatten().as_slice() else {
+            panic!("expected only the partner to be missing: {}", error);
+        };
+        assert_eq!(error.instance_id, *pair.get_id());
+        assert_eq!(error.operative_id, PAIR_OPERATIVE_ID);
+        assert_eq!(
+            (error.slot_id, error.slot_name.as_str()),
+            (PARTNER_SLOT_ID, "partner")
+        );
+        assert!(matches!(error.bounds, SlotBounds::Single));
+        assert_eq!(error.attempted, 0);
+    }
+}
@@ -0,0 +1,1356 @@
+//! A graph runtime over [`StandaloneRGSOConcrete`]s which doesn't depend on Leptos, for servers and
+//! command line tools.
+//!
+//! Changes are made by applying [`StandaloneBlueprint`]s. Each blueprint is checked against the
+//! schema before anything is changed, so it is either applied completely or not at all.
+//! [`Transaction`]s build those blueprints from individual edits.
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "serde")]
+use crate::post_generation::{
+    bundle::{export_bundle, plan_bundle_import, BundleError, BundleOptions, SubgraphBundle},
+    import::{import_transaction, prepare_import, ImportError, ImportInput, ImportMapping},
+    journal::{instances_from_json, write_snapshot_as, JournalError},
+    IdFormat,
+};
+use crate::{
+    common::Uid,
+    constraint_schema::{ConstraintSchema, OperativeSlot},
+    constraint_schema_item::ConstraintSchemaItem,
+    post_generation::{
+        change_set::{ChangeOrigin, ChangeSet},
+        duplicate::{plan_duplicate, plan_transaction},
+        export::{to_cypher, to_graphml, to_neo4j_csv, to_turtle, ExportError, Neo4jCsvExport},
+        field_index::FieldValueIndex,
+        integrity::{check_instances, find_problems, IntegrityProblem, IntegrityReport, LoadError},
+        query::{execute_query, QueryError, QueryResult},
+        validation::{check_field_value_type, check_unique_fields, slot_accepts_operative},
+        ActiveSlot, BoundCheckError, ElementCreationError, ExecutionResult, HistoryFieldEdit,
+        LibOp, LibTemplate, RequiredFieldError, SlotRef, StandaloneBlueprint,
+        StandaloneRGSOConcrete,
+    },
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+/// Instances and history are held in persistent collections, so a clone shares its structure with
+/// the original and is cheap to take, see [`crate::post_generation::concurrent`]
+#[derive(Debug, Clone)]
+pub struct PlainGraphEnvironment {
+    pub constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    instances: im::HashMap<Uid, StandaloneRGSOConcrete>,
+    field_index: FieldValueIndex,
+    /// Number of applied blueprints which have touched each instance, see [`Self::version`]
+    versions: im::HashMap<Uid, u64>,
+    undo: im::Vector<StandaloneBlueprint>,
+    redo: im::Vector<StandaloneBlueprint>,
+}
+
+impl PlainGraphEnvironment {
+    pub fn new(
+        constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    ) -> Self {
+        Self {
+            constraint_schema,
+            instances: im::HashMap::new(),
+            field_index: FieldValueIndex::new(constraint_schema),
+            versions: im::HashMap::new(),
+            undo: im::Vector::new(),
+            redo: im::Vector::new(),
+        }
+    }
+    /// Builds a graph from a snapshot, checking every instance against the schema
+    pub fn from_instances(
+        constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+        instances: Vec<StandaloneRGSOConcrete>,
+    ) -> Result<Self, ElementCreationError> {
+        let mut graph = Self::new(constraint_schema);
+        graph.process(StandaloneBlueprint {
+            added_instances: instances,
+            ..StandaloneBlueprint::default()
+        })?;
+        // Loaded instances start out at version 0, as in the reactive graph
+        graph.versions.clear();
+        Ok(graph)
+    }
+
+    /// Changes with every blueprint (including undos and redos) which touches the instance or its
+    /// slots. Instances which have never been touched are at version 0
+    pub fn version(&self, id: &Uid) -> u64 {
+        self.versions.get(id).copied().unwrap_or(0)
+    }
+    pub fn get(&self, id: &Uid) -> Option<&StandaloneRGSOConcrete> {
+        self.instances.get(id)
+    }
+    pub fn instances(&self) -> impl Iterator<Item = &StandaloneRGSOConcrete> {
+        self.instances.values()
+    }
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+    /// Finds all instances holding the given value in the given field.
+    /// Uses the value index if the field is marked as indexed in the schema, otherwise scans the graph.
+    pub fn find_by_field(
+        &self,
+        field_id: &Uid,
+        value: &PrimitiveValues,
+    ) -> Vec<&StandaloneRGSOConcrete> {
+        if self.field_index.is_indexed(field_id) {
+            return self
+                .field_index
+                .lookup(field_id, value)
+                .iter()
+                .filter_map(|id| self.instances.get(id))
+                .collect();
+        }
+        let mut found = self
+            .instances
+            .values()
+            .filter(|instance| instance.fields.get(field_id) == Some(value))
+            .collect::<Vec<_>>();
+        found.sort_by_key(|instance| instance.id);
+        found
+    }
+    /// Runs a query against the current state of the graph, see [`crate::post_generation::query`]
+    pub fn query(&self, query: &str) -> Result<QueryResult, QueryError> {
+        let nodes = self.instances.values().cloned().collect::<Vec<_>>();
+        execute_query(self.constraint_schema, &nodes, query)
+    }
+
+    /// A Cypher script which recreates the graph, see [`crate::post_generation::export`]
+    pub fn export_cypher(&self) -> Result<String, ExportError> {
+        to_cypher(self.constraint_schema, &self.snapshot())
+    }
+    pub fn export_neo4j_csv(&self) -> Result<Neo4jCsvExport, ExportError> {
+        to_neo4j_csv(self.constraint_schema, &self.snapshot())
+    }
+    pub fn export_graphml(&self) -> Result<String, ExportError> {
+        to_graphml(self.constraint_schema, &self.snapshot())
+    }
+    /// An RDF graph with IRIs under `base_iri`, see [`to_turtle`]
+    pub fn export_turtle(&self, base_iri: &str) -> Result<String, ExportError> {
+        to_turtle(self.constraint_schema, &self.snapshot(), base_iri)
+    }
+
+    /// Creates the instances described by the mapping in a single transaction, see
+    /// [`crate::post_generation::import`]
+    #[cfg(feature = "serde")]
+    pub fn import(
+        &mut self,
+        mapping: &ImportMapping,
+        input: &ImportInput,
+    ) -> Result<ExecutionResult, ImportError> {
+        let rows = prepare_import(self.constraint_schema, mapping, input)?;
+        Ok(self.execute(import_transaction(&rows))?)
+    }
+
+    /// The instance and what it reaches as a bundle for another graph, see
+    /// [`crate::post_generation::bundle`]
+    #[cfg(feature = "serde")]
+    pub fn export_bundle(
+        &self,
+        root_id: &Uid,
+        options: &BundleOptions,
+    ) -> Result<SubgraphBundle, BundleError> {
+        export_bundle(
+            self.constraint_schema,
+            |id| self.get(id).cloned(),
+            root_id,
+            options,
+        )
+    }
+    /// Creates the bundle's instances with new ids in a single transaction
+    #[cfg(feature = "serde")]
+    pub fn import_bundle(
+        &mut self,
+        bundle: &SubgraphBundle,
+    ) -> Result<ExecutionResult, BundleError> {
+        let plan = plan_bundle_import(
+            self.constraint_schema,
+            bundle,
+            |id| self.get(id).cloned(),
+            |field_id, value| {
+                self.find_by_field(field_id, value)
+                    .into_iter()
+                    .cloned()
+                    .collect()
+            },
+        )?;
+        Ok(self.execute(plan_transaction(&plan, "Import bundle"))?)
+    }
+    /// Copies the instance and everything it owns through `owning_slots` in a single
+    /// transaction, see [`crate::post_generation::duplicate`]
+    pub fn duplicate(
+        &mut self,
+        id: &Uid,
+        owning_slots: &[Uid],
+        add_to_holders: bool,
+    ) -> Result<ExecutionResult, ElementCreationError> {
+        let plan = plan_duplicate(
+            self.constraint_schema,
+            |id| self.get(id).cloned(),
+            id,
+            owning_slots,
+            add_to_holders,
+        )?;
+        self.execute(plan_transaction(&plan, "Duplicate"))
+    }
+
+    /// Audits the graph for internal consistency, see [`crate::post_generation::integrity`]
+    pub fn check_integrity(&self) -> Vec<IntegrityProblem> {
+        find_problems(self.constraint_schema, &self.snapshot())
+    }
+
+    /// All instances, ordered by id, e.g. to be written as a snapshot
+    pub fn snapshot(&self) -> Vec<StandaloneRGSOConcrete> {
+        let mut instances = self.instances.values().cloned().collect::<Vec<_>>();
+        instances.sort_by_key(|instance| instance.id);
+        instances
+    }
+    /// Same as [`Self::from_instances`], but lists every problem with the instances rather than
+    /// failing on the first blueprint error, and can repair their edges first, see
+    /// [`crate::post_generation::integrity`]
+    pub fn from_instances_validated(
+        constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+        instances: Vec<StandaloneRGSOConcrete>,
+        repair: bool,
+    ) -> Result<(Self, IntegrityReport), LoadError> {
+        let (instances, mut report) = check_instances(constraint_schema, instances, repair);
+        if !report.is_valid() {
+            return Err(LoadError::Invalid(report));
+        }
+        match Self::from_instances(constraint_schema, instances) {
+            Ok(graph) => Ok((graph, report)),
+            Err(error) => {
+                report.problems.push(IntegrityProblem::Invalid(error));
+                Err(LoadError::Invalid(report))
+            }
+        }
+    }
+    #[cfg(feature = "serde")]
+    pub fn write_snapshot(&self, path: impl AsRef<std::path::Path>) -> Result<(), JournalError> {
+        write_snapshot_as(path, &self.snapshot(), IdFormat::Numeric)
+    }
+    #[cfg(feature = "serde")]
+    /// Same as [`Self::write_snapshot`], writing ids in the given format
+    pub fn write_snapshot_as(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        format: IdFormat,
+    ) -> Result<(), JournalError> {
+        write_snapshot_as(path, &self.snapshot(), format)
+    }
+    #[cfg(feature = "serde")]
+    /// Builds a graph from a snapshot written by [`Self::write_snapshot`]. A missing file is an
+    /// empty graph.
+    pub fn read_snapshot(
+        constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, JournalError> {
+        let instances = match std::fs::read_to_string(path) {
+            Ok(json) => instances_from_json(&json)
+                .map_err(|error| JournalError::MalformedSnapshot(error.to_string()))?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error.into()),
+        };
+        Self::from_instances(constraint_schema, instances).map_err(JournalError::InvalidSnapshot)
+    }
+
+    /// Resolves the transaction against the current state of the graph and applies it, see
+    /// [`Self::apply`]
+    pub fn execute(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<ExecutionResult, ElementCreationError> {
+        let temp_id_map = transaction.temp_ids.clone();
+        self.apply(self.resolve(transaction)?)?;
+        Ok(ExecutionResult { temp_id_map })
+    }
+    /// Turns the transaction into the blueprint which would carry it out, without applying it.
+    /// Field edits record their current value and deletions detach the deleted instances from
+    /// the rest of the graph.
+    pub fn resolve(
+        &self,
+        transaction: Transaction,
+    ) -> Result<StandaloneBlueprint, ElementCreationError> {
+        let mut errors = Vec::new();
+        let mut blueprint = StandaloneBlueprint {
+            label: transaction.label,
+            ..StandaloneBlueprint::default()
+        };
+        let mut deleted_ids = Vec::new();
+        for step in transaction.steps {
+            match step {
+                TransactionStep::Create { id, operative_id } => {
+                    match self.constraint_schema.operative_library.get(&operative_id) {
+                        Some(operative) => blueprint.added_instances.push(StandaloneRGSOConcrete {
+                            id,
+                            fields: HashMap::new(),
+                            outgoing_slots: Vec::new(),
+                            incoming_slots: Vec::new(),
+                            operative: operative_id,
+                            template: operative.template_id,
+                        }),
+                        None => errors.push(ElementCreationError::NonexistentOperative {
+                            instance_id: id,
+                            operative_id,
+                        }),
+                    }
+                }
+                TransactionStep::SetField {
+                    id,
+                    field_id,
+                    value,
+                } => {
+                    if let Some(created) = blueprint
+                        .added_instances
+                        .iter_mut()
+                        .find(|instance| instance.id == id)
+                    {
+                        created.fields.insert(field_id, value);
+                        continue;
+                    }
+                    // A field set twice keeps the value it had before the transaction
+                    if let Some((_, field_edit)) = blueprint
+                        .field_updates
+                        .iter_mut()
+                        .find(|(edited_id, edit)| *edited_id == id && edit.field_id == field_id)
+                    {
+                        field_edit.new_value = value;
+                        continue;
+                    }
+                    let Some(existing) = self.instances.get(&id) else {
+                        errors.push(ElementCreationError::NonexistentInstance { id });
+                        continue;
+                    };
+                    let Some(prev_value) = existing.fields.get(&field_id) else {
+                        errors.push(ElementCreationError::NonexistentField {
+                            instance_id: id,
+                            field_id,
+                        });
+                        continue;
+                    };
+                    blueprint.field_updates.push((
+                        id,
+                        HistoryFieldEdit {
+                            instance_id: id,
+                            field_id,
+                            new_value: value,
+                            prev_value: prev_value.clone(),
+                        },
+                    ));
+                }
+                TransactionStep::AddEdge(slot_ref) => {
+                    // Adding back an edge which was removed earlier in the transaction cancels the removal
+                    let removed_before = blueprint.remove_outgoing_updates.len();
+                    blueprint
+                        .remove_outgoing_updates
+                        .retain(|(_, removed)| !same_edge(removed, &slot_ref));
+                    if blueprint.remove_outgoing_updates.len() != removed_before {
+                        blueprint
+                            .remove_incoming_updates
+                            .retain(|(_, removed)| !same_edge(removed, &slot_ref));
+                        continue;
+                    }
+                    match blueprint
+                        .added_instances
+                        .iter_mut()
+                        .find(|instance| instance.id == slot_ref.host_instance_id)
+                    {
+                        Some(host) => host.outgoing_slots.push(slot_ref.clone()),
+                        None => blueprint
+                            .add_outgoing_updates
+                            .push((slot_ref.host_instance_id, slot_ref.clone())),
+                    }
+                    match blueprint
+                        .added_instances
+                        .iter_mut()
+                        .find(|instance| instance.id == slot_ref.target_instance_id)
+                    {
+                        Some(target) => target.incoming_slots.push(slot_ref),
+                        None => blueprint
+                            .add_incoming_updates
+                            .push((slot_ref.target_instance_id, slot_ref)),
+                    }
+                }
+                TransactionStep::RemoveEdge(slot_ref) => {
+                    let pending = blueprint
+                        .added_instances
+                        .iter()
+                        .flat_map(|instance| instance.outgoing_slots.iter())
+                        .chain(
+                            blueprint
+                                .add_outgoing_updates
+                                .iter()
+                                .map(|(_, added)| added),
+                        )
+                        .any(|added| same_edge(added, &slot_ref));
+                    if pending {
+                        blueprint.forget_edge(&slot_ref);
+                        continue;
+                    }
+                    let exists =
+                        self.instances
+                            .get(&slot_ref.host_instance_id)
+                            .is_some_and(|host| {
+                                host.outgoing_slots
+                                    .iter()
+                                    .any(|existing| same_edge(existing, &slot_ref))
+                            });
+                    if !exists {
+                        errors.push(ElementCreationError::NonexistentEdge {
+                            host_id: slot_ref.host_instance_id,
+                            slot_id: slot_ref.slot_id,
+                            target_id: slot_ref.target_instance_id,
+                        });
+                        continue;
+                    }
+                    push_edge_update(
+                        &mut blueprint.remove_outgoing_updates,
+                        slot_ref.host_instance_id,
+                        &slot_ref,
+                    );
+                    push_edge_update(
+                        &mut blueprint.remove_incoming_updates,
+                        slot_ref.target_instance_id,
+                        &slot_ref,
+                    );
+                }
+                TransactionStep::Delete(id) => {
+                    // Instances created in this transaction are simply never added
+                    if let Some(index) = blueprint
+                        .added_instances
+                        .iter()
+                        .position(|instance| instance.id == id)
+                    {
+                        let created = blueprint.added_instances.remove(index);
+                        created
+                            .outgoing_slots
+                            .iter()
+                            .chain(created.incoming_slots.iter())
+                            .for_each(|slot_ref| blueprint.forget_edge(slot_ref));
+                        blueprint
+                            .field_updates
+                            .retain(|(edited_id, _)| *edited_id != id);
+                        continue;
+                    }
+                    if !self.instances.contains_key(&id) {
+                        errors.push(ElementCreationError::NonexistentInstance { id });
+                        continue;
+                    }
+                    if !deleted_ids.contains(&id) {
+                        deleted_ids.push(id);
+                    }
+                }
+            }
+        }
+
+        for id in &deleted_ids {
+            let Some(instance) = self.instances.get(id) else {
+                continue;
+            };
+            blueprint
+                .field_updates
+                .retain(|(edited_id, _)| edited_id != id);
+            instance
+                .incoming_slots
+                .iter()
+                .filter(|slot_ref| !deleted_ids.contains(&slot_ref.host_instance_id))
+                .for_each(|slot_ref| {
+                    push_edge_update(
+                        &mut blueprint.remove_outgoing_updates,
+                        slot_ref.host_instance_id,
+                        slot_ref,
+                    )
+                });
+            instance
+                .outgoing_slots
+                .iter()
+                .filter(|slot_ref| !deleted_ids.contains(&slot_ref.target_instance_id))
+                .for_each(|slot_ref| {
+                    push_edge_update(
+                        &mut blueprint.remove_incoming_updates,
+                        slot_ref.target_instance_id,
+                        slot_ref,
+                    )
+                });
+            // The deleted instance takes its own side of every edge with it, and edges to it
+            // which this transaction would add are dropped
+            blueprint
+                .remove_outgoing_updates
+                .retain(|(host_id, _)| host_id != id);
+            blueprint
+                .remove_incoming_updates
+                .retain(|(target_id, _)| target_id != id);
+            blueprint
+                .add_outgoing_updates
+                .iter()
+                .chain(blueprint.add_incoming_updates.iter())
+                .map(|(_, slot_ref)| slot_ref)
+                .chain(
+                    blueprint
+                        .added_instances
+                        .iter()
+                        .flat_map(|instance| instance.outgoing_slots.iter()),
+                )
+                .filter(|slot_ref| {
+                    slot_ref.host_instance_id == *id || slot_ref.target_instance_id == *id
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+                .iter()
+                .for_each(|slot_ref| blueprint.forget_edge(slot_ref));
+            blueprint.deleted_instances.push(instance.clone());
+        }
+
+        if errors.is_empty() {
+            Ok(blueprint)
+        } else {
+            Err(ElementCreationError::Stack(errors))
+        }
+    }
+
+    /// Checks the blueprint against the schema and, if it conforms, applies it and adds it to the
+    /// undo history
+    pub fn apply(
+        &mut self,
+        blueprint: StandaloneBlueprint,
+    ) -> Result<ChangeSet, ElementCreationError> {
+        self.process(blueprint.clone())?;
+        let change_set = ChangeSet::from_standalone_blueprint(&blueprint, ChangeOrigin::Commit);
+        self.undo.push_back(blueprint);
+        self.redo.clear();
+        Ok(change_set)
+    }
+    /// Returns the change which was reverted, or `None` if there was nothing to undo
+    pub fn undo(&mut self) -> Result<Option<ChangeSet>, ElementCreationError> {
+        let Some(blueprint) = self.undo.pop_back() else {
+            return Ok(None);
+        };
+        let reversed = blueprint.clone().reverse();
+        if let Err(error) = self.process(reversed.clone()) {
+            self.undo.push_back(blueprint);
+            return Err(error);
+        }
+        let change_set = ChangeSet::from_standalone_blueprint(&reversed, ChangeOrigin::Undo);
+        self.redo.push_back(reversed);
+        Ok(Some(change_set))
+    }
+    /// Returns the change which was reapplied, or `None` if there was nothing to redo
+    pub fn redo(&mut self) -> Result<Option<ChangeSet>, ElementCreationError> {
+        let Some(blueprint) = self.redo.pop_back() else {
+            return Ok(None);
+        };
+        let reversed = blueprint.clone().reverse();
+        if let Err(error) = self.process(reversed.clone()) {
+            self.redo.push_back(blueprint);
+            return Err(error);
+        }
+        let change_set = ChangeSet::from_standalone_blueprint(&reversed, ChangeOrigin::Redo);
+        self.undo.push_back(reversed);
+        Ok(Some(change_set))
+    }
+
+    fn process(&mut self, blueprint: StandaloneBlueprint) -> Result<(), ElementCreationError> {
+        self.validate(&blueprint)?;
+        blueprint.touched_ids().into_iter().for_each(|id| {
+            *self.versions.entry(id).or_insert(0) += 1;
+        });
+        blueprint.added_instances.iter().for_each(|instance| {
+            self.field_index
+                .insert_instance(instance.id, instance.fields.clone());
+        });
+        blueprint.deleted_instances.iter().for_each(|instance| {
+            self.field_index
+                .remove_instance(&instance.id, instance.fields.clone());
+        });
+        blueprint.field_updates.iter().for_each(|(id, field_edit)| {
+            self.field_index
+                .remove(id, &field_edit.field_id, &field_edit.prev_value);
+            self.field_index
+                .insert(*id, field_edit.field_id, field_edit.new_value.clone());
+        });
+
+        blueprint.added_instances.into_iter().for_each(|instance| {
+            self.instances.insert(instance.id, instance);
+        });
+        blueprint.deleted_instances.iter().for_each(|instance| {
+            self.instances.remove(&instance.id);
+        });
+        blueprint
+            .add_outgoing_updates
+            .into_iter()
+            .for_each(|(host_id, slot_ref)| {
+                if let Some(host) = self.instances.get_mut(&host_id) {
+                    host.outgoing_slots.push(slot_ref);
+                }
+            });
+        blueprint
+            .add_incoming_updates
+            .into_iter()
+            .for_each(|(target_id, slot_ref)| {
+                if let Some(target) = self.instances.get_mut(&target_id) {
+                    target.incoming_slots.push(slot_ref);
+                }
+            });
+        blueprint
+            .remove_outgoing_updates
+            .iter()
+            .for_each(|(host_id, slot_ref)| {
+                if let Some(host) = self.instances.get_mut(host_id) {
+                    host.outgoing_slots.retain(|existing| {
+                        existing.slot_id != slot_ref.slot_id
+                            || existing.target_instance_id != slot_ref.target_instance_id
+                    });
+                }
+            });
+        blueprint
+            .remove_incoming_updates
+            .iter()
+            .for_each(|(target_id, slot_ref)| {
+                if let Some(target) = self.instances.get_mut(target_id) {
+                    target.incoming_slots.retain(|existing| {
+                        existing.slot_id != slot_ref.slot_id
+                            || existing.host_instance_id != slot_ref.host_instance_id
+                    });
+                }
+            });
+        blueprint
+            .field_updates
+            .into_iter()
+            .for_each(|(id, field_edit)| {
+                if let Some(instance) = self.instances.get_mut(&id) {
+                    instance
+                        .fields
+                        .insert(field_edit.field_id, field_edit.new_value);
+                }
+            });
+        Ok(())
+    }
+
+    fn operative_and_template(
+        &self,
+        instance: &StandaloneRGSOConcrete,
+    ) -> Result<(&'static LibOp, &'static LibTemplate), ElementCreationError> {
+        let operative = self
+            .constraint_schema
+            .operative_library
+            .get(&instance.operative)
+            .ok_or(ElementCreationError::NonexistentOperative {
+                instance_id: instance.id,
+                operative_id: instance.operative,
+            })?;
+        let template = self
+            .constraint_schema
+            .template_library
+            .get(&operative.template_id)
+            .ok_or(ElementCreationError::NonexistentTemplate {
+                instance_id: instance.id,
+                template_id: operative.template_id,
+            })?;
+        Ok((operative, template))
+    }
+
+    fn validate(&self, blueprint: &StandaloneBlueprint) -> Result<(), ElementCreationError> {
+        let deleted_ids = blueprint
+            .deleted_instances
+            .iter()
+            .map(|instance| instance.id)
+            .collect::<HashSet<_>>();
+        let added = blueprint
+            .added_instances
+            .iter()
+            .map(|instance| (instance.id, instance))
+            .collect::<HashMap<_, _>>();
+        // The state of an instance once the blueprint has been applied, ignoring edge and field updates
+        let resolve = |id: &Uid| {
+            if deleted_ids.contains(id) {
+                return None;
+            }
+            added.get(id).copied().or_else(|| self.instances.get(id))
+        };
+
+        let mut errors = blueprint
+            .deleted_instances
+            .iter()
+            .filter(|instance| !self.instances.contains_key(&instance.id))
+            .map(|instance| ElementCreationError::NonexistentInstance { id: instance.id })
+            .collect::<Vec<_>>();
+        // Adding an instance which is still in the graph would silently replace it
+        let mut added_ids = HashSet::new();
+        blueprint
+            .added_instances
+            .iter()
+            .filter(|instance| {
+                !added_ids.insert(instance.id) || self.instances.contains_key(&instance.id)
+            })
+            .for_each(|instance| {
+                errors.push(ElementCreationError::DuplicateInstance { id: instance.id })
+            });
+
+        blueprint.added_instances.iter().for_each(|instance| {
+            let (operative, template) = match self.operative_and_template(instance) {
+                Ok(found) => found,
+                Err(error) => return errors.push(error),
+            };
+            let locked_fields = operative
+                .get_locked_fields_digest(self.constraint_schema)
+                .map(|digest| digest.locked_fields)
+                .unwrap_or_default();
+            template
+                .field_constraints
+                .iter()
+                .filter(|(field_id, _)| !locked_fields.contains_key(field_id))
+                .filter(|(field_id, _)| !instance.fields.contains_key(field_id))
+                .for_each(|(field_id, field)| {
+                    errors.push(
+                        RequiredFieldError {
+                            instance_id: instance.id,
+                            temp_id: None,
+                            operative_id: operative.tag.id,
+                            operative_name: operative.tag.name.clone(),
+                            field_id: *field_id,
+                            field_name: field.tag.name.clone(),
+                        }
+                        .into(),
+                    )
+                });
+            instance.fields.iter().for_each(|(field_id, value)| {
+                if let Err(error) = check_field_value_type(instance.id, template, field_id, value) {
+                    errors.push(error);
+                }
+            });
+        });
+
+        blueprint.field_updates.iter().for_each(|(id, field_edit)| {
+            let Some(instance) = resolve(id) else {
+                return errors.push(ElementCreationError::NonexistentInstance { id: *id });
+            };
+            match self.operative_and_template(instance) {
+                Ok((_, template)) => {
+                    if let Err(error) = check_field_value_type(
+                        *id,
+                        template,
+                        &field_edit.field_id,
+                        &field_edit.new_value,
+                    ) {
+                        errors.push(error);
+                    }
+                }
+                Err(error) => errors.push(error),
+            }
+        });
+
+        // Edges carried by added instances are created along with them
+        let new_edges = blueprint
+            .added_instances
+            .iter()
+            .flat_map(|instance| {
+                instance
+                    .outgoing_slots
+                    .iter()
+                    .map(|slot_ref| (instance.id, slot_ref))
+            })
+            .chain(
+                blueprint
+                    .add_outgoing_updates
+                    .iter()
+                    .map(|(host_id, slot_ref)| (*host_id, slot_ref)),
+            )
+            .collect::<Vec<_>>();
+        // New instances are checked for their required slots even if they hold no edges
+        let mut touched_hosts = added.keys().copied().collect::<HashSet<_>>();
+        new_edges
+            .iter()
+            .copied()
+            .map(|(host_id, slot_ref)| (host_id, slot_ref, true))
+            .chain(
+                blueprint
+                    .remove_outgoing_updates
+                    .iter()
+                    .map(|(host_id, slot_ref)| (*host_id, slot_ref, false)),
+            )
+            .for_each(|(host_id, slot_ref, is_new)| {
+                let Some(host) = resolve(&host_id) else {
+                    return errors.push(ElementCreationError::NonexistentInstance { id: host_id });
+                };
+                let slot = match self.operative_and_template(host) {
+                    Ok((_, template)) => template.operative_slots.get(&slot_ref.slot_id),
+                    Err(error) => return errors.push(error),
+                };
+                let Some(slot) = slot else {
+                    return errors.push(ElementCreationError::NonexistentSlot {
+                        instance_id: host_id,
+                        slot_id: slot_ref.slot_id,
+                    });
+                };
+                touched_hosts.insert(host_id);
+                if is_new {
+                    if let Some(error) =
+                        self.check_slot_target(slot, host_id, slot_ref.target_instance_id, &resolve)
+                    {
+                        errors.push(error);
+                    }
+                }
+            });
+
+        // Instances which survive the blueprint can't keep pointing at deleted ones
+        if !deleted_ids.is_empty() {
+            self.instances
+                .values()
+                .filter(|host| !deleted_ids.contains(&host.id) && !added.contains_key(&host.id))
+                .chain(added.values().copied())
+                .flat_map(|host| host.outgoing_slots.iter())
+                .filter(|slot_ref| deleted_ids.contains(&slot_ref.target_instance_id))
+                .filter(|slot_ref| {
+                    !blueprint
+                        .remove_outgoing_updates
+                        .iter()
+                        .any(|(host_id, removed)| {
+                            *host_id == slot_ref.host_instance_id
+                                && removed.slot_id == slot_ref.slot_id
+                                && removed.target_instance_id == slot_ref.target_instance_id
+                        })
+                })
+                .for_each(|slot_ref| {
+                    errors.push(ElementCreationError::OutgoingElementDoesntExist {
+                        host_id: slot_ref.host_instance_id,
+                        slot_id: slot_ref.slot_id,
+                        target_id: slot_ref.target_instance_id,
+                    })
+                });
+        }
+
+        touched_hosts.iter().for_each(|host_id| {
+            if let Some(host) = resolve(host_id) {
+                errors.extend(self.check_slot_bounds(host, blueprint));
+            }
+        });
+        let field_updates = blueprint.field_updates.iter().collect::<Vec<_>>();
+        errors.extend(check_unique_fields(
+            self.constraint_schema,
+            &self.field_index,
+            blueprint.added_instances.iter().flat_map(|instance| {
+                instance
+                    .fields
+                    .iter()
+                    .map(|(field_id, value)| (instance.id, *field_id, value.clone()))
+            }),
+            &field_updates,
+            &deleted_ids,
+            |_| None,
+        ));
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ElementCreationError::Stack(errors))
+        }
+    }
+
+    fn check_slot_target<'a>(
+        &self,
+        slot: &OperativeSlot,
+        host_id: Uid,
+        target_id: Uid,
+        resolve: &impl Fn(&Uid) -> Option<&'a StandaloneRGSOConcrete>,
+    ) -> Option<ElementCreationError> {
+        let constraint_schema = self.constraint_schema;
+        let Some(target) = resolve(&target_id) else {
+            return Some(ElementCreationError::OutgoingElementDoesntExist {
+                host_id,
+                slot_id: slot.tag.id,
+                target_id,
+            });
+        };
+        let target_operative = match self.operative_and_template(target) {
+            Ok((operative, _)) => operative,
+            Err(error) => return Some(error),
+        };
+        if slot_accepts_operative(constraint_schema, slot, target_operative) {
+            return None;
+        }
+        let mut expected = constraint_schema
+            .operative_library
+            .values()
+            .filter(|operative| slot_accepts_operative(constraint_schema, slot, operative))
+            .map(|operative| operative.tag.name.clone())
+            .collect::<Vec<_>>();
+        expected.sort();
+        Some(ElementCreationError::OutgoingElementIsWrongType {
+            host_id,
+            slot_id: slot.tag.id,
+            target_id,
+            expected,
+            received: target_operative.tag.name.clone(),
+        })
+    }
+
+    // Counts the edges of each slot of the host once the blueprint has been applied
+    fn check_slot_bounds(
+        &self,
+        host: &StandaloneRGSOConcrete,
+        blueprint: &StandaloneBlueprint,
+    ) -> Vec<ElementCreationError> {
+        let Ok((operative, template)) = self.operative_and_template(host) else {
+            return Vec::new();
+        };
+        let mut outgoing: Vec<&SlotRef> = host.outgoing_slots.iter().collect();
+        outgoing.extend(
+            blueprint
+                .add_outgoing_updates
+                .iter()
+                .filter(|(host_id, _)| *host_id == host.id)
+                .map(|(_, slot_ref)| slot_ref),
+        );
+        outgoing.retain(|slot_ref| {
+            !blueprint
+                .remove_outgoing_updates
+                .iter()
+                .any(|(host_id, removed)| {
+                    *host_id == host.id
+                        && removed.slot_id == slot_ref.slot_id
+                        && removed.target_instance_id == slot_ref.target_instance_id
+                })
+        });
+        template
+            .operative_slots
+            .iter()
+            .filter_map(|(slot_id, slot)| {
+                let active_slot = ActiveSlot {
+                    slot,
+                    slotted_instances: outgoing
+                        .iter()
+                        .filter(|slot_ref| slot_ref.slot_id == *slot_id)
+                        .map(|slot_ref| slot_ref.target_instance_id)
+                        .collect(),
+                };
+                if active_slot.check_current_conformity() {
+                    return None;
+                }
+                Some(
+                    BoundCheckError {
+                        instance_id: host.id,
+                        temp_id: None,
+                        operative_id: operative.tag.id,
+                        operative_name: operative.tag.name.clone(),
+                        slot_id: *slot_id,
+                        slot_name: slot.tag.name.clone(),
+                        bounds: slot.bounds.clone(),
+                        attempted: active_slot.slotted_instances.len(),
+                    }
+                    .into(),
+                )
+            })
+            .collect()
+    }
+}
+
+fn same_edge(a: &SlotRef, b: &SlotRef) -> bool {
+    a.host_instance_id == b.host_instance_id
+        && a.slot_id == b.slot_id
+        && a.target_instance_id == b.target_instance_id
+}
+
+fn push_edge_update(updates: &mut Vec<(Uid, SlotRef)>, id: Uid, slot_ref: &SlotRef) {
+    if !updates
+        .iter()
+        .any(|(existing_id, existing)| *existing_id == id && same_edge(existing, slot_ref))
+    {
+        updates.push((id, slot_ref.clone()));
+    }
+}
+
+impl StandaloneBlueprint {
+    // Every instance whose version changes when the blueprint is applied
+    fn touched_ids(&self) -> HashSet<Uid> {
+        self.added_instances
+            .iter()
+            .chain(self.deleted_instances.iter())
+            .map(|instance| instance.id)
+            .chain(
+                self.add_outgoing_updates
+                    .iter()
+                    .chain(self.remove_outgoing_updates.iter())
+                    .chain(self.add_incoming_updates.iter())
+                    .chain(self.remove_incoming_updates.iter())
+                    .map(|(id, _)| *id),
+            )
+            .chain(self.field_updates.iter().map(|(id, _)| *id))
+            .collect()
+    }
+    // Drops an edge which this blueprint would otherwise add
+    fn forget_edge(&mut self, slot_ref: &SlotRef) {
+        self.added_instances.iter_mut().for_each(|instance| {
+            instance
+                .outgoing_slots
+                .retain(|existing| !same_edge(existing, slot_ref));
+            instance
+                .incoming_slots
+                .retain(|existing| !same_edge(existing, slot_ref));
+        });
+        self.add_outgoing_updates
+            .retain(|(_, existing)| !same_edge(existing, slot_ref));
+        self.add_incoming_updates
+            .retain(|(_, existing)| !same_edge(existing, slot_ref));
+    }
+}
+
+#[derive(Debug, Clone)]
+enum TransactionStep {
+    Create {
+        id: Uid,
+        operative_id: Uid,
+    },
+    SetField {
+        id: Uid,
+        field_id: Uid,
+        value: PrimitiveValues,
+    },
+    AddEdge(SlotRef),
+    RemoveEdge(SlotRef),
+    Delete(Uid),
+}
+
+/// A set of changes which is checked and applied as a whole by
+/// [`PlainGraphEnvironment::execute`]. Steps are recorded in order and only resolved against the
+/// graph on execution, so a transaction can be built without access to the graph.
+#[derive(Debug, Clone, Default)]
+pub struct Transaction {
+    steps: Vec<TransactionStep>,
+    temp_ids: HashMap<String, Uid>,
+    label: Option<String>,
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The label under which the transaction appears in the undo history
+    pub fn with_label(label: impl Into<String>) -> Self {
+        Self {
+            label: Some(label.into()),
+            ..Self::default()
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+    // The instances which the transaction changes or slots into another, other than the ones it
+    // creates itself
+    pub(crate) fn existing_ids(&self) -> HashSet<Uid> {
+        let created = self
+            .steps
+            .iter()
+            .filter_map(|step| match step {
+                TransactionStep::Create { id, .. } => Some(*id),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        self.steps
+            .iter()
+            .flat_map(|step| match step {
+                TransactionStep::Create { .. } => vec![],
+                TransactionStep::SetField { id, .. } | TransactionStep::Delete(id) => vec![*id],
+                TransactionStep::AddEdge(slot_ref) | TransactionStep::RemoveEdge(slot_ref) => {
+                    vec![slot_ref.host_instance_id, slot_ref.target_instance_id]
+                }
+            })
+            .filter(|id| !created.contains(id))
+            .collect()
+    }
+    /// Adds an instance of the given operative and returns the id it will have in the graph
+    pub fn create(&mut self, operative_id: Uid) -> Uid {
+        let id = uuid::Uuid::new_v4().as_u128();
+        self.steps
+            .push(TransactionStep::Create { id, operative_id });
+        id
+    }
+    /// Names an instance so that its id can be found in the [`ExecutionResult`]
+    pub fn set_temp_id(&mut self, id: Uid, temp_id: impl Into<String>) -> &mut Self {
+        self.temp_ids.insert(temp_id.into(), id);
+        self
+    }
+    pub fn get_id(&self, temp_id: &str) -> Option<Uid> {
+        self.temp_ids.get(temp_id).copied()
+    }
+    pub fn set_field(&mut self, id: Uid, field_id: Uid, value: PrimitiveValues) -> &mut Self {
+        self.steps.push(TransactionStep::SetField {
+            id,
+            field_id,
+            value,
+        });
+        self
+    }
+    pub fn add_edge(&mut self, host_id: Uid, slot_id: Uid, target_id: Uid) -> &mut Self {
+        self.steps.push(TransactionStep::AddEdge(SlotRef {
+            host_instance_id: host_id,
+            slot_id,
+            target_instance_id: target_id,
+        }));
+        self
+    }
+    pub fn remove_edge(&mut self, host_id: Uid, slot_id: Uid, target_id: Uid) -> &mut Self {
+        self.steps.push(TransactionStep::RemoveEdge(SlotRef {
+            host_instance_id: host_id,
+            slot_id,
+            target_instance_id: target_id,
+        }));
+        self
+    }
+    /// Moves the target from a slot of one host into a slot of another. As with any other
+    /// edits, the bounds are only checked once the whole transaction has been resolved
+    pub fn move_to(
+        &mut self,
+        host_id: Uid,
+        slot_id: Uid,
+        target_id: Uid,
+        to_host_id: Uid,
+        to_slot_id: Uid,
+    ) -> &mut Self {
+        if (host_id, slot_id) == (to_host_id, to_slot_id) {
+            return self;
+        }
+        self.remove_edge(host_id, slot_id, target_id);
+        self.add_edge(to_host_id, to_slot_id, target_id)
+    }
+    /// Deletes the instance along with every edge to or from it
+    pub fn delete(&mut self, id: Uid) -> &mut Self {
+        self.steps.push(TransactionStep::Delete(id));
+        self
+    }
+}
+
+// The test schema is built on the reactive graph's types
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        LABEL_OPERATIVE_ID, LINKS_SLOT_ID, NAME_FIELD_ID, NODE_OPERATIVE_ID, PAIR_OPERATIVE_ID,
+        PARTNER_SLOT_ID, RANK_FIELD_ID, TEST_SCHEMA, TEXT_FIELD_ID,
+    };
+
+    fn create_node(transaction: &mut Transaction, name: &str) -> Uid {
+        let id = transaction.create(NODE_OPERATIVE_ID);
+        transaction.set_field(id, NAME_FIELD_ID, PrimitiveValues::String(name.to_string()));
+        id
+    }
+    fn create_label(transaction: &mut Transaction, text: &str, rank: u32) -> Uid {
+        let id = transaction.create(LABEL_OPERATIVE_ID);
+        transaction
+            .set_field(id, TEXT_FIELD_ID, PrimitiveValues::String(text.to_string()))
+            .set_field(id, RANK_FIELD_ID, PrimitiveValues::Int(rank));
+        id
+    }
+    fn targets(graph: &PlainGraphEnvironment, host_id: &Uid, slot_id: &Uid) -> Vec<Uid> {
+        graph
+            .get(host_id)
+            .unwrap()
+            .outgoing_slots
+            .iter()
+            .filter(|slot_ref| slot_ref.slot_id == *slot_id)
+            .map(|slot_ref| slot_ref.target_instance_id)
+            .collect()
+    }
+    fn name(graph: &PlainGraphEnvironment, id: &Uid) -> Option<PrimitiveValues> {
+        graph.get(id)?.fields.get(&NAME_FIELD_ID).cloned()
+    }
+
+    #[test]
+    fn undo_and_redo_step_through_applied_changes() {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let mut create = Transaction::with_label("create");
+        let node = create_node(&mut create, "a");
+        graph.execute(create).unwrap();
+        let mut rename = Transaction::new();
+        rename.set_field(
+            node,
+            NAME_FIELD_ID,
+            PrimitiveValues::String("b".to_string()),
+        );
+        graph.execute(rename).unwrap();
+        assert_eq!(
+            name(&graph, &node),
+            Some(PrimitiveValues::String("b".to_string()))
+        );
+
+        let undone = graph.undo().unwrap().unwrap();
+        assert_eq!(undone.origin, ChangeOrigin::Undo);
+        assert_eq!(
+            name(&graph, &node),
+            Some(PrimitiveValues::String("a".to_string()))
+        );
+        let undone = graph.undo().unwrap().unwrap();
+        assert_eq!(undone.label.as_deref(), Some("create"));
+        assert_eq!(undone.deleted_instances, vec![node]);
+        assert!(graph.is_empty());
+        assert!(graph.undo().unwrap().is_none());
+
+        let redone = graph.redo().unwrap().unwrap();
+        assert_eq!(redone.origin, ChangeOrigin::Redo);
+        assert_eq!(redone.added_instances, vec![node]);
+        graph.redo().unwrap().unwrap();
+        assert_eq!(
+            name(&graph, &node),
+            Some(PrimitiveValues::String("b".to_string()))
+        );
+        assert!(graph.redo().unwrap().is_none());
+        assert!(graph
+            .find_by_field(&NAME_FIELD_ID, &PrimitiveValues::String("a".to_string()))
+            .is_empty());
+    }
+
+    #[test]
+    fn applying_a_change_clears_the_redo_history() {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let mut first = Transaction::new();
+        create_node(&mut first, "a");
+        graph.execute(first).unwrap();
+        graph.undo().unwrap();
+
+        let mut second = Transaction::new();
+        create_node(&mut second, "b");
+        graph.execute(second).unwrap();
+        assert!(graph.redo().unwrap().is_none());
+        assert_eq!(graph.len(), 1);
+    }
+
+    #[test]
+    fn apply_rejects_adding_an_instance_which_already_exists() {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let mut transaction = Transaction::new();
+        let node = create_node(&mut transaction, "a");
+        graph.execute(transaction).unwrap();
+
+        let mut replacement = graph.get(&node).unwrap().clone();
+        replacement
+            .fields
+            .insert(NAME_FIELD_ID, PrimitiveValues::String("b".to_string()));
+        let error = graph
+            .apply(StandaloneBlueprint {
+                added_instances: vec![replacement],
+                ..StandaloneBlueprint::default()
+            })
+            .unwrap_err();
+
+        assert!(matches!(
+            error.flatten().as_slice(),
+            [ElementCreationError::DuplicateInstance { id }] if *id == node
+        ));
+        assert_eq!(
+            name(&graph, &node),
+            Some(PrimitiveValues::String("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn validate_rejects_blueprints_which_break_the_schema() {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let mut transaction = Transaction::new();
+        let node = create_node(&mut transaction, "a");
+        let pair = transaction.create(PAIR_OPERATIVE_ID);
+        transaction.add_edge(pair, PARTNER_SLOT_ID, node);
+        graph.execute(transaction).unwrap();
+
+        // A node without its name
+        let mut unnamed = Transaction::new();
+        unnamed.create(NODE_OPERATIVE_ID);
+        let error = graph.execute(unnamed).unwrap_err();
+        let errors = error.flatten();
+        assert!(matches!(
+            errors.as_slice(),
+            [ElementCreationError::RequiredFieldIsEmpty(_)]
+        ));
+
+        // A pair whose partner isn't a node, and which would hold two partners
+        let mut wrong_target = Transaction::new();
+        wrong_target.add_edge(pair, PARTNER_SLOT_ID, pair);
+        let error = graph.execute(wrong_target).unwrap_err();
+        let errors = error.flatten();
+        assert!(errors.iter().any(|error| matches!(
+            error,
+            ElementCreationError::OutgoingElementIsWrongType { .. }
+        )));
+        assert!(errors
+            .iter()
+            .any(|error| matches!(error, ElementCreationError::BoundCheckOutOfRange(_))));
+
+        // A field of the wrong type
+        let mut wrong_type = Transaction::new();
+        wrong_type.set_field(node, NAME_FIELD_ID, PrimitiveValues::Bool(true));
+        let error = graph.execute(wrong_type).unwrap_err();
+        let errors = error.flatten();
+        assert!(matches!(
+            errors.as_slice(),
+            [ElementCreationError::FieldValueTypeMismatch { .. }]
+        ));
+
+        // A node which is still the partner of a pair
+        let mut delete = Transaction::new();
+        delete.delete(node);
+        assert!(graph.execute(delete).is_err());
+
+        // Two labels sharing a text
+        let mut labels = Transaction::new();
+        create_label(&mut labels, "x", 1);
+        create_label(&mut labels, "x", 2);
+        let error = graph.execute(labels).unwrap_err();
+        let errors = error.flatten();
+        assert!(matches!(
+            errors.as_slice(),
+            [ElementCreationError::UniqueFieldViolation(_)]
+        ));
+
+        // None of the rejected changes were applied
+        assert_eq!(graph.len(), 2);
+        assert_eq!(targets(&graph, &pair, &PARTNER_SLOT_ID), vec![node]);
+        assert!(graph.check_integrity().is_empty());
+    }
+
+    #[test]
+    fn move_to_swaps_the_partners_of_two_pairs() {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let mut transaction = Transaction::new();
+        let a = create_node(&mut transaction, "a");
+        let b = create_node(&mut transaction, "b");
+        let first = transaction.create(PAIR_OPERATIVE_ID);
+        let second = transaction.create(PAIR_OPERATIVE_ID);
+        transaction
+            .add_edge(first, PARTNER_SLOT_ID, a)
+            .add_edge(second, PARTNER_SLOT_ID, b);
+        graph.execute(transaction).unwrap();
+
+        // Each pair only ever holds a single partner once both moves are done
+        let mut swap = Transaction::new();
+        swap.move_to(first, PARTNER_SLOT_ID, a, second, PARTNER_SLOT_ID)
+            .move_to(second, PARTNER_SLOT_ID, b, first, PARTNER_SLOT_ID);
+        graph.execute(swap).unwrap();
+
+        assert_eq!(targets(&graph, &first, &PARTNER_SLOT_ID), vec![b]);
+        assert_eq!(targets(&graph, &second, &PARTNER_SLOT_ID), vec![a]);
+        assert!(graph.check_integrity().is_empty());
+    }
+
+    #[test]
+    fn move_to_continues_from_an_earlier_move() {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let mut transaction = Transaction::new();
+        let [first, second, third, target] =
+            ["first", "second", "third", "target"].map(|name| create_node(&mut transaction, name));
+        transaction.add_edge(first, LINKS_SLOT_ID, target);
+        graph.execute(transaction).unwrap();
+
+        let mut moves = Transaction::new();
+        moves
+            .move_to(first, LINKS_SLOT_ID, target, second, LINKS_SLOT_ID)
+            .move_to(second, LINKS_SLOT_ID, target, third, LINKS_SLOT_ID);
+        graph.execute(moves).unwrap();
+
+        assert!(targets(&graph, &first, &LINKS_SLOT_ID).is_empty());
+        assert!(targets(&graph, &second, &LINKS_SLOT_ID).is_empty());
+        assert_eq!(targets(&graph, &third, &LINKS_SLOT_ID), vec![target]);
+        assert!(graph.check_integrity().is_empty());
+    }
+}
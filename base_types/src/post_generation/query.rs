@@ -0,0 +1,1558 @@
+//! A small Cypher-like query language which is evaluated in-process against the nodes of a graph.
+//!
+//! ```text
+//! MATCH (s:Sentence)-[words]->(w:Word) WHERE w.display = "Today" RETURN s
+//! MATCH (w:Word)<-[words]-(s:Sentence) RETURN s, count(w) AS word_count LIMIT 10
+//! ```
+//!
+//! Labels are resolved against operative names (including ancestor operatives) and template names,
+//! slot names are resolved against the slots defined on templates, and properties are resolved
+//! against the field names of the matched node's template.
+//!
+//! Supported clauses:
+//!  - `MATCH` one or more comma-separated paths. Edges are written `-[slot]->` or `<-[slot]-`,
+//!    with `-->`/`<--` or `-[]->` matching any slot.
+//!  - `WHERE` with `AND`, `OR`, `NOT`, parentheses, `=`, `<>`, `<`, `<=`, `>`, `>=` and `CONTAINS`.
+//!  - `RETURN` variables, properties and the aggregates `count`, `sum`, `min`, `max` and `avg`,
+//!    each optionally followed by `AS alias`. Non-aggregate items act as the grouping key.
+//!  - `LIMIT n`.
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use crate::{
+    common::Uid,
+    constraint_schema::{ConstraintSchema, LibraryOperative, LibraryTemplate},
+    constraint_schema_item::ConstraintSchemaItem,
+    post_generation::StandaloneRGSOConcrete,
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryError {
+    UnexpectedEnd,
+    UnexpectedToken {
+        position: usize,
+        found: String,
+        expected: String,
+    },
+    UnterminatedString {
+        position: usize,
+    },
+    UnknownLabel(String),
+    UnknownSlot(String),
+    UnknownField(String),
+    UnboundVariable(String),
+    NonNumericAggregate {
+        function: String,
+        field: String,
+    },
+    AggregateOverflow {
+        function: String,
+    },
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::UnexpectedEnd => write!(f, "Unexpected end of query"),
+            QueryError::UnexpectedToken {
+                position,
+                found,
+                expected,
+            } => write!(
+                f,
+                "Unexpected token '{}' at position {}, expected {}",
+                found, position, expected
+            ),
+            QueryError::UnterminatedString { position } => {
+                write!(f, "Unterminated string starting at position {}", position)
+            }
+            QueryError::UnknownLabel(label) => {
+                write!(f, "No operative or template named '{}'", label)
+            }
+            QueryError::UnknownSlot(slot) => write!(f, "No slot named '{}'", slot),
+            QueryError::UnknownField(field) => write!(f, "No field named '{}'", field),
+            QueryError::UnboundVariable(variable) => {
+                write!(f, "Variable '{}' is not bound in MATCH", variable)
+            }
+            QueryError::NonNumericAggregate { function, field } => write!(
+                f,
+                "{}() can only be applied to Int fields, '{}' is not an Int field",
+                function, field
+            ),
+            QueryError::AggregateOverflow { function } => {
+                write!(f, "{}() overflowed the range of an integer", function)
+            }
+        }
+    }
+}
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryValue {
+    Node(Uid),
+    Value(PrimitiveValues),
+    Integer(i64),
+    Float(f64),
+    Null,
+}
+
+impl QueryValue {
+    fn as_integer(&self) -> Option<i64> {
+        match self {
+            QueryValue::Integer(value) => Some(*value),
+            QueryValue::Value(PrimitiveValues::Int(value)) => Some(*value as i64),
+            _ => None,
+        }
+    }
+    fn loosely_equals(&self, other: &QueryValue) -> bool {
+        match (self.as_integer(), other.as_integer()) {
+            (Some(left), Some(right)) => left == right,
+            _ => self != &QueryValue::Null && self == other,
+        }
+    }
+}
+
+impl Display for QueryValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryValue::Node(id) => write!(f, "{}", id),
+            QueryValue::Value(value) => write!(f, "{}", value),
+            QueryValue::Integer(value) => write!(f, "{}", value),
+            QueryValue::Float(value) => write!(f, "{}", value),
+            QueryValue::Null => write!(f, "null"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<QueryValue>>,
+}
+
+impl QueryResult {
+    /// Collects the ids of all nodes returned in the given column
+    pub fn node_ids(&self, column: &str) -> Vec<Uid> {
+        let Some(index) = self.columns.iter().position(|name| name == column) else {
+            return Vec::new();
+        };
+        self.rows
+            .iter()
+            .filter_map(|row| match row.get(index) {
+                Some(QueryValue::Node(id)) => Some(*id),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Tokens
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Int(i64),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Colon,
+    Comma,
+    Dot,
+    Star,
+    Dash,
+    Arrow,
+    LeftArrow,
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "{}", ident),
+            Token::Str(string) => write!(f, "\"{}\"", string),
+            Token::Int(int) => write!(f, "{}", int),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::LBracket => write!(f, "["),
+            Token::RBracket => write!(f, "]"),
+            Token::Colon => write!(f, ":"),
+            Token::Comma => write!(f, ","),
+            Token::Dot => write!(f, "."),
+            Token::Star => write!(f, "*"),
+            Token::Dash => write!(f, "-"),
+            Token::Arrow => write!(f, "->"),
+            Token::LeftArrow => write!(f, "<-"),
+            Token::Eq => write!(f, "="),
+            Token::Neq => write!(f, "<>"),
+            Token::Lt => write!(f, "<"),
+            Token::Lte => write!(f, "<="),
+            Token::Gt => write!(f, ">"),
+            Token::Gte => write!(f, ">="),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let chars = source.char_indices().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let peek = |i: usize| chars.get(i).map(|(_, c)| *c);
+    while let Some((position, c)) = chars.get(i).cloned() {
+        let (token, consumed) = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => (Token::LParen, 1),
+            ')' => (Token::RParen, 1),
+            '[' => (Token::LBracket, 1),
+            ']' => (Token::RBracket, 1),
+            ':' => (Token::Colon, 1),
+            ',' => (Token::Comma, 1),
+            '.' => (Token::Dot, 1),
+            '*' => (Token::Star, 1),
+            '=' => (Token::Eq, 1),
+            '-' if peek(i + 1) == Some('>') => (Token::Arrow, 2),
+            '-' => (Token::Dash, 1),
+            '<' if peek(i + 1) == Some('-') => (Token::LeftArrow, 2),
+            '<' if peek(i + 1) == Some('=') => (Token::Lte, 2),
+            '<' if peek(i + 1) == Some('>') => (Token::Neq, 2),
+            '<' => (Token::Lt, 1),
+            '>' if peek(i + 1) == Some('=') => (Token::Gte, 2),
+            '>' => (Token::Gt, 1),
+            '!' if peek(i + 1) == Some('=') => (Token::Neq, 2),
+            '"' | '\'' => {
+                let mut value = String::new();
+                let mut j = i + 1;
+                loop {
+                    match peek(j) {
+                        None => return Err(QueryError::UnterminatedString { position }),
+                        Some('\\') => {
+                            match peek(j + 1) {
+                                Some('n') => value.push('\n'),
+                                Some('t') => value.push('\t'),
+                                Some(escaped) => value.push(escaped),
+                                None => return Err(QueryError::UnterminatedString { position }),
+                            }
+                            j += 2;
+                        }
+                        Some(end) if end == c => break,
+                        Some(other) => {
+                            value.push(other);
+                            j += 1;
+                        }
+                    }
+                }
+                (Token::Str(value), j + 1 - i)
+            }
+            c if c.is_ascii_digit() => {
+                let mut j = i;
+                while peek(j).is_some_and(|c| c.is_ascii_digit()) {
+                    j += 1;
+                }
+                let digits = chars[i..j].iter().map(|(_, c)| c).collect::<String>();
+                let value = digits
+                    .parse::<i64>()
+                    .map_err(|_| QueryError::UnexpectedToken {
+                        position,
+                        found: digits.clone(),
+                        expected: "an integer which fits in 64 bits".to_string(),
+                    })?;
+                (Token::Int(value), j - i)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut j = i;
+                while peek(j).is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                    j += 1;
+                }
+                let ident = chars[i..j].iter().map(|(_, c)| c).collect::<String>();
+                (Token::Ident(ident), j - i)
+            }
+            other => {
+                return Err(QueryError::UnexpectedToken {
+                    position,
+                    found: other.to_string(),
+                    expected: "a valid token".to_string(),
+                })
+            }
+        };
+        tokens.push((position, token));
+        i += consumed;
+    }
+    Ok(tokens)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Syntax tree
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+struct NodePattern {
+    variable: String,
+    label: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Outgoing,
+    Incoming,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RelPattern {
+    slot: Option<String>,
+    direction: Direction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PathPattern {
+    start: NodePattern,
+    steps: Vec<(RelPattern, NodePattern)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    String(String),
+    Int(i64),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Literal(Literal),
+    Variable(String),
+    Property { variable: String, field: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CompareOp, Operand),
+    Truthy(Operand),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Aggregate {
+    Count,
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+impl Aggregate {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "count" => Some(Aggregate::Count),
+            "sum" => Some(Aggregate::Sum),
+            "min" => Some(Aggregate::Min),
+            "max" => Some(Aggregate::Max),
+            "avg" => Some(Aggregate::Avg),
+            _ => None,
+        }
+    }
+    fn name(&self) -> &'static str {
+        match self {
+            Aggregate::Count => "count",
+            Aggregate::Sum => "sum",
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+            Aggregate::Avg => "avg",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Projection {
+    Value(Operand),
+    // An operand of None represents `*`
+    Aggregate(Aggregate, Option<Operand>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ReturnItem {
+    projection: Projection,
+    column: String,
+}
+
+/// A parsed query which can be executed any number of times
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    patterns: Vec<PathPattern>,
+    filter: Option<Expr>,
+    returns: Vec<ReturnItem>,
+    limit: Option<usize>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Parser
+// ------------------------------------------------------------------------------------------------
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    anonymous_count: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+    fn next(&mut self) -> Result<Token, QueryError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .map(|(_, token)| token.clone())
+            .ok_or(QueryError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+    fn error(&self, expected: &str) -> QueryError {
+        match self.tokens.get(self.pos) {
+            Some((position, token)) => QueryError::UnexpectedToken {
+                position: *position,
+                found: token.to_string(),
+                expected: expected.to_string(),
+            },
+            None => QueryError::UnexpectedEnd,
+        }
+    }
+    fn expect(&mut self, expected: Token) -> Result<(), QueryError> {
+        if self.peek() == Some(&expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("'{}'", expected)))
+        }
+    }
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword))
+    }
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.is_keyword(keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), QueryError> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(keyword))
+        }
+    }
+    fn expect_ident(&mut self, expected: &str) -> Result<String, QueryError> {
+        match self.peek() {
+            Some(Token::Ident(ident)) => {
+                let ident = ident.clone();
+                self.pos += 1;
+                Ok(ident)
+            }
+            _ => Err(self.error(expected)),
+        }
+    }
+
+    fn parse_query(&mut self) -> Result<Query, QueryError> {
+        self.expect_keyword("MATCH")?;
+        let mut patterns = vec![self.parse_path()?];
+        while self.peek() == Some(&Token::Comma) {
+            self.pos += 1;
+            patterns.push(self.parse_path()?);
+        }
+
+        let filter = if self.eat_keyword("WHERE") {
+            Some(self.parse_or()?)
+        } else {
+            None
+        };
+
+        self.expect_keyword("RETURN")?;
+        let mut returns = vec![self.parse_return_item()?];
+        while self.peek() == Some(&Token::Comma) {
+            self.pos += 1;
+            returns.push(self.parse_return_item()?);
+        }
+
+        let limit = if self.eat_keyword("LIMIT") {
+            match self.next()? {
+                Token::Int(limit) => Some(limit as usize),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("an integer limit"));
+                }
+            }
+        } else {
+            None
+        };
+
+        if self.peek().is_some() {
+            return Err(self.error("end of query"));
+        }
+        Ok(Query {
+            patterns,
+            filter,
+            returns,
+            limit,
+        })
+    }
+
+    fn parse_path(&mut self) -> Result<PathPattern, QueryError> {
+        let start = self.parse_node()?;
+        let mut steps = Vec::new();
+        while matches!(self.peek(), Some(Token::Dash) | Some(Token::LeftArrow)) {
+            let rel = self.parse_rel()?;
+            steps.push((rel, self.parse_node()?));
+        }
+        Ok(PathPattern { start, steps })
+    }
+
+    fn parse_node(&mut self) -> Result<NodePattern, QueryError> {
+        self.expect(Token::LParen)?;
+        let variable = match self.peek() {
+            Some(Token::Ident(ident)) => {
+                let ident = ident.clone();
+                self.pos += 1;
+                ident
+            }
+            _ => {
+                // Leading space keeps anonymous variables from colliding with user variables
+                self.anonymous_count += 1;
+                format!(" anonymous_{}", self.anonymous_count)
+            }
+        };
+        let label = if self.peek() == Some(&Token::Colon) {
+            self.pos += 1;
+            Some(self.expect_ident("a label")?)
+        } else {
+            None
+        };
+        self.expect(Token::RParen)?;
+        Ok(NodePattern { variable, label })
+    }
+
+    fn parse_rel(&mut self) -> Result<RelPattern, QueryError> {
+        let incoming = match self.next()? {
+            Token::LeftArrow => true,
+            Token::Dash => false,
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("'-' or '<-'"));
+            }
+        };
+        let mut slot = None;
+        if self.peek() == Some(&Token::LBracket) {
+            self.pos += 1;
+            if self.peek() == Some(&Token::Colon) {
+                self.pos += 1;
+            }
+            if let Some(Token::Ident(ident)) = self.peek() {
+                slot = Some(ident.clone());
+                self.pos += 1;
+            }
+            self.expect(Token::RBracket)?;
+        }
+        if incoming {
+            self.expect(Token::Dash)?;
+            Ok(RelPattern {
+                slot,
+                direction: Direction::Incoming,
+            })
+        } else {
+            self.expect(Token::Arrow)?;
+            Ok(RelPattern {
+                slot,
+                direction: Direction::Outgoing,
+            })
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_and()?;
+        while self.eat_keyword("OR") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_not()?;
+        while self.eat_keyword("AND") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, QueryError> {
+        if self.eat_keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.parse_or()?;
+            self.expect(Token::RParen)?;
+            return Ok(expr);
+        }
+        let left = self.parse_operand()?;
+        let op = match self.peek() {
+            Some(Token::Eq) => CompareOp::Eq,
+            Some(Token::Neq) => CompareOp::Neq,
+            Some(Token::Lt) => CompareOp::Lt,
+            Some(Token::Lte) => CompareOp::Lte,
+            Some(Token::Gt) => CompareOp::Gt,
+            Some(Token::Gte) => CompareOp::Gte,
+            Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case("CONTAINS") => {
+                CompareOp::Contains
+            }
+            _ => return Ok(Expr::Truthy(left)),
+        };
+        self.pos += 1;
+        let right = self.parse_operand()?;
+        Ok(Expr::Compare(left, op, right))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, QueryError> {
+        match self.next()? {
+            Token::Str(value) => Ok(Operand::Literal(Literal::String(value))),
+            Token::Int(value) => Ok(Operand::Literal(Literal::Int(value))),
+            Token::Dash => match self.next()? {
+                Token::Int(value) => Ok(Operand::Literal(Literal::Int(-value))),
+                _ => {
+                    self.pos -= 1;
+                    Err(self.error("an integer"))
+                }
+            },
+            Token::Ident(ident) if ident.eq_ignore_ascii_case("true") => {
+                Ok(Operand::Literal(Literal::Bool(true)))
+            }
+            Token::Ident(ident) if ident.eq_ignore_ascii_case("false") => {
+                Ok(Operand::Literal(Literal::Bool(false)))
+            }
+            Token::Ident(ident) if ident.eq_ignore_ascii_case("null") => {
+                Ok(Operand::Literal(Literal::Null))
+            }
+            Token::Ident(variable) => self.parse_variable_or_property(variable),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("a literal, variable or property"))
+            }
+        }
+    }
+
+    fn parse_variable_or_property(&mut self, variable: String) -> Result<Operand, QueryError> {
+        if self.peek() == Some(&Token::Dot) {
+            self.pos += 1;
+            let field = self.expect_ident("a field name")?;
+            Ok(Operand::Property { variable, field })
+        } else {
+            Ok(Operand::Variable(variable))
+        }
+    }
+
+    fn parse_return_item(&mut self) -> Result<ReturnItem, QueryError> {
+        let name = self.expect_ident("a variable or aggregate")?;
+        let aggregate = Aggregate::from_name(&name).filter(|_| self.peek() == Some(&Token::LParen));
+        let (projection, default_column) = if let Some(aggregate) = aggregate {
+            self.expect(Token::LParen)?;
+            let (operand, inner) =
+                if aggregate == Aggregate::Count && self.peek() == Some(&Token::Star) {
+                    self.pos += 1;
+                    (None, "*".to_string())
+                } else {
+                    let variable = self.expect_ident("a variable")?;
+                    let operand = self.parse_variable_or_property(variable)?;
+                    let inner = operand_column(&operand);
+                    (Some(operand), inner)
+                };
+            self.expect(Token::RParen)?;
+            (
+                Projection::Aggregate(aggregate, operand),
+                format!("{}({})", aggregate.name(), inner),
+            )
+        } else {
+            let operand = self.parse_variable_or_property(name)?;
+            let column = operand_column(&operand);
+            (Projection::Value(operand), column)
+        };
+        let column = if self.eat_keyword("AS") {
+            self.expect_ident("an alias")?
+        } else {
+            default_column
+        };
+        Ok(ReturnItem { projection, column })
+    }
+}
+
+fn operand_column(operand: &Operand) -> String {
+    match operand {
+        Operand::Variable(variable) => variable.clone(),
+        Operand::Property { variable, field } => format!("{}.{}", variable, field),
+        Operand::Literal(literal) => format!("{:?}", literal),
+    }
+}
+
+impl Query {
+    pub fn parse(source: &str) -> Result<Self, QueryError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+            anonymous_count: 0,
+        };
+        parser.parse_query()
+    }
+
+    /// Executes the query against a set of nodes, resolving names through the given schema
+    pub fn execute(
+        &self,
+        schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+        nodes: &[StandaloneRGSOConcrete],
+    ) -> Result<QueryResult, QueryError> {
+        let view = GraphView::new(schema, nodes);
+        let plan = view.plan(self)?;
+
+        let mut rows = vec![Binding::new()];
+        for (pattern, slot_ids) in self.patterns.iter().zip(plan.iter()) {
+            rows = view.match_path(pattern, slot_ids, rows);
+        }
+        if let Some(filter) = &self.filter {
+            rows.retain(|row| view.evaluate(filter, row));
+        }
+
+        let mut result_rows = if self
+            .returns
+            .iter()
+            .any(|item| matches!(item.projection, Projection::Aggregate(..)))
+        {
+            view.aggregate(&self.returns, &rows)?
+        } else {
+            rows.iter()
+                .map(|row| {
+                    self.returns
+                        .iter()
+                        .map(|item| match &item.projection {
+                            Projection::Value(operand) => view.operand_value(operand, row),
+                            Projection::Aggregate(..) => QueryValue::Null,
+                        })
+                        .collect()
+                })
+                .collect::<Vec<_>>()
+        };
+        if let Some(limit) = self.limit {
+            result_rows.truncate(limit);
+        }
+        Ok(QueryResult {
+            columns: self
+                .returns
+                .iter()
+                .map(|item| item.column.clone())
+                .collect(),
+            rows: result_rows,
+        })
+    }
+}
+
+/// Parses and executes a query in one step
+pub fn execute_query(
+    schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    nodes: &[StandaloneRGSOConcrete],
+    query: &str,
+) -> Result<QueryResult, QueryError> {
+    Query::parse(query)?.execute(schema, nodes)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Evaluation
+// ------------------------------------------------------------------------------------------------
+
+type Binding = BTreeMap<String, Uid>;
+
+struct GraphView<'a> {
+    schema: &'a ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    nodes: BTreeMap<Uid, &'a StandaloneRGSOConcrete>,
+}
+
+impl<'a> GraphView<'a> {
+    fn new(
+        schema: &'a ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+        nodes: &'a [StandaloneRGSOConcrete],
+    ) -> Self {
+        Self {
+            schema,
+            nodes: nodes.iter().map(|node| (node.id, node)).collect(),
+        }
+    }
+
+    fn templates(
+        &self,
+    ) -> impl Iterator<Item = &'a LibraryTemplate<PrimitiveTypes, PrimitiveValues>> {
+        self.schema.template_library.values()
+    }
+
+    fn operatives(
+        &self,
+    ) -> impl Iterator<Item = &'a LibraryOperative<PrimitiveTypes, PrimitiveValues>> {
+        self.schema.operative_library.values()
+    }
+
+    /// Checks every name used in the query against the schema and resolves slot names to ids.
+    fn plan(&self, query: &Query) -> Result<Vec<Vec<Option<Vec<Uid>>>>, QueryError> {
+        let mut bound = Vec::new();
+        let check_label = |label: &Option<String>| -> Result<(), QueryError> {
+            match label {
+                Some(label)
+                    if !self.operatives().any(|op| op.tag.name == *label)
+                        && !self.templates().any(|template| template.tag.name == *label) =>
+                {
+                    Err(QueryError::UnknownLabel(label.clone()))
+                }
+                _ => Ok(()),
+            }
+        };
+
+        let mut plan = Vec::new();
+        for pattern in &query.patterns {
+            check_label(&pattern.start.label)?;
+            bound.push(pattern.start.variable.clone());
+            let mut slot_ids = Vec::new();
+            for (rel, node) in &pattern.steps {
+                check_label(&node.label)?;
+                bound.push(node.variable.clone());
+                slot_ids.push(match &rel.slot {
+                    Some(slot_name) => {
+                        let ids = self
+                            .templates()
+                            .flat_map(|template| template.operative_slots.iter())
+                            .filter(|(_, slot)| slot.tag.name == *slot_name)
+                            .map(|(slot_id, _)| *slot_id)
+                            .collect::<Vec<_>>();
+                        if ids.is_empty() {
+                            return Err(QueryError::UnknownSlot(slot_name.clone()));
+                        }
+                        Some(ids)
+                    }
+                    None => None,
+                });
+            }
+            plan.push(slot_ids);
+        }
+
+        let check_operand = |operand: &Operand| -> Result<(), QueryError> {
+            match operand {
+                Operand::Literal(_) => Ok(()),
+                Operand::Variable(variable) | Operand::Property { variable, .. }
+                    if !bound.contains(variable) =>
+                {
+                    Err(QueryError::UnboundVariable(variable.clone()))
+                }
+                Operand::Property { field, .. } if self.field_types(field).is_empty() => {
+                    Err(QueryError::UnknownField(field.clone()))
+                }
+                _ => Ok(()),
+            }
+        };
+        if let Some(filter) = &query.filter {
+            let mut stack = vec![filter];
+            while let Some(expr) = stack.pop() {
+                match expr {
+                    Expr::Or(left, right) | Expr::And(left, right) => {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                    Expr::Not(inner) => stack.push(inner),
+                    Expr::Compare(left, _, right) => {
+                        check_operand(left)?;
+                        check_operand(right)?;
+                    }
+                    Expr::Truthy(operand) => check_operand(operand)?,
+                }
+            }
+        }
+        for item in &query.returns {
+            match &item.projection {
+                Projection::Value(operand) => check_operand(operand)?,
+                Projection::Aggregate(_, None) => {}
+                Projection::Aggregate(aggregate, Some(operand)) => {
+                    check_operand(operand)?;
+                    if *aggregate == Aggregate::Count {
+                        continue;
+                    }
+                    let is_numeric = match operand {
+                        Operand::Property { field, .. } => {
+                            self.field_types(field).iter().all(|value_type| {
+                                matches!(value_type, PrimitiveTypes::Int)
+                                    || matches!(value_type, PrimitiveTypes::Option(inner) if **inner == PrimitiveTypes::Int)
+                            })
+                        }
+                        _ => false,
+                    };
+                    if !is_numeric {
+                        return Err(QueryError::NonNumericAggregate {
+                            function: aggregate.name().to_string(),
+                            field: operand_column(operand),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(plan)
+    }
+
+    fn field_types(&self, field_name: &str) -> Vec<&'a PrimitiveTypes> {
+        self.templates()
+            .flat_map(|template| template.field_constraints.values())
+            .filter(|field| field.tag.name == field_name)
+            .map(|field| &field.value_type)
+            .collect()
+    }
+
+    fn matches_label(&self, node: &StandaloneRGSOConcrete, label: &Option<String>) -> bool {
+        let Some(label) = label else {
+            return true;
+        };
+        if self
+            .schema
+            .template_library
+            .get(&node.template)
+            .is_some_and(|template| template.tag.name == *label)
+        {
+            return true;
+        }
+        let mut next_operative_id = Some(node.operative);
+        while let Some(operative_id) = next_operative_id {
+            let Some(operative) = self.schema.operative_library.get(&operative_id) else {
+                return false;
+            };
+            if operative.tag.name == *label {
+                return true;
+            }
+            next_operative_id = operative.parent_operative_id;
+        }
+        false
+    }
+
+    fn bind(
+        &self,
+        row: &Binding,
+        node_pattern: &NodePattern,
+        candidate: &StandaloneRGSOConcrete,
+    ) -> Option<Binding> {
+        if !self.matches_label(candidate, &node_pattern.label) {
+            return None;
+        }
+        match row.get(&node_pattern.variable) {
+            Some(existing) if *existing != candidate.id => None,
+            Some(_) => Some(row.clone()),
+            None => {
+                let mut row = row.clone();
+                row.insert(node_pattern.variable.clone(), candidate.id);
+                Some(row)
+            }
+        }
+    }
+
+    fn match_path(
+        &self,
+        pattern: &PathPattern,
+        slot_ids: &[Option<Vec<Uid>>],
+        seeds: Vec<Binding>,
+    ) -> Vec<Binding> {
+        let mut rows = seeds
+            .iter()
+            .flat_map(|row| {
+                self.nodes
+                    .values()
+                    .filter_map(|node| self.bind(row, &pattern.start, node))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut previous = &pattern.start;
+        for ((rel, node_pattern), allowed_slots) in pattern.steps.iter().zip(slot_ids) {
+            rows = rows
+                .iter()
+                .flat_map(|row| {
+                    let Some(from) = row
+                        .get(&previous.variable)
+                        .and_then(|id| self.nodes.get(id))
+                    else {
+                        return Vec::new();
+                    };
+                    let slot_allowed = |slot_id: &Uid| {
+                        allowed_slots
+                            .as_ref()
+                            .is_none_or(|allowed| allowed.contains(slot_id))
+                    };
+                    let neighbours = match rel.direction {
+                        Direction::Outgoing => from
+                            .outgoing_slots
+                            .iter()
+                            .filter(|slot_ref| slot_allowed(&slot_ref.slot_id))
+                            .map(|slot_ref| slot_ref.target_instance_id)
+                            .collect::<Vec<_>>(),
+                        Direction::Incoming => from
+                            .incoming_slots
+                            .iter()
+                            .filter(|slot_ref| slot_allowed(&slot_ref.slot_id))
+                            .map(|slot_ref| slot_ref.host_instance_id)
+                            .collect::<Vec<_>>(),
+                    };
+                    neighbours
+                        .iter()
+                        .filter_map(|id| self.nodes.get(id))
+                        .filter_map(|neighbour| self.bind(row, node_pattern, neighbour))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            previous = node_pattern;
+        }
+        rows
+    }
+
+    fn field_value(&self, node_id: &Uid, field_name: &str) -> QueryValue {
+        let Some(node) = self.nodes.get(node_id) else {
+            return QueryValue::Null;
+        };
+        let Some(field_id) = self
+            .schema
+            .template_library
+            .get(&node.template)
+            .and_then(|template| {
+                template
+                    .field_constraints
+                    .iter()
+                    .find(|(_, field)| field.tag.name == field_name)
+            })
+            .map(|(field_id, _)| *field_id)
+        else {
+            return QueryValue::Null;
+        };
+        let mut value = node.fields.get(&field_id).cloned().or_else(|| {
+            // Fields locked by the operative are not stored on the node itself
+            self.schema
+                .operative_library
+                .get(&node.operative)
+                .and_then(|operative| operative.get_locked_fields_digest(self.schema))
+                .and_then(|digest| digest.locked_fields.get(&field_id).cloned())
+                .map(|locked| locked.fulfilled_field.value)
+        });
+        while let Some(PrimitiveValues::Option(inner)) = value {
+            value = *inner;
+        }
+        value.map_or(QueryValue::Null, QueryValue::Value)
+    }
+
+    fn operand_value(&self, operand: &Operand, row: &Binding) -> QueryValue {
+        match operand {
+            Operand::Literal(Literal::String(value)) => {
+                QueryValue::Value(PrimitiveValues::String(value.clone()))
+            }
+            Operand::Literal(Literal::Int(value)) => QueryValue::Integer(*value),
+            Operand::Literal(Literal::Bool(value)) => {
+                QueryValue::Value(PrimitiveValues::Bool(*value))
+            }
+            Operand::Literal(Literal::Null) => QueryValue::Null,
+            Operand::Variable(variable) => row
+                .get(variable)
+                .map_or(QueryValue::Null, |id| QueryValue::Node(*id)),
+            Operand::Property { variable, field } => row
+                .get(variable)
+                .map_or(QueryValue::Null, |id| self.field_value(id, field)),
+        }
+    }
+
+    fn evaluate(&self, expr: &Expr, row: &Binding) -> bool {
+        match expr {
+            Expr::Or(left, right) => self.evaluate(left, row) || self.evaluate(right, row),
+            Expr::And(left, right) => self.evaluate(left, row) && self.evaluate(right, row),
+            Expr::Not(inner) => !self.evaluate(inner, row),
+            Expr::Truthy(operand) => matches!(
+                self.operand_value(operand, row),
+                QueryValue::Value(PrimitiveValues::Bool(true)) | QueryValue::Node(_)
+            ),
+            Expr::Compare(left, op, right) => {
+                let left = self.operand_value(left, row);
+                let right = self.operand_value(right, row);
+                compare(&left, *op, &right)
+            }
+        }
+    }
+
+    fn aggregate(
+        &self,
+        items: &[ReturnItem],
+        rows: &[Binding],
+    ) -> Result<Vec<Vec<QueryValue>>, QueryError> {
+        let mut groups: Vec<(Vec<QueryValue>, Vec<&Binding>)> = Vec::new();
+        for row in rows {
+            let key = items
+                .iter()
+                .filter_map(|item| match &item.projection {
+                    Projection::Value(operand) => Some(self.operand_value(operand, row)),
+                    Projection::Aggregate(..) => None,
+                })
+                .collect::<Vec<_>>();
+            match groups.iter_mut().find(|(existing, _)| *existing == key) {
+                Some((_, members)) => members.push(row),
+                None => groups.push((key, vec![row])),
+            }
+        }
+        let has_grouping = items
+            .iter()
+            .any(|item| matches!(item.projection, Projection::Value(_)));
+        if groups.is_empty() && !has_grouping {
+            groups.push((Vec::new(), Vec::new()));
+        }
+
+        groups
+            .into_iter()
+            .map(|(key, members)| {
+                let mut key = key.into_iter();
+                items
+                    .iter()
+                    .map(|item| match &item.projection {
+                        Projection::Value(_) => Ok(key.next().unwrap_or(QueryValue::Null)),
+                        Projection::Aggregate(aggregate, operand) => {
+                            self.apply_aggregate(*aggregate, operand.as_ref(), &members)
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn apply_aggregate(
+        &self,
+        aggregate: Aggregate,
+        operand: Option<&Operand>,
+        members: &[&Binding],
+    ) -> Result<QueryValue, QueryError> {
+        let Some(operand) = operand else {
+            return Ok(QueryValue::Integer(members.len() as i64));
+        };
+        let values = members
+            .iter()
+            .map(|row| self.operand_value(operand, row))
+            .filter(|value| *value != QueryValue::Null)
+            .collect::<Vec<_>>();
+        let integers = values
+            .iter()
+            .filter_map(QueryValue::as_integer)
+            .collect::<Vec<_>>();
+        let sum = || {
+            checked_sum(&integers).ok_or_else(|| QueryError::AggregateOverflow {
+                function: aggregate.name().to_string(),
+            })
+        };
+        Ok(match aggregate {
+            Aggregate::Count => QueryValue::Integer(values.len() as i64),
+            Aggregate::Sum => QueryValue::Integer(sum()?),
+            Aggregate::Min => integers
+                .iter()
+                .min()
+                .map_or(QueryValue::Null, |min| QueryValue::Integer(*min)),
+            Aggregate::Max => integers
+                .iter()
+                .max()
+                .map_or(QueryValue::Null, |max| QueryValue::Integer(*max)),
+            Aggregate::Avg if integers.is_empty() => QueryValue::Null,
+            Aggregate::Avg => QueryValue::Float(sum()? as f64 / integers.len() as f64),
+        })
+    }
+}
+
+fn checked_sum(integers: &[i64]) -> Option<i64> {
+    integers
+        .iter()
+        .try_fold(0i64, |total, integer| total.checked_add(*integer))
+}
+
+fn compare(left: &QueryValue, op: CompareOp, right: &QueryValue) -> bool {
+    if *left == QueryValue::Null || *right == QueryValue::Null {
+        return false;
+    }
+    let ordering = match (left.as_integer(), right.as_integer(), left, right) {
+        (Some(left), Some(right), _, _) => Some(left.cmp(&right)),
+        (
+            _,
+            _,
+            QueryValue::Value(PrimitiveValues::String(left)),
+            QueryValue::Value(PrimitiveValues::String(right)),
+        ) => Some(left.cmp(right)),
+        _ => None,
+    };
+    match op {
+        CompareOp::Eq => left.loosely_equals(right),
+        CompareOp::Neq => !left.loosely_equals(right),
+        CompareOp::Lt => ordering.is_some_and(|ordering| ordering.is_lt()),
+        CompareOp::Lte => ordering.is_some_and(|ordering| ordering.is_le()),
+        CompareOp::Gt => ordering.is_some_and(|ordering| ordering.is_gt()),
+        CompareOp::Gte => ordering.is_some_and(|ordering| ordering.is_ge()),
+        CompareOp::Contains => match (left, right) {
+            (
+                QueryValue::Value(PrimitiveValues::String(haystack)),
+                QueryValue::Value(PrimitiveValues::String(needle)),
+            ) => haystack.contains(needle.as_str()),
+            (QueryValue::Value(PrimitiveValues::List(items)), needle) => items
+                .iter()
+                .any(|item| QueryValue::Value(item.clone()).loosely_equals(needle)),
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        post_generation::plain::{PlainGraphEnvironment, Transaction},
+        testing::{
+            LABEL_OPERATIVE_ID, LINKS_SLOT_ID, NAME_FIELD_ID, NODE_OPERATIVE_ID, PAIR_OPERATIVE_ID,
+            PARTNER_SLOT_ID, RANK_FIELD_ID, TEST_SCHEMA, TEXT_FIELD_ID,
+        },
+    };
+
+    // a links to b and c, b links to c, a pair is partnered with a and three labels are ranked
+    // 3, 5 and 5
+    fn graph() -> PlainGraphEnvironment {
+        let mut graph = PlainGraphEnvironment::new(&TEST_SCHEMA);
+        let mut transaction = Transaction::new();
+        let [a, b, c] = ["a", "b", "c"].map(|name| {
+            let id = transaction.create(NODE_OPERATIVE_ID);
+            transaction.set_field(id, NAME_FIELD_ID, string(name));
+            id
+        });
+        transaction
+            .add_edge(a, LINKS_SLOT_ID, b)
+            .add_edge(a, LINKS_SLOT_ID, c)
+            .add_edge(b, LINKS_SLOT_ID, c);
+        let pair = transaction.create(PAIR_OPERATIVE_ID);
+        transaction.add_edge(pair, PARTNER_SLOT_ID, a);
+        [("x", 3), ("y", 5), ("z", 5)]
+            .iter()
+            .for_each(|(text, rank)| {
+                let id = transaction.create(LABEL_OPERATIVE_ID);
+                transaction
+                    .set_field(id, TEXT_FIELD_ID, string(text))
+                    .set_field(id, RANK_FIELD_ID, PrimitiveValues::Int(*rank));
+            });
+        graph.execute(transaction).unwrap();
+        graph
+    }
+    fn string(value: &str) -> PrimitiveValues {
+        PrimitiveValues::String(value.to_string())
+    }
+    // The rows of the result rendered as strings, sorted since nodes are matched in id order
+    fn rows(result: &QueryResult) -> Vec<Vec<String>> {
+        let mut rows = result
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|value| value.to_string()).collect())
+            .collect::<Vec<Vec<_>>>();
+        rows.sort();
+        rows
+    }
+    fn query(query: &str) -> Vec<Vec<String>> {
+        rows(&graph().query(query).unwrap())
+    }
+    fn tokens(source: &str) -> Vec<Token> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|(_, token)| token)
+            .collect()
+    }
+
+    #[test]
+    fn tokenize_splits_operators_strings_and_numbers() {
+        assert_eq!(
+            tokens("(n:Node)<-[links]-(m)-->()"),
+            vec![
+                Token::LParen,
+                Token::Ident("n".to_string()),
+                Token::Colon,
+                Token::Ident("Node".to_string()),
+                Token::RParen,
+                Token::LeftArrow,
+                Token::LBracket,
+                Token::Ident("links".to_string()),
+                Token::RBracket,
+                Token::Dash,
+                Token::LParen,
+                Token::Ident("m".to_string()),
+                Token::RParen,
+                Token::Dash,
+                Token::Arrow,
+                Token::LParen,
+                Token::RParen,
+            ]
+        );
+        assert_eq!(
+            tokens("= <> != < <= > >= * . ,"),
+            vec![
+                Token::Eq,
+                Token::Neq,
+                Token::Neq,
+                Token::Lt,
+                Token::Lte,
+                Token::Gt,
+                Token::Gte,
+                Token::Star,
+                Token::Dot,
+                Token::Comma,
+            ]
+        );
+        assert_eq!(
+            tokens(r#"'it\'s' "a\tb" 42"#),
+            vec![
+                Token::Str("it's".to_string()),
+                Token::Str("a\tb".to_string()),
+                Token::Int(42),
+            ]
+        );
+        let positions = tokenize("MATCH  (n)")
+            .unwrap()
+            .into_iter()
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![0, 7, 8, 9]);
+    }
+
+    #[test]
+    fn match_follows_slots_by_name_and_direction() {
+        assert_eq!(
+            query(r#"MATCH (n:Node)-[links]->(m:Node) WHERE n.name = "a" RETURN m.name"#),
+            vec![vec!["b"], vec!["c"]]
+        );
+        assert_eq!(
+            query(r#"MATCH (m)<-[links]-(n) WHERE m.name = "c" RETURN n.name"#),
+            vec![vec!["a"], vec!["b"]]
+        );
+        // Any slot, and a path through more than one edge
+        assert_eq!(query("MATCH (p:Pair)-->(n) RETURN n.name"), vec![vec!["a"]]);
+        assert_eq!(
+            query("MATCH (p:Pair)-[partner]->(a)-[links]->(b)-[links]->(c) RETURN c.name"),
+            vec![vec!["c"]]
+        );
+        // Patterns separated by commas share their variables
+        assert_eq!(
+            query("MATCH (p:Pair)-[partner]->(a), (a)-[:links]->(b) RETURN b.name"),
+            vec![vec!["b"], vec!["c"]]
+        );
+    }
+
+    #[test]
+    fn where_combines_comparisons() {
+        assert_eq!(
+            query("MATCH (l:Label) WHERE l.rank > 3 AND NOT l.text = 'z' RETURN l.text"),
+            vec![vec!["y"]]
+        );
+        assert_eq!(
+            query("MATCH (l:Label) WHERE (l.rank <= 3 OR l.text <> 'y') RETURN l.text"),
+            vec![vec!["x"], vec!["z"]]
+        );
+        assert_eq!(
+            query("MATCH (n:Node) WHERE n.name >= 'b' AND n.name < 'c' RETURN n.name"),
+            vec![vec!["b"]]
+        );
+        assert_eq!(
+            query("MATCH (l:Label) WHERE l.text CONTAINS 'x' OR l.rank = -1 RETURN l.text"),
+            vec![vec!["x"]]
+        );
+    }
+
+    #[test]
+    fn return_names_columns_and_limit_truncates() {
+        let graph = graph();
+        let result = graph
+            .query("MATCH (n:Node)-[links]->(m) RETURN n, m.name AS target LIMIT 2")
+            .unwrap();
+        assert_eq!(result.columns, vec!["n", "target"]);
+        assert_eq!(result.rows.len(), 2);
+        let names = result
+            .node_ids("n")
+            .iter()
+            .map(|id| graph.get(id).unwrap().fields[&NAME_FIELD_ID].clone())
+            .collect::<Vec<_>>();
+        assert!(names
+            .iter()
+            .all(|name| *name == string("a") || *name == string("b")));
+        assert!(result.node_ids("missing").is_empty());
+
+        let result = graph.query("MATCH (n:Node) RETURN n.name LIMIT 0").unwrap();
+        assert_eq!(result.columns, vec!["n.name"]);
+        assert!(result.rows.is_empty());
+    }
+
+    #[test]
+    fn aggregates_group_by_the_other_items() {
+        let result = graph()
+            .query("MATCH (n:Node)-[links]->(m) RETURN n.name, count(m) AS targets")
+            .unwrap();
+        assert_eq!(result.columns, vec!["n.name", "targets"]);
+        assert_eq!(rows(&result), vec![vec!["a", "2"], vec!["b", "1"]]);
+        assert_eq!(
+            query("MATCH (l:Label) RETURN l.rank, count(*)"),
+            vec![vec!["3", "1"], vec!["5", "2"]]
+        );
+    }
+
+    #[test]
+    fn aggregates_compute_over_all_rows() {
+        let result = graph()
+            .query(
+                "MATCH (l:Label) RETURN count(l), sum(l.rank), min(l.rank), max(l.rank), avg(l.rank)",
+            )
+            .unwrap();
+        assert_eq!(
+            result.columns,
+            vec![
+                "count(l)",
+                "sum(l.rank)",
+                "min(l.rank)",
+                "max(l.rank)",
+                "avg(l.rank)"
+            ]
+        );
+        assert_eq!(
+            result.rows,
+            vec![vec![
+                QueryValue::Integer(3),
+                QueryValue::Integer(13),
+                QueryValue::Integer(3),
+                QueryValue::Integer(5),
+                QueryValue::Float(13.0 / 3.0),
+            ]]
+        );
+        // Without any matching rows there is still a single row of aggregates
+        let result = graph()
+            .query("MATCH (l:Label) WHERE l.rank > 5 RETURN count(*), sum(l.rank), max(l.rank), avg(l.rank)")
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![vec![
+                QueryValue::Integer(0),
+                QueryValue::Integer(0),
+                QueryValue::Null,
+                QueryValue::Null,
+            ]]
+        );
+    }
+
+    #[test]
+    fn sums_which_overflow_are_detected() {
+        assert_eq!(checked_sum(&[1, 2, 3]), Some(6));
+        assert_eq!(checked_sum(&[i64::MAX, 1]), None);
+        assert_eq!(
+            QueryError::AggregateOverflow {
+                function: "sum".to_string()
+            }
+            .to_string(),
+            "sum() overflowed the range of an integer"
+        );
+    }
+
+    #[test]
+    fn parse_reports_where_the_query_goes_wrong() {
+        assert_eq!(
+            Query::parse("MATCH (n").unwrap_err(),
+            QueryError::UnexpectedEnd
+        );
+        assert_eq!(
+            Query::parse("MATCH n RETURN n").unwrap_err(),
+            QueryError::UnexpectedToken {
+                position: 6,
+                found: "n".to_string(),
+                expected: "'('".to_string(),
+            }
+        );
+        assert_eq!(
+            Query::parse("MATCH (n) WHERE n.name = 'a RETURN n").unwrap_err(),
+            QueryError::UnterminatedString { position: 25 }
+        );
+        assert_eq!(
+            Query::parse("MATCH (n) RETURN n LIMIT ten").unwrap_err(),
+            QueryError::UnexpectedToken {
+                position: 25,
+                found: "ten".to_string(),
+                expected: "an integer limit".to_string(),
+            }
+        );
+        assert_eq!(
+            Query::parse("MATCH (n) RETURN n n").unwrap_err(),
+            QueryError::UnexpectedToken {
+                position: 19,
+                found: "n".to_string(),
+                expected: "end of query".to_string(),
+            }
+        );
+        assert!(matches!(
+            Query::parse("MATCH (n) RETURN n # comment").unwrap_err(),
+            QueryError::UnexpectedToken { position: 19, .. }
+        ));
+        assert!(matches!(
+            Query::parse("MATCH (n) RETURN 99999999999999999999").unwrap_err(),
+            QueryError::UnexpectedToken { position: 17, .. }
+        ));
+    }
+
+    #[test]
+    fn execute_rejects_names_missing_from_the_schema() {
+        let graph = graph();
+        let error = |query: &str| graph.query(query).unwrap_err();
+        assert_eq!(
+            error("MATCH (n:Sentence) RETURN n"),
+            QueryError::UnknownLabel("Sentence".to_string())
+        );
+        assert_eq!(
+            error("MATCH (n)-[words]->(m) RETURN m"),
+            QueryError::UnknownSlot("words".to_string())
+        );
+        assert_eq!(
+            error("MATCH (n) RETURN n.display"),
+            QueryError::UnknownField("display".to_string())
+        );
+        assert_eq!(
+            error("MATCH (n) WHERE m.name = 'a' RETURN n"),
+            QueryError::UnboundVariable("m".to_string())
+        );
+        assert_eq!(
+            error("MATCH (n:Node) RETURN sum(n.name)"),
+            QueryError::NonNumericAggregate {
+                function: "sum".to_string(),
+                field: "n.name".to_string(),
+            }
+        );
+        assert_eq!(
+            error("MATCH (n:Node) RETURN avg(n)"),
+            QueryError::NonNumericAggregate {
+                function: "avg".to_string(),
+                field: "n".to_string(),
+            }
+        );
+    }
+}
@@ -13,8 +13,24 @@ use crate::{
     primitives::{PrimitiveTypes, PrimitiveValues},
 };
 
+use crate::post_generation::change_set::{ChangeOrigin, ChangeSet, ChangeSubscribers, SubscriptionId};
+use crate::post_generation::bundle::{export_bundle, BundleError, BundleOptions, SubgraphBundle};
+use crate::post_generation::field_index::FieldValueIndex;
+use crate::post_generation::integrity::{find_problems, IntegrityProblem};
+use crate::post_generation::validation::{
+    check_field_value_type, check_unique_fields, check_versions, slot_accepts_operative,
+};
+use crate::post_generation::journal::{
+    instances_to_json, snapshot_fingerprint, write_snapshot_json, BlueprintJournal, JournalError,
+    JournalWriter,
+};
+use crate::post_generation::store::{GraphStore, StoreError, StoreWriter};
+use crate::post_generation::merge::Replica;
+use crate::post_generation::query::{execute_query, QueryError, QueryResult};
+use crate::post_generation::export::{to_cypher, to_graphml, to_neo4j_csv, to_turtle, ExportError, Neo4jCsvExport};
 use crate::post_generation::{
-    ElementCreationError, FieldEdit, HistoryFieldEdit, SlotRef, TaggedAction, Verifiable,
+    BoundCheckError, ElementCreationError, ExecutionResult, FieldEdit, HistoryFieldEdit, IdFormat,
+    RequiredFieldError, SlotRef, Verifiable,
 };
 use crate::utils::IntoPrimitiveValue;
 use leptos::prelude::*;
@@ -40,10 +56,9 @@ pub mod hidden {
         <T as HasSlotEnum>::SlotEnum: Clone,
     {
         fn update_field(&self, field_edit: HistoryFieldEdit) -> &Self {
-            self.fields
-                .get(&field_edit.field_id)
-                .unwrap()
-                .set(field_edit.new_value);
+            if let Some(field) = self.fields.get(&field_edit.field_id) {
+                field.set(field_edit.new_value);
+            }
             self
         }
         fn add_incoming(&self, slot_ref: SlotRef) -> &Self {
@@ -54,15 +69,13 @@ pub mod hidden {
         }
 
         fn remove_outgoing(&self, slot_ref: &SlotRef) -> &Self {
-            self.outgoing_slots
-                .get(&slot_ref.slot_id)
-                .unwrap()
-                .slotted_instances
-                .update(|slotted_instances| {
+            if let Some(slot) = self.outgoing_slots.get(&slot_ref.slot_id) {
+                slot.slotted_instances.update(|slotted_instances| {
                     slotted_instances.retain(|slotted_instance_id| {
                         *slotted_instance_id != slot_ref.target_instance_id
                     });
                 });
+            }
             self
         }
 
@@ -88,13 +101,11 @@ pub mod hidden {
         }
 
         fn add_outgoing(&self, slot_ref: SlotRef) -> &Self {
-            self.outgoing_slots
-                .get(&slot_ref.slot_id)
-                .unwrap()
-                .slotted_instances
-                .update(|slotted_instances| {
+            if let Some(slot) = self.outgoing_slots.get(&slot_ref.slot_id) {
+                slot.slotted_instances.update(|slotted_instances| {
                     slotted_instances.push(slot_ref.target_instance_id);
                 });
+            }
             self
         }
         fn get_graph(&self) -> &std::sync::Arc<RBaseGraphEnvironment<Self::Schema>> {
@@ -110,25 +121,37 @@ pub mod hidden {
             graph: SharedGraph<TSchema>,
             constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>, // operative_ref: &'static LibraryOperative<PrimitiveTypes, PrimitiveValues>,
                                                                                            // template_ref: &'static LibraryTemplate<PrimitiveTypes, PrimitiveValues>,
-        ) -> Self {
+        ) -> Result<Self, ElementCreationError> {
             let operative_ref = constraint_schema
                 .operative_library
                 .get(&value.operative)
-                .unwrap();
+                .ok_or(ElementCreationError::NonexistentOperative {
+                    instance_id: value.id,
+                    operative_id: value.operative,
+                })?;
             let template_ref = constraint_schema
                 .template_library
                 .get(&value.template)
-                .unwrap();
+                .ok_or(ElementCreationError::NonexistentTemplate {
+                    instance_id: value.id,
+                    template_id: value.template,
+                })?;
+            let mut errors = Vec::new();
             let mut initial_btree =
                 std::collections::BTreeMap::<Uid, SpecializedRActiveSlot<T::SlotEnum>>::new();
             template_ref
                 .operative_slots
                 .iter()
-                .for_each(|(slot_id, _slot)| {
-                    let operative_slot_ref = template_ref.operative_slots.get(&slot_id).unwrap();
-                    let enum_variant = T::SlotEnum::from_str(&u128_to_string(slot_id.clone()))
-                        .ok()
-                        .expect("Slot enum variant mismatched with slot id");
+                .for_each(|(slot_id, operative_slot_ref)| {
+                    let Ok(enum_variant) =
+                        T::SlotEnum::from_str(&u128_to_string(*slot_id))
+                    else {
+                        errors.push(ElementCreationError::NonexistentSlot {
+                            instance_id: value.id,
+                            slot_id: *slot_id,
+                        });
+                        return;
+                    };
                     initial_btree.insert(
                         slot_id.clone(),
                         SpecializedRActiveSlot::<T::SlotEnum> {
@@ -145,15 +168,25 @@ pub mod hidden {
                     .outgoing_slots
                     .iter()
                     .fold(initial_btree, |mut agg, slot_ref| {
-                        agg.entry(slot_ref.slot_id).and_modify(|r_active_slot| {
-                            r_active_slot
+                        match agg.get(&slot_ref.slot_id) {
+                            Some(r_active_slot) => r_active_slot
                                 .base
                                 .slotted_instances
-                                .update_untracked(|prev| prev.push(slot_ref.target_instance_id));
-                        });
+                                .update_untracked(|prev| prev.push(slot_ref.target_instance_id)),
+                            None => errors.push(ElementCreationError::NonexistentSlot {
+                                instance_id: value.id,
+                                slot_id: slot_ref.slot_id,
+                            }),
+                        }
                         agg
                     });
-            Self {
+            errors.extend(value.fields.iter().filter_map(|(field_id, field)| {
+                check_field_value_type(value.id, template_ref, field_id, field).err()
+            }));
+            if !errors.is_empty() {
+                return Err(ElementCreationError::Stack(errors));
+            }
+            Ok(Self {
                 id: value.id,
                 fields: value
                     .fields
@@ -166,14 +199,14 @@ pub mod hidden {
                 operative: operative_ref,
                 template: template_ref,
                 _phantom: PhantomData,
-            }
+            })
         }
     }
 }
 
 use hidden::EditRGSO;
 
-use super::{ActiveSlot, StandaloneRGSOConcrete};
+use super::{StandaloneBlueprint, StandaloneRGSOConcrete};
 pub trait FromNonReactive<NTSchema>
 where
     Self: EditRGSO<Schema = Self>,
@@ -286,10 +319,98 @@ pub trait RProducable<T> {
     fn produce(&self) -> T;
 }
 
+/// A single entry on the undo or redo stack. Usually holds one blueprint, but a group (see
+/// [`RBaseGraphEnvironment::begin_group`]) collects every commit made while it is open
+#[derive(Clone, Debug)]
+pub struct HistoryStep<TSchema> {
+    pub label: Option<String>,
+    pub blueprints: Vec<Blueprint<TSchema>>,
+}
+
+/// Summary of a [`HistoryStep`], for listing the history without handing out the blueprints
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub label: Option<String>,
+    pub blueprint_count: usize,
+}
+impl<TSchema> From<&HistoryStep<TSchema>> for HistoryEntry {
+    fn from(step: &HistoryStep<TSchema>) -> Self {
+        Self {
+            label: step.label.clone(),
+            blueprint_count: step.blueprints.len(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct OpenHistoryGroup {
+    label: Option<String>,
+    depth: usize,
+    // Whether the group already owns the step on top of the undo stack
+    step_pushed: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct RHistoryContainer<TSchema> {
-    pub undo: Vec<Blueprint<TSchema>>,
-    pub redo: Vec<Blueprint<TSchema>>,
+    pub undo: Vec<HistoryStep<TSchema>>,
+    pub redo: Vec<HistoryStep<TSchema>>,
+    /// The oldest steps are discarded once either stack grows past this
+    pub max_depth: Option<usize>,
+    open_group: Option<OpenHistoryGroup>,
+}
+impl<TSchema> RHistoryContainer<TSchema> {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            max_depth: None,
+            open_group: None,
+        }
+    }
+    fn record(&mut self, blueprint: Blueprint<TSchema>) {
+        self.redo.clear();
+        match &mut self.open_group {
+            Some(group) if group.step_pushed => {
+                if let Some(step) = self.undo.last_mut() {
+                    step.blueprints.push(blueprint);
+                }
+            }
+            Some(group) => {
+                group.step_pushed = true;
+                self.undo.push(HistoryStep {
+                    label: group.label.clone().or(blueprint.label.clone()),
+                    blueprints: vec![blueprint],
+                });
+            }
+            None => self.undo.push(HistoryStep {
+                label: blueprint.label.clone(),
+                blueprints: vec![blueprint],
+            }),
+        }
+        self.trim();
+    }
+    fn trim(&mut self) {
+        if let Some(max_depth) = self.max_depth {
+            if self.undo.len() > max_depth {
+                self.undo.drain(..self.undo.len() - max_depth);
+            }
+            if self.redo.len() > max_depth {
+                self.redo.drain(..self.redo.len() - max_depth);
+            }
+        }
+    }
+    // Undoing or redoing inside a group means later commits no longer belong to the step on top
+    // of the undo stack
+    fn detach_open_group(&mut self) {
+        if let Some(group) = &mut self.open_group {
+            group.step_pushed = false;
+        }
+    }
+}
+impl<TSchema> Default for RHistoryContainer<TSchema> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -297,6 +418,13 @@ pub struct RBaseGraphEnvironment<TSchema: 'static> {
     pub created_instances: RwSignal<std::collections::HashMap<Uid, TSchema>>,
     pub constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
     pub history: std::sync::Arc<std::sync::Mutex<RHistoryContainer<TSchema>>>,
+    pub field_index: std::sync::Arc<std::sync::Mutex<FieldValueIndex>>,
+    pub journal: std::sync::Arc<std::sync::Mutex<Option<JournalWriter<TSchema>>>>,
+    pub subscribers: std::sync::Arc<std::sync::Mutex<ChangeSubscribers>>,
+    pub replica: std::sync::Arc<std::sync::Mutex<Option<Replica<TSchema>>>>,
+    /// Number of committed blueprints which have touched each instance, see [`Self::version`]
+    pub versions: std::sync::Arc<std::sync::Mutex<std::collections::HashMap<Uid, u64>>>,
+    pub store: std::sync::Arc<std::sync::Mutex<Option<StoreWriter<TSchema>>>>,
 }
 impl<TSchema: Send + Sync> RBaseGraphEnvironment<TSchema> {
     pub fn new(
@@ -305,20 +433,277 @@ impl<TSchema: Send + Sync> RBaseGraphEnvironment<TSchema> {
         Self {
             created_instances: RwSignal::new(std::collections::HashMap::new()),
             constraint_schema,
-            history: std::sync::Arc::new(std::sync::Mutex::new(RHistoryContainer {
-                undo: Vec::new(),
-                redo: Vec::new(),
-            })),
+            history: std::sync::Arc::new(std::sync::Mutex::new(RHistoryContainer::new())),
+            field_index: std::sync::Arc::new(std::sync::Mutex::new(FieldValueIndex::new(
+                constraint_schema,
+            ))),
+            journal: std::sync::Arc::new(std::sync::Mutex::new(None)),
+            replica: std::sync::Arc::new(std::sync::Mutex::new(None)),
+            subscribers: std::sync::Arc::new(std::sync::Mutex::new(ChangeSubscribers::default())),
+            versions: std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
+            store: std::sync::Arc::new(std::sync::Mutex::new(None)),
+        }
+    }
+    /// Changes whenever a committed blueprint adds, deletes or edits the instance, including its
+    /// slots. Instances which have never been touched are at version 0
+    pub fn version(&self, id: &Uid) -> u64 {
+        self.versions.lock().unwrap().get(id).copied().unwrap_or(0)
+    }
+    /// Calls the callback with the change set of every blueprint committed from now on, including
+    /// those applied by `undo` and `redo`
+    pub fn subscribe(
+        &self,
+        callback: impl Fn(&ChangeSet) + Send + Sync + 'static,
+    ) -> SubscriptionId {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .add_callback(std::sync::Arc::new(callback))
+    }
+    /// Same as [`Self::subscribe`], but delivers the change sets through a channel. The
+    /// subscription ends when the receiver is dropped
+    pub fn subscribe_channel(&self) -> (SubscriptionId, std::sync::mpsc::Receiver<ChangeSet>) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let id = self.subscribers.lock().unwrap().add_channel(sender);
+        (id, receiver)
+    }
+    /// Returns whether the subscription was still active
+    pub fn unsubscribe(&self, id: &SubscriptionId) -> bool {
+        self.subscribers.lock().unwrap().remove(id)
+    }
+    /// Starts collecting every following commit into a single undo step, until the matching
+    /// [`Self::end_group`]. Groups may be nested, in which case only the outermost one counts
+    pub fn begin_group(&self, label: Option<String>) {
+        let mut history = self.history.lock().unwrap();
+        match &mut history.open_group {
+            Some(group) => group.depth += 1,
+            None => {
+                history.open_group = Some(OpenHistoryGroup {
+                    label,
+                    depth: 1,
+                    step_pushed: false,
+                })
+            }
+        }
+    }
+    pub fn end_group(&self) {
+        let mut history = self.history.lock().unwrap();
+        if let Some(group) = &mut history.open_group {
+            group.depth -= 1;
+            if group.depth == 0 {
+                history.open_group = None;
+            }
         }
     }
+    /// Limits how many steps are kept on each of the undo and redo stacks. `None` is unbounded
+    pub fn set_max_history_depth(&self, max_depth: Option<usize>) {
+        let mut history = self.history.lock().unwrap();
+        history.max_depth = max_depth;
+        history.trim();
+    }
+    /// The step which the next call to `undo` would revert
+    pub fn peek_undo(&self) -> Option<HistoryEntry> {
+        self.history.lock().unwrap().undo.last().map(HistoryEntry::from)
+    }
+    /// The step which the next call to `redo` would reapply
+    pub fn peek_redo(&self) -> Option<HistoryEntry> {
+        self.history.lock().unwrap().redo.last().map(HistoryEntry::from)
+    }
+    /// The undo stack, most recent step first
+    pub fn undo_history(&self) -> Vec<HistoryEntry> {
+        let history = self.history.lock().unwrap();
+        history.undo.iter().rev().map(HistoryEntry::from).collect()
+    }
+    /// The redo stack, most recent step first
+    pub fn redo_history(&self) -> Vec<HistoryEntry> {
+        let history = self.history.lock().unwrap();
+        history.redo.iter().rev().map(HistoryEntry::from).collect()
+    }
+}
+impl<TSchema: RGSO + Send + Sync> RBaseGraphEnvironment<TSchema> {
     pub fn initialize(&self, created_instances: std::collections::HashMap<Uid, TSchema>) {
+        {
+            let mut field_index = self.field_index.lock().unwrap();
+            field_index.clear();
+            created_instances.values().for_each(|instance| {
+                field_index.insert_instance(*instance.get_id(), indexable_fields(instance));
+            });
+        }
         self.created_instances.set(created_instances);
     }
+    /// Finds all instances holding the given value in the given field.
+    /// Uses the value index if the field is marked as indexed in the schema, otherwise scans the graph.
+    pub fn find_by_field(&self, field_id: &Uid, value: &PrimitiveValues) -> Vec<TSchema> {
+        let indexed_ids = {
+            let field_index = self.field_index.lock().unwrap();
+            if field_index.is_indexed(field_id) {
+                Some(field_index.lookup(field_id, value))
+            } else {
+                None
+            }
+        };
+        self.created_instances
+            .with_untracked(|created_instances| match indexed_ids {
+                Some(ids) => ids
+                    .iter()
+                    .filter_map(|id| created_instances.get(id).cloned())
+                    .collect(),
+                None => {
+                    let mut found = created_instances
+                        .values()
+                        .filter(|instance| {
+                            instance
+                                .fields()
+                                .get(field_id)
+                                .is_some_and(|field| field.with_untracked(|field| field == value))
+                        })
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    found.sort_by_key(|instance| *instance.get_id());
+                    found
+                }
+            })
+    }
+}
+fn indexable_fields<TSchema: RGSO>(instance: &TSchema) -> Vec<(Uid, PrimitiveValues)> {
+    instance
+        .fields()
+        .iter()
+        .map(|(field_id, value)| (*field_id, value.get_untracked()))
+        .collect()
+}
+
+// Every instance whose version changes when the blueprint is applied
+fn touched_ids<TSchema: EditRGSO>(blueprint: &Blueprint<TSchema>) -> std::collections::HashSet<Uid> {
+    blueprint
+        .added_instances
+        .iter()
+        .chain(blueprint.deleted_instances.iter())
+        .map(|instance| *instance.get_id())
+        .chain(
+            blueprint
+                .add_outgoing_updates
+                .iter()
+                .chain(blueprint.remove_outgoing_updates.iter())
+                .map(|(host_id, _)| *host_id),
+        )
+        .chain(
+            blueprint
+                .add_incoming_updates
+                .iter()
+                .chain(blueprint.remove_incoming_updates.iter())
+                .map(|(target_id, _)| *target_id),
+        )
+        .chain(blueprint.field_updates.iter().map(|(id, _)| *id))
+        .collect()
 }
 
 impl<TSchema: EditRGSO + Send + Sync> RBaseGraphEnvironment<TSchema> {
-    fn process_blueprint(&self, blueprint: Blueprint<TSchema>) {
+    // Makes sure that every instance, slot and field referenced by the blueprint will exist once
+    // its instances have been added and deleted, so that applying it can't fail halfway through
+    fn validate_blueprint(&self, blueprint: &Blueprint<TSchema>) -> Result<(), ElementCreationError> {
+        let deleted_ids = blueprint
+            .deleted_instances
+            .iter()
+            .map(|instance| *instance.get_id())
+            .collect::<std::collections::HashSet<_>>();
+        let errors = self.created_instances.with_untracked(|created_instances| {
+            let resolve = |id: &Uid| {
+                if deleted_ids.contains(id) {
+                    return None;
+                }
+                blueprint
+                    .added_instances
+                    .iter()
+                    .find(|instance| instance.get_id() == id)
+                    .or_else(|| created_instances.get(id))
+            };
+            let mut errors = blueprint
+                .deleted_instances
+                .iter()
+                .filter(|instance| !created_instances.contains_key(instance.get_id()))
+                .map(|instance| ElementCreationError::NonexistentInstance {
+                    id: *instance.get_id(),
+                })
+                .collect::<Vec<_>>();
+            blueprint
+                .add_outgoing_updates
+                .iter()
+                .chain(blueprint.remove_outgoing_updates.iter())
+                .for_each(|(host_id, slot_ref)| match resolve(host_id) {
+                    Some(host) if !host.outgoing_slots().contains_key(&slot_ref.slot_id) => {
+                        errors.push(ElementCreationError::NonexistentSlot {
+                            instance_id: *host_id,
+                            slot_id: slot_ref.slot_id,
+                        })
+                    }
+                    Some(_) => {}
+                    None => errors.push(ElementCreationError::NonexistentInstance { id: *host_id }),
+                });
+            blueprint
+                .add_incoming_updates
+                .iter()
+                .chain(blueprint.remove_incoming_updates.iter())
+                .filter(|(target_id, _)| resolve(target_id).is_none())
+                .for_each(|(target_id, _)| {
+                    errors.push(ElementCreationError::NonexistentInstance { id: *target_id })
+                });
+            blueprint
+                .field_updates
+                .iter()
+                .for_each(|(id, field_edit)| match resolve(id) {
+                    Some(instance) if !instance.fields().contains_key(&field_edit.field_id) => {
+                        errors.push(ElementCreationError::NonexistentField {
+                            instance_id: *id,
+                            field_id: field_edit.field_id,
+                        })
+                    }
+                    Some(_) => {}
+                    None => errors.push(ElementCreationError::NonexistentInstance { id: *id }),
+                });
+            errors
+        });
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(ElementCreationError::Stack(errors))
+    }
+    // All-or-nothing, the graph is left untouched if any part of the blueprint is invalid
+    pub(crate) fn process_blueprint(&self, blueprint: Blueprint<TSchema>) -> Result<(), ElementCreationError> {
+        self.process_blueprint_if_current(blueprint, &std::collections::HashMap::new())
+    }
+    // Same as `process_blueprint`, but fails with `ElementCreationError::Conflict` if any of the
+    // instances has been changed since it was read at the given version
+    pub(crate) fn process_blueprint_if_current(
+        &self,
+        blueprint: Blueprint<TSchema>,
+        read_versions: &std::collections::HashMap<Uid, u64>,
+    ) -> Result<(), ElementCreationError> {
+        {
+            // Held until the versions are bumped, so that two commits which read the same
+            // instance can't both pass the check
+            let mut versions = self.versions.lock().unwrap();
+            check_versions(|id| versions.get(id).copied().unwrap_or(0), read_versions)?;
+            self.validate_blueprint(&blueprint)?;
+            self.persist(&blueprint)?;
+            touched_ids(&blueprint).into_iter().for_each(|id| {
+                *versions.entry(id).or_insert(0) += 1;
+            });
+        }
         leptos::logging::log!("starting processing of blueprint");
+        {
+            let mut field_index = self.field_index.lock().unwrap();
+            blueprint.added_instances.iter().for_each(|instance| {
+                field_index.insert_instance(*instance.get_id(), indexable_fields(instance));
+            });
+            blueprint.deleted_instances.iter().for_each(|instance| {
+                field_index.remove_instance(instance.get_id(), indexable_fields(instance));
+            });
+            blueprint.field_updates.iter().for_each(|(id, field_edit)| {
+                field_index.remove(id, &field_edit.field_id, &field_edit.prev_value);
+                field_index.insert(*id, field_edit.field_id, field_edit.new_value.clone());
+            });
+        }
         blueprint.added_instances.into_iter().for_each(|instance| {
             self.created_instances.update(|prev| {
                 prev.insert(*instance.get_id(), instance);
@@ -332,74 +717,134 @@ impl<TSchema: EditRGSO + Send + Sync> RBaseGraphEnvironment<TSchema> {
                     prev.remove(instance.get_id());
                 });
             });
-        blueprint
-            .add_outgoing_updates
-            .into_iter()
-            .for_each(|add_outgoing| {
-                self.created_instances.with(|created_instances| {
-                    created_instances
-                        .get(&add_outgoing.0)
-                        .unwrap()
-                        .add_outgoing(add_outgoing.1);
+        self.created_instances.with(|created_instances| {
+            blueprint
+                .add_outgoing_updates
+                .into_iter()
+                .for_each(|add_outgoing| {
+                    if let Some(instance) = created_instances.get(&add_outgoing.0) {
+                        instance.add_outgoing(add_outgoing.1);
+                    }
                 });
-            });
-        blueprint
-            .add_incoming_updates
-            .into_iter()
-            .for_each(|add_incoming| {
-                self.created_instances.with(|created_instances| {
-                    created_instances
-                        .get(&add_incoming.0)
-                        .unwrap()
-                        .add_incoming(add_incoming.1);
+            blueprint
+                .add_incoming_updates
+                .into_iter()
+                .for_each(|add_incoming| {
+                    if let Some(instance) = created_instances.get(&add_incoming.0) {
+                        instance.add_incoming(add_incoming.1);
+                    }
                 });
-            });
-        blueprint
-            .remove_outgoing_updates
-            .into_iter()
-            .for_each(|remove_outgoing| {
-                self.created_instances.with(|created_instances| {
-                    created_instances
-                        .get(&remove_outgoing.0)
-                        .unwrap()
-                        .remove_outgoing(&remove_outgoing.1);
+            blueprint
+                .remove_outgoing_updates
+                .into_iter()
+                .for_each(|remove_outgoing| {
+                    if let Some(instance) = created_instances.get(&remove_outgoing.0) {
+                        instance.remove_outgoing(&remove_outgoing.1);
+                    }
                 });
-            });
-        blueprint
-            .remove_incoming_updates
-            .into_iter()
-            .for_each(|remove_incoming| {
-                self.created_instances.with(|created_instances| {
-                    created_instances
-                        .get(&remove_incoming.0)
-                        .unwrap()
-                        .remove_incoming(
+            blueprint
+                .remove_incoming_updates
+                .into_iter()
+                .for_each(|remove_incoming| {
+                    if let Some(instance) = created_instances.get(&remove_incoming.0) {
+                        instance.remove_incoming(
                             &remove_incoming.1.host_instance_id,
                             Some(&remove_incoming.1.slot_id),
                         );
+                    }
                 });
-            });
-        blueprint
-            .field_updates
-            .into_iter()
-            .for_each(|field_update| {
-                self.created_instances.with(|created_instances| {
-                    created_instances
-                        .get(&field_update.0)
-                        .unwrap()
-                        .update_field(field_update.1);
+            blueprint
+                .field_updates
+                .into_iter()
+                .for_each(|field_update| {
+                    if let Some(instance) = created_instances.get(&field_update.0) {
+                        instance.update_field(field_update.1);
+                    }
                 });
-            });
+        });
         leptos::logging::log!("finished processing of blueprint");
+        Ok(())
     }
-    fn push_undo(&self, blueprint: Blueprint<TSchema>) {
-        self.history.as_ref().lock().unwrap().undo.push(blueprint);
+    fn record_history(&self, blueprint: Blueprint<TSchema>) {
+        self.history.as_ref().lock().unwrap().record(blueprint.clone());
+        self.committed(std::slice::from_ref(&blueprint), ChangeOrigin::Commit);
+    }
+    // Written ahead of applying the blueprint, so that a blueprint which can't be stored or
+    // journaled isn't applied either and both always match the graph
+    fn persist(&self, blueprint: &Blueprint<TSchema>) -> Result<(), ElementCreationError> {
+        let persistence_error = |error: &dyn std::fmt::Display| ElementCreationError::Persistence {
+            message: error.to_string(),
+        };
+        let mut store = self.store.lock().unwrap();
+        if let Some(store) = store.as_mut() {
+            store
+                .write(std::slice::from_ref(blueprint))
+                .map_err(|error| persistence_error(&error))?;
+        }
+        if let Some(journal) = self.journal.lock().unwrap().as_mut() {
+            if let Err(error) = journal.write(std::slice::from_ref(blueprint)) {
+                // Reverted so that the store doesn't run ahead of the graph
+                if let Some(store) = store.as_mut() {
+                    let _ = store.write(&[blueprint.clone().reverse()]);
+                }
+                return Err(persistence_error(&error));
+            }
+        }
+        Ok(())
     }
-    fn push_redo(&self, blueprint: Blueprint<TSchema>) {
-        self.history.as_ref().lock().unwrap().redo.push(blueprint);
+    // Announces blueprints which have already been applied
+    pub(crate) fn committed(&self, blueprints: &[Blueprint<TSchema>], origin: ChangeOrigin) {
+        // Merged blueprints came from the other replicas, so they aren't sent back
+        if origin != ChangeOrigin::Merge {
+            if let Some(replica) = self.replica.lock().unwrap().as_mut() {
+                replica.record_local(blueprints);
+            }
+        }
+        let change_sets = blueprints
+            .iter()
+            .map(|blueprint| ChangeSet::from_blueprint(blueprint, origin))
+            .collect::<Vec<_>>();
+        let callbacks = self
+            .subscribers
+            .lock()
+            .unwrap()
+            .dispatch_channels(&change_sets);
+        callbacks.iter().for_each(|callback| {
+            change_sets.iter().for_each(|change_set| callback(change_set));
+        });
     }
-    fn clear_redo(&self) {
-        self.history.as_ref().lock().unwrap().redo.clear();
+    // Applies the reverse of each blueprint in the step, last first, and returns the step which was
+    // actually applied. If any blueprint fails, the ones already applied are rolled back, and if
+    // that fails too the error is `ElementCreationError::RollbackFailed`
+    fn apply_step_reversed(
+        &self,
+        step: &HistoryStep<TSchema>,
+    ) -> Result<HistoryStep<TSchema>, ElementCreationError> {
+        let mut applied: Vec<Blueprint<TSchema>> = Vec::with_capacity(step.blueprints.len());
+        for blueprint in step.blueprints.iter().rev() {
+            let reversed = blueprint.clone().reverse();
+            if let Err(error) = self.process_blueprint(reversed.clone()) {
+                let rollback_errors = applied
+                    .into_iter()
+                    .rev()
+                    .filter_map(|applied_blueprint| {
+                        self.process_blueprint(applied_blueprint.reverse()).err()
+                    })
+                    .collect::<Vec<_>>();
+                if rollback_errors.is_empty() {
+                    return Err(error);
+                }
+                return Err(ElementCreationError::RollbackFailed {
+                    error: Box::new(error),
+                    rollback_errors,
+                });
+            }
+            applied.push(reversed);
+        }
+        Ok(HistoryStep {
+            label: step.label.clone(),
+            blueprints: applied,
+        })
     }
 }
 impl<TSchema: EditRGSO + Send + Sync> RGraphEnvironment for RBaseGraphEnvironment<TSchema> {
@@ -418,25 +863,187 @@ impl<TSchema: EditRGSO + Send + Sync> RGraphEnvironment for RBaseGraphEnvironmen
         test
     }
 
-    fn undo(&self) {
-        let undo_item = self.history.as_ref().lock().unwrap().undo.pop();
-        if undo_item.is_none() {
-            return;
+    fn undo(&self) -> Result<(), ElementCreationError> {
+        let undo_step = {
+            let mut history = self.history.as_ref().lock().unwrap();
+            history.detach_open_group();
+            history.undo.pop()
+        };
+        let Some(undo_step) = undo_step else {
+            return Ok(());
+        };
+        let result = self.apply_step_reversed(&undo_step);
+        match result {
+            Ok(reversed) => {
+                let blueprints = reversed.blueprints.clone();
+                {
+                    let mut history = self.history.as_ref().lock().unwrap();
+                    history.redo.push(reversed);
+                    history.trim();
+                }
+                self.committed(&blueprints, ChangeOrigin::Undo);
+                Ok(())
+            }
+            Err(error) => {
+                self.history.as_ref().lock().unwrap().undo.push(undo_step);
+                Err(error)
+            }
         }
-        let undo_item = undo_item.unwrap().reverse();
-
-        self.process_blueprint(undo_item.clone());
-        self.push_redo(undo_item)
     }
 
-    fn redo(&self) {
-        let redo_item = self.history.as_ref().lock().unwrap().redo.pop();
-        if redo_item.is_none() {
-            return;
+    fn redo(&self) -> Result<(), ElementCreationError> {
+        let redo_step = {
+            let mut history = self.history.as_ref().lock().unwrap();
+            history.detach_open_group();
+            history.redo.pop()
+        };
+        let Some(redo_step) = redo_step else {
+            return Ok(());
+        };
+        let result = self.apply_step_reversed(&redo_step);
+        match result {
+            Ok(reversed) => {
+                let blueprints = reversed.blueprints.clone();
+                {
+                    let mut history = self.history.as_ref().lock().unwrap();
+                    history.undo.push(reversed);
+                    history.trim();
+                }
+                self.committed(&blueprints, ChangeOrigin::Redo);
+                Ok(())
+            }
+            Err(error) => {
+                self.history.as_ref().lock().unwrap().redo.push(redo_step);
+                Err(error)
+            }
         }
-        let redo_item = redo_item.unwrap().reverse();
-        self.process_blueprint(redo_item.clone());
-        self.push_undo(redo_item);
+    }
+}
+impl<TSchema: Send + Sync + Clone + Into<StandaloneRGSOConcrete> + 'static>
+    RBaseGraphEnvironment<TSchema>
+{
+    /// Appends every blueprint committed from now on, including undos and redos, to the journal
+    pub fn attach_journal(&self, journal: BlueprintJournal) {
+        *self.journal.lock().unwrap() = Some(JournalWriter::new(journal));
+    }
+    pub fn detach_journal(&self) -> Option<BlueprintJournal> {
+        self.journal
+            .lock()
+            .unwrap()
+            .take()
+            .map(JournalWriter::into_journal)
+    }
+    /// Writes every blueprint committed from now on, including undos, redos and merges, through to
+    /// the store. The store is expected to already hold the current state of the graph, see
+    /// [`Self::save_to_store`]
+    pub fn attach_store(&self, store: impl GraphStore + Send + 'static) {
+        *self.store.lock().unwrap() = Some(StoreWriter::new(Box::new(store)));
+    }
+    pub fn detach_store(&self) -> Option<Box<dyn GraphStore + Send>> {
+        self.store.lock().unwrap().take().map(StoreWriter::into_store)
+    }
+    /// Replaces the contents of the attached store with the current state of the graph
+    pub fn save_to_store(&self) -> Result<(), StoreError> {
+        let mut store = self.store.lock().unwrap();
+        let Some(store) = store.as_mut() else {
+            return Ok(());
+        };
+        let standalone_instances = self.created_instances.with_untracked(|created_instances| {
+            created_instances
+                .values()
+                .cloned()
+                .map(|inst| inst.into())
+                .collect::<Vec<StandaloneRGSOConcrete>>()
+        });
+        store.store.save(&standalone_instances)
+    }
+    /// Writes the current state of the graph as a snapshot to `snapshot_path` and empties the
+    /// attached journal, whose entries are all contained in the snapshot
+    pub fn compact_journal(
+        &self,
+        snapshot_path: impl AsRef<std::path::Path>,
+    ) -> Result<(), JournalError> {
+        let mut journal = self.journal.lock().unwrap();
+        let standalone_instances = self.created_instances.with_untracked(|created_instances| {
+            created_instances
+                .values()
+                .cloned()
+                .map(|inst| inst.into())
+                .collect::<Vec<StandaloneRGSOConcrete>>()
+        });
+        let json = instances_to_json(&standalone_instances, IdFormat::Numeric)
+            .map_err(|error| JournalError::Serialization(error.to_string()))?;
+        let Some(journal) = journal.as_mut() else {
+            return write_snapshot_json(snapshot_path, &json);
+        };
+        // The old journal is only replaced once the snapshot is written. If this is interrupted
+        // in between, `BlueprintJournal::recover` tells from the new journal's header whether
+        // the snapshot already contains the old journal's entries
+        journal.journal.prepare_rotation(&snapshot_fingerprint(&json))?;
+        write_snapshot_json(snapshot_path, &json)?;
+        journal.journal.finish_rotation()
+    }
+    /// Runs a query against the current state of the graph, see [`crate::post_generation::query`]
+    pub fn query(&self, query: &str) -> Result<QueryResult, QueryError> {
+        let standalone_instances = self.created_instances.with_untracked(|created_instances| {
+            created_instances
+                .values()
+                .cloned()
+                .map(|inst| inst.into())
+                .collect::<Vec<StandaloneRGSOConcrete>>()
+        });
+        execute_query(self.constraint_schema, &standalone_instances, query)
+    }
+    /// A Cypher script which recreates the graph, see [`crate::post_generation::export`]
+    pub fn export_cypher(&self) -> Result<String, ExportError> {
+        to_cypher(self.constraint_schema, &self.standalone_instances())
+    }
+    pub fn export_neo4j_csv(&self) -> Result<Neo4jCsvExport, ExportError> {
+        to_neo4j_csv(self.constraint_schema, &self.standalone_instances())
+    }
+    pub fn export_graphml(&self) -> Result<String, ExportError> {
+        to_graphml(self.constraint_schema, &self.standalone_instances())
+    }
+    /// An RDF graph with IRIs under `base_iri`, see [`to_turtle`]
+    pub fn export_turtle(&self, base_iri: &str) -> Result<String, ExportError> {
+        to_turtle(self.constraint_schema, &self.standalone_instances(), base_iri)
+    }
+    /// Audits the graph for internal consistency, e.g. edges missing from one of their ends, see
+    /// [`crate::post_generation::integrity`]
+    pub fn check_integrity(&self) -> Vec<IntegrityProblem> {
+        find_problems(self.constraint_schema, &self.standalone_instances())
+    }
+    /// The instance and what it reaches as a bundle for another graph, see
+    /// [`crate::post_generation::bundle`]
+    pub fn export_bundle(
+        &self,
+        root_id: &Uid,
+        options: &BundleOptions,
+    ) -> Result<SubgraphBundle, BundleError> {
+        export_bundle(
+            self.constraint_schema,
+            |id| {
+                self.created_instances
+                    .with_untracked(|created_instances| created_instances.get(id).cloned())
+                    .map(Into::into)
+            },
+            root_id,
+            options,
+        )
+    }
+    /// The graph's instances as JSON with ids in the given format. The `Serialize` implementation
+    /// writes them as numbers
+    pub fn to_json(&self, format: IdFormat) -> serde_json::Result<String> {
+        instances_to_json(&self.standalone_instances(), format)
+    }
+    fn standalone_instances(&self) -> Vec<StandaloneRGSOConcrete> {
+        self.created_instances.with_untracked(|created_instances| {
+            created_instances
+                .values()
+                .cloned()
+                .map(|inst| inst.into())
+                .collect()
+        })
     }
 }
 impl<TSchema: Send + Sync + Clone + Into<StandaloneRGSOConcrete> + 'static> Serialize
@@ -468,8 +1075,8 @@ pub trait RGraphEnvironment {
 
     fn get(&self, id: &Uid) -> Option<Self::Schema>;
     fn get_constraint_schema(&self) -> &ConstraintSchema<Self::Types, Self::Values>;
-    fn undo(&self);
-    fn redo(&self);
+    fn undo(&self) -> Result<(), ElementCreationError>;
+    fn redo(&self) -> Result<(), ElementCreationError>;
 }
 
 /// Reactive Generated Schema Object
@@ -496,11 +1103,6 @@ pub trait RGSO: std::fmt::Debug + Clone {
     }
     fn fields(&self) -> &std::collections::HashMap<Uid, RwSignal<PrimitiveValues>>;
 }
-impl From<RActiveSlot> for Uid {
-    fn from(value: RActiveSlot) -> Self {
-        todo!()
-    }
-}
 
 pub trait Slotted {}
 
@@ -558,11 +1160,10 @@ impl RActiveSlot {
         self.check_bound_conformity(len)
     }
     pub fn can_remove_one(&self) -> bool {
-        let len = self
-            .slotted_instances
+        self.slotted_instances
             .with(|slotted_instances| slotted_instances.len())
-            - 1;
-        self.check_bound_conformity(len)
+            .checked_sub(1)
+            .is_some_and(|len| self.check_bound_conformity(len))
     }
     pub fn can_add_one(&self) -> bool {
         let len = self
@@ -642,10 +1243,8 @@ impl<T: HasSlotEnum, TSchema> std::fmt::Debug for RGSOConcrete<T, TSchema> {
                             self.template
                                 .field_constraints
                                 .get(field_id)
-                                .unwrap()
-                                .tag
-                                .name
-                                .clone(),
+                                .map(|field| field.tag.name.clone())
+                                .unwrap_or_else(|| field_id.to_string()),
                             data.get(),
                         )
                     })
@@ -770,26 +1369,43 @@ impl<T: HasSlotEnum, TSchema> Verifiable for RGSOConcreteBuilder<T, TSchema> {
     fn verify(&self) -> Result<(), crate::post_generation::ElementCreationError> {
         let field_errors = self
             .data
-            .values()
-            .filter_map(|field_val| {
+            .iter()
+            .filter_map(|(field_id, field_val)| {
                 if field_val.with(|field_val| field_val.is_none()) {
-                    return Some(ElementCreationError::RequiredFieldIsEmpty);
+                    return Some(RequiredFieldError {
+                        instance_id: self.id,
+                        temp_id: Some(self.temp_id.clone()),
+                        operative_id: self.operative.tag.id,
+                        operative_name: self.operative.tag.name.clone(),
+                        field_id: *field_id,
+                        field_name: self
+                            .template
+                            .field_constraints
+                            .get(field_id)
+                            .map(|field| field.tag.name.clone())
+                            .unwrap_or_default(),
+                    }
+                    .into());
                 }
                 None
             })
             .collect::<Vec<_>>();
         let slot_errors = self
             .slots
-            .values()
-            .filter_map(|active_slot| {
+            .iter()
+            .filter_map(|(slot_id, active_slot)| {
                 if !active_slot.check_current_conformity() {
-                    Some(ElementCreationError::BoundCheckOutOfRange(format!(
-                        "{}: {}\nBounds: {:?}, Attempted: {}",
-                        self.operative.tag.name,
-                        active_slot.slot.tag.name,
-                        active_slot.slot.bounds,
-                        active_slot.slotted_instances.get().len()
-                    )))
+                    Some(BoundCheckError {
+                        instance_id: self.id,
+                        temp_id: Some(self.temp_id.clone()),
+                        operative_id: self.operative.tag.id,
+                        operative_name: self.operative.tag.name.clone(),
+                        slot_id: *slot_id,
+                        slot_name: active_slot.slot.tag.name.clone(),
+                        bounds: active_slot.slot.bounds.clone(),
+                        attempted: active_slot.slotted_instances.with(|instances| instances.len()),
+                    }
+                    .into())
                 } else {
                     None
                 }
@@ -823,6 +1439,35 @@ where
     fn get_operative_id() -> Uid;
 }
 
+/// Receives the builder started by [`BuildByOperative::build_by_operative`], whose concrete type
+/// is only known once the operative has been looked up
+pub trait BuilderVisitor<TSchema: 'static> {
+    type Output;
+
+    fn visit<T>(self, builder: SubgraphBuilder<T, TSchema>) -> Self::Output
+    where
+        T: RIntoSchema<Schema = TSchema>
+            + HasSlotEnum
+            + Send
+            + Sync
+            + Clone
+            + std::fmt::Debug
+            + 'static,
+        <T as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug + Send + Sync,
+        RGSOConcreteBuilder<T, TSchema>: RProducable<RGSOConcrete<T, TSchema>>;
+}
+
+/// Starts building an instance of an operative picked at runtime, for callers which only have its
+/// id (e.g. importers)
+pub trait BuildByOperative: Sized + 'static {
+    /// Returns `None` if the operative isn't part of the schema
+    fn build_by_operative<V: BuilderVisitor<Self>>(
+        operative_id: &Uid,
+        graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<Self>>>,
+        visitor: V,
+    ) -> Option<V::Output>;
+}
+
 trait RInstantiable: Send + Sync {
     type Schema;
 
@@ -831,20 +1476,20 @@ trait RInstantiable: Send + Sync {
     fn get_temp_id(&self) -> &String;
     fn get_template(&self) -> &'static LibraryTemplate<PrimitiveTypes, PrimitiveValues>;
     fn add_incoming(&mut self, host_id: &Uid, slot_id: &Uid);
-    fn add_outgoing(&mut self, target_id: &Uid, slot_id: &Uid);
+    fn add_outgoing(&mut self, target_id: &Uid, slot_id: &Uid) -> Result<(), ElementCreationError>;
 }
 type RInstantiableElements<TSchema> = Vec<std::sync::Arc<dyn RInstantiable<Schema = TSchema>>>;
 
 #[derive(Clone, Debug)]
 pub struct Blueprint<TSchema> {
-    added_instances: Vec<TSchema>,
-    deleted_instances: Vec<TSchema>,
-    add_outgoing_updates: std::collections::HashSet<(Uid, SlotRef)>,
-    remove_outgoing_updates: std::collections::HashSet<(Uid, SlotRef)>,
-    add_incoming_updates: std::collections::HashSet<(Uid, SlotRef)>,
-    remove_incoming_updates: std::collections::HashSet<(Uid, SlotRef)>,
-    field_updates: std::collections::HashSet<(Uid, HistoryFieldEdit)>,
-    action_tag: Option<TaggedAction>,
+    pub added_instances: Vec<TSchema>,
+    pub deleted_instances: Vec<TSchema>,
+    pub add_outgoing_updates: std::collections::HashSet<(Uid, SlotRef)>,
+    pub remove_outgoing_updates: std::collections::HashSet<(Uid, SlotRef)>,
+    pub add_incoming_updates: std::collections::HashSet<(Uid, SlotRef)>,
+    pub remove_incoming_updates: std::collections::HashSet<(Uid, SlotRef)>,
+    pub field_updates: std::collections::HashSet<(Uid, HistoryFieldEdit)>,
+    pub label: Option<String>,
 }
 impl<TSchema> Blueprint<TSchema> {
     fn reverse(self) -> Self {
@@ -860,11 +1505,71 @@ impl<TSchema> Blueprint<TSchema> {
                 .into_iter()
                 .map(|(id, field_update)| (id, field_update.reverse()))
                 .collect(),
-            action_tag: self.action_tag,
+            label: self.label,
         }
     }
 }
 
+impl<TSchema: Clone + Into<StandaloneRGSOConcrete>> From<&Blueprint<TSchema>>
+    for StandaloneBlueprint
+{
+    fn from(value: &Blueprint<TSchema>) -> Self {
+        Self::from_blueprint(value, TSchema::into)
+    }
+}
+impl StandaloneBlueprint {
+    pub(crate) fn from_blueprint<TSchema: Clone>(
+        value: &Blueprint<TSchema>,
+        to_standalone: fn(TSchema) -> StandaloneRGSOConcrete,
+    ) -> Self {
+        Self {
+            added_instances: value
+                .added_instances
+                .iter()
+                .cloned()
+                .map(to_standalone)
+                .collect(),
+            deleted_instances: value
+                .deleted_instances
+                .iter()
+                .cloned()
+                .map(to_standalone)
+                .collect(),
+            add_outgoing_updates: value.add_outgoing_updates.iter().cloned().collect(),
+            remove_outgoing_updates: value.remove_outgoing_updates.iter().cloned().collect(),
+            add_incoming_updates: value.add_incoming_updates.iter().cloned().collect(),
+            remove_incoming_updates: value.remove_incoming_updates.iter().cloned().collect(),
+            field_updates: value.field_updates.iter().cloned().collect(),
+            label: value.label.clone(),
+        }
+    }
+}
+
+impl<TSchema: from_reactive::FromStandalone<Schema = TSchema>> Blueprint<TSchema> {
+    /// Rebuilds a blueprint whose instances belong to the given graph
+    pub fn from_standalone(
+        value: StandaloneBlueprint,
+        graph: SharedGraph<TSchema>,
+    ) -> Result<Self, ElementCreationError> {
+        let to_instances = |instances: Vec<StandaloneRGSOConcrete>| {
+            instances
+                .into_iter()
+                .map(|instance| TSchema::from_standalone(instance, std::sync::Arc::clone(&graph).into()))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(Self {
+            added_instances: to_instances(value.added_instances)?,
+            deleted_instances: to_instances(value.deleted_instances)?,
+            add_outgoing_updates: value.add_outgoing_updates.into_iter().collect(),
+            remove_outgoing_updates: value.remove_outgoing_updates.into_iter().collect(),
+            add_incoming_updates: value.add_incoming_updates.into_iter().collect(),
+            remove_incoming_updates: value.remove_incoming_updates.into_iter().collect(),
+            field_updates: value.field_updates.into_iter().collect(),
+            label: value.label,
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TempAddIncomingSlotRef {
     pub host_instance_id: BlueprintId,
@@ -876,15 +1581,6 @@ pub struct TempAddOutgoingSlotRef {
     pub slot_id: Uid,
 }
 
-pub struct ExecutionResult {
-    pub temp_id_map: std::collections::HashMap<String, Uid>,
-}
-impl ExecutionResult {
-    pub fn get_final_id(&self, temp_id: &str) -> Option<&Uid> {
-        self.temp_id_map.get(temp_id)
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct SubgraphBuilder<T: HasSlotEnum + Clone + std::fmt::Debug, TSchema: 'static>
 where
@@ -900,6 +1596,9 @@ where
     pub deleted_instances: RwSignal<std::collections::HashSet<Uid>>,
     pub to_delete_recursive: RwSignal<std::collections::HashSet<Uid>>,
     pub field_updates: RwSignal<std::collections::HashSet<(Uid, HistoryFieldEdit)>>,
+    // The version of every existing instance which the updates were based on, as of when it was
+    // first read. `execute` fails if any of them has changed since
+    pub read_versions: RwSignal<std::collections::HashMap<Uid, u64>>,
     pub temp_add_incoming_updates:
         RwSignal<std::collections::HashSet<(String, TempAddIncomingSlotRef)>>,
     pub temp_add_outgoing_updates:
@@ -925,11 +1624,27 @@ where
         &self.id
     }
     pub fn execute(&self) -> Result<ExecutionResult, ElementCreationError> {
+        self.execute_labelled(None)
+    }
+    /// Same as [`Self::execute`], but names the resulting undo step (e.g. "Rename template")
+    pub fn execute_with_label(
+        &self,
+        label: impl Into<String>,
+    ) -> Result<ExecutionResult, ElementCreationError> {
+        self.execute_labelled(Some(label.into()))
+    }
+    fn execute_labelled(
+        &self,
+        label: Option<String>,
+    ) -> Result<ExecutionResult, ElementCreationError> {
         let graph = self.graph.clone();
-        let (blueprint, execution_result) = self.clone().get_blueprint()?;
-        graph.clear_redo();
-        graph.push_undo(blueprint.clone());
-        graph.process_blueprint(blueprint);
+        // Checked up front as well, since the bounds checks in `get_blueprint` would otherwise run
+        // against a graph which has changed underneath the builder
+        check_versions(|id| graph.version(id), &self.read_versions.get_untracked())?;
+        let (mut blueprint, execution_result) = self.clone().get_blueprint()?;
+        blueprint.label = label;
+        graph.process_blueprint_if_current(blueprint.clone(), &self.read_versions.get_untracked())?;
+        graph.record_history(blueprint);
         Ok(execution_result)
     }
     pub fn incorporate<
@@ -979,6 +1694,16 @@ where
         self.field_updates.update(|prev| {
             prev.extend(other_builder.field_updates.get());
         });
+        self.read_versions.update(|prev| {
+            other_builder
+                .read_versions
+                .get()
+                .into_iter()
+                .for_each(|(id, version)| {
+                    let read_version = prev.entry(id).or_insert(version);
+                    *read_version = (*read_version).min(version);
+                });
+        });
         self.cumulative_errors
             .update(|prev| prev.extend(other_builder.cumulative_errors.get()));
     }
@@ -992,8 +1717,12 @@ where
     // -------------
     // To be private
     // -------------
-    pub fn delete_recursive_handler(&self, id: &Uid) {
-        let item = self.graph.get(id).unwrap();
+    pub fn delete_recursive_handler(&mut self, id: &Uid) {
+        let Some(item) = self.graph.get(id) else {
+            self.add_error(ElementCreationError::NonexistentInstance { id: *id });
+            return;
+        };
+        self.observe(id);
         let pending_incoming_removals = self.remove_incoming_updates.with(|remove_updates| {
             remove_updates
                 .iter()
@@ -1009,21 +1738,21 @@ where
                 .len()
         });
         if item.incoming_slots().with(|incoming_slots| {
-            incoming_slots.len() + pending_incoming_additions - pending_incoming_removals == 0
+            (incoming_slots.len() + pending_incoming_additions)
+                .saturating_sub(pending_incoming_removals)
+                == 0
         }) {
-            let slotted_instances = item
-                .outgoing_slots()
+            item.outgoing_slots()
                 .values()
                 .flat_map(|slot| slot.slotted_instances.get())
                 .for_each(|instance_id| self.delete_recursive_handler(&instance_id));
-            slotted_instances
         }
     }
     // Perform final calculations to gather all changes
     pub fn get_blueprint(
         mut self,
     ) -> Result<(Blueprint<TSchema>, ExecutionResult), ElementCreationError> {
-        let mut all_errors = self.cumulative_errors.get();
+        let mut all_errors = Vec::new();
         let mut new_instantiables = self.instantiables.get();
         if let Some(instance) = &self.wip_instance {
             new_instantiables.push(std::sync::Arc::new(std::sync::Mutex::new(instance.clone())));
@@ -1060,10 +1789,10 @@ where
                             },
                         ),
                     };
-                    if let Some(error) = final_host_id.clone().err() {
-                        return Some(error);
-                    }
-                    let final_host_id = final_host_id.unwrap();
+                    let final_host_id = match final_host_id {
+                        Ok(final_host_id) => final_host_id,
+                        Err(error) => return Some(error),
+                    };
                     if let Some(instantiable) = new_instantiables.iter_mut().find(|instantiable| {
                         instantiable.lock().unwrap().get_temp_id() == &update.0
                     }) {
@@ -1101,10 +1830,10 @@ where
                             })
                             .cloned(),
                     };
-                    if let Some(error) = final_target_id.clone().err() {
-                        return Some(error);
-                    }
-                    let final_target_id = final_target_id.unwrap();
+                    let final_target_id = match final_target_id {
+                        Ok(final_target_id) => final_target_id,
+                        Err(error) => return Some(error),
+                    };
 
                     match &update.0 {
                         BlueprintId::Existing(existing_id) => {
@@ -1125,11 +1854,16 @@ where
                                     instantiable.lock().unwrap().get_temp_id() == temp_id
                                 })
                             {
-                                instantiable
+                                return instantiable
                                     .as_ref()
                                     .lock()
                                     .unwrap()
-                                    .add_outgoing(&final_target_id, &update.1.slot_id);
+                                    .add_outgoing(&final_target_id, &update.1.slot_id)
+                                    .err();
+                            } else {
+                                return Some(ElementCreationError::NonexistentTempId {
+                                    temp_id: temp_id.clone(),
+                                });
                             }
                         }
                     };
@@ -1142,13 +1876,12 @@ where
         let to_delete = self.to_delete_recursive.get();
         to_delete.iter().for_each(|to_delete_id| {
             self.delete(to_delete_id);
-            let item = self.graph.get(to_delete_id).unwrap();
-            let slotted_instances = item
-                .outgoing_slots()
-                .values()
-                .flat_map(|slot| slot.slotted_instances.get())
-                .for_each(|instance_id| self.delete_recursive_handler(&instance_id));
-            slotted_instances
+            if let Some(item) = self.graph.get(to_delete_id) {
+                item.outgoing_slots()
+                    .values()
+                    .flat_map(|slot| slot.slotted_instances.get())
+                    .for_each(|instance_id| self.delete_recursive_handler(&instance_id));
+            }
         });
 
         // Get rid of all changes on nodes that will be deleted
@@ -1156,7 +1889,7 @@ where
         let cloned_delete_instances = self.deleted_instances.with(|deleted_instances| {
             deleted_instances
                 .iter()
-                .map(|deleted_instance_id| {
+                .filter_map(|deleted_instance_id| {
                     self.add_outgoing_updates
                         .update(|prev| prev.retain(|change| change.0 != *deleted_instance_id));
                     self.remove_outgoing_updates
@@ -1167,13 +1900,24 @@ where
                         .update(|prev| prev.retain(|change| change.0 != *deleted_instance_id));
                     self.field_updates
                         .update(|prev| prev.retain(|change| change.0 != *deleted_instance_id));
-                    self.graph.created_instances.with(|created_instances| {
-                        created_instances.get(deleted_instance_id).unwrap().clone()
-                    })
+                    // Nonexistent ids have already been reported by `delete`
+                    self.graph.get(deleted_instance_id)
                 })
                 .collect::<Vec<_>>()
         });
 
+        // The bounds checks read the current slots of every host
+        let read_ids = self
+            .add_outgoing_updates
+            .get_untracked()
+            .into_iter()
+            .chain(self.remove_outgoing_updates.get_untracked())
+            .chain(self.add_incoming_updates.get_untracked())
+            .chain(self.remove_incoming_updates.get_untracked())
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        read_ids.iter().for_each(|id| self.observe(id));
+
         // Check slot bounds for conformity
         let mut already_checked = vec![];
         let bounds_checks = self
@@ -1195,35 +1939,41 @@ where
                             let all_additions = add_outgoing_updates
                                 .iter()
                                 .filter(|item| item.0 == update.0);
-                            let errors = self
-                                .graph
-                                .get(&update.0)
-                                .unwrap()
+                            let Some(host) = self.graph.get(&update.0) else {
+                                return Some(vec![ElementCreationError::NonexistentInstance {
+                                    id: update.0,
+                                }]);
+                            };
+                            let errors = host
                                 .outgoing_slots()
                                 .iter()
                                 .filter_map(|slot| {
-                                    let final_count = slot
+                                    let final_count = (slot
                                         .1
                                         .slotted_instances
                                         .with(|slotted_instances| slotted_instances.len())
                                         + all_additions
                                             .clone()
                                             .filter(|addition| addition.1.slot_id == **slot.0)
-                                            .collect::<Vec<_>>()
-                                            .len()
-                                        - all_removals
+                                            .count())
+                                    .saturating_sub(
+                                        all_removals
                                             .clone()
-                                            .filter(|addition| addition.1.slot_id == **slot.0)
-                                            .collect::<Vec<_>>()
-                                            .len();
+                                            .filter(|removal| removal.1.slot_id == **slot.0)
+                                            .count(),
+                                    );
                                     if !slot.1.check_bound_conformity(final_count) {
-                                        Some(ElementCreationError::BoundCheckOutOfRange(format!(
-                                            "{}: {}\nBounds: {:?}, Attempted: {}",
-                                            self.graph.get(&update.0).unwrap().operative().tag.name,
-                                            slot.1.slot.tag.name,
-                                            slot.1.slot.bounds,
-                                            slot.1.slotted_instances.get().len()
-                                        )))
+                                        Some(BoundCheckError {
+                                            instance_id: update.0,
+                                            temp_id: None,
+                                            operative_id: host.operative().tag.id,
+                                            operative_name: host.operative().tag.name.clone(),
+                                            slot_id: **slot.0,
+                                            slot_name: slot.1.slot.tag.name.clone(),
+                                            bounds: slot.1.slot.bounds.clone(),
+                                            attempted: final_count,
+                                        }
+                                        .into())
                                     } else {
                                         None
                                     }
@@ -1243,8 +1993,8 @@ where
 
         if !bounds_checks.is_empty() {
             leptos::logging::log!("{:#?}", bounds_checks);
-            return Err(ElementCreationError::Stack(bounds_checks));
         }
+        all_errors.extend(bounds_checks);
 
         let (instantiated_elements, instantiation_errors) = new_instantiables.iter().fold(
             (Vec::with_capacity(new_instantiables.len()), Vec::new()),
@@ -1258,6 +2008,16 @@ where
         );
 
         all_errors.extend(instantiation_errors);
+        // Includes any errors recorded while gathering deletions above
+        let mut all_errors = [self.cumulative_errors.get(), all_errors].concat();
+        all_errors.extend(
+            self.check_slot_targets(&instantiated_elements, &cloned_delete_instances),
+        );
+        all_errors.extend(self.check_unique_fields(
+            &instantiated_elements,
+            &cloned_delete_instances,
+            &temp_id_map,
+        ));
         if !all_errors.is_empty() {
             return Err(ElementCreationError::Stack(all_errors));
         }
@@ -1271,11 +2031,125 @@ where
                 remove_incoming_updates: self.remove_incoming_updates.get(),
                 deleted_instances: cloned_delete_instances,
                 field_updates: self.field_updates.get(),
-                action_tag: None,
+                label: None,
             },
             ExecutionResult { temp_id_map },
         ))
     }
+    // Ensures that every edge created in this subgraph points at an instance which will exist once
+    // the subgraph has been committed, and which the slot accepts
+    fn check_slot_targets(
+        &self,
+        added_instances: &[TSchema],
+        deleted_instances: &[TSchema],
+    ) -> Vec<ElementCreationError> {
+        let constraint_schema = self.graph.constraint_schema;
+        let resolve = |id: &Uid| {
+            if deleted_instances
+                .iter()
+                .any(|instance| instance.get_id() == id)
+            {
+                return None;
+            }
+            added_instances
+                .iter()
+                .find(|instance| instance.get_id() == id)
+                .cloned()
+                .or_else(|| self.graph.get(id))
+        };
+        // Edges of newly created instances
+        let mut new_edges = added_instances
+            .iter()
+            .flat_map(|instance| {
+                instance
+                    .outgoing_slots()
+                    .into_values()
+                    .flat_map(|active_slot| {
+                        active_slot
+                            .slotted_instances
+                            .get_untracked()
+                            .into_iter()
+                            .map(|target_id| (*instance.get_id(), active_slot.slot, target_id))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        // Edges added to existing instances
+        let mut errors = Vec::new();
+        self.add_outgoing_updates.with(|add_outgoing_updates| {
+            add_outgoing_updates.iter().for_each(|(host_id, slot_ref)| {
+                let Some(host) = resolve(host_id) else {
+                    return;
+                };
+                match host.template().operative_slots.get(&slot_ref.slot_id) {
+                    Some(slot) => new_edges.push((*host_id, slot, slot_ref.target_instance_id)),
+                    None => errors.push(ElementCreationError::NonexistentSlot {
+                        instance_id: *host_id,
+                        slot_id: slot_ref.slot_id,
+                    }),
+                }
+            })
+        });
+
+        new_edges
+            .into_iter()
+            .for_each(|(host_id, slot, target_id)| match resolve(&target_id) {
+                None => errors.push(ElementCreationError::OutgoingElementDoesntExist {
+                    host_id,
+                    slot_id: slot.tag.id,
+                    target_id,
+                }),
+                Some(target) if !slot_accepts_operative(constraint_schema, slot, target.operative()) => {
+                    let mut expected = constraint_schema
+                        .operative_library
+                        .values()
+                        .filter(|operative| slot_accepts_operative(constraint_schema, slot, operative))
+                        .map(|operative| operative.tag.name.clone())
+                        .collect::<Vec<_>>();
+                    expected.sort();
+                    errors.push(ElementCreationError::OutgoingElementIsWrongType {
+                        host_id,
+                        slot_id: slot.tag.id,
+                        target_id,
+                        expected,
+                        received: target.operative().tag.name.clone(),
+                    })
+                }
+                Some(_) => {}
+            });
+        errors
+    }
+    // Ensures that no two instances will share a value in any field marked as unique once this
+    // subgraph has been committed
+    fn check_unique_fields(
+        &self,
+        added_instances: &[TSchema],
+        deleted_instances: &[TSchema],
+        temp_id_map: &std::collections::HashMap<String, Uid>,
+    ) -> Vec<ElementCreationError> {
+        let deleted_ids = deleted_instances
+            .iter()
+            .map(|instance| *instance.get_id())
+            .collect::<std::collections::HashSet<_>>();
+        let field_updates = self.field_updates.get();
+        check_unique_fields(
+            self.graph.constraint_schema,
+            &self.graph.field_index.lock().unwrap(),
+            added_instances.iter().flat_map(|instance| {
+                indexable_fields(instance)
+                    .into_iter()
+                    .map(|(field_id, value)| (*instance.get_id(), field_id, value))
+            }),
+            &field_updates.iter().collect::<Vec<_>>(),
+            &deleted_ids,
+            |instance_id| {
+                temp_id_map
+                    .iter()
+                    .find(|(_, id)| *id == instance_id)
+                    .map(|(temp_id, _)| temp_id.clone())
+            },
+        )
+    }
     pub fn get_graph(&self) -> &std::sync::Arc<RBaseGraphEnvironment<TSchema>> {
         &self.graph
     }
@@ -1284,11 +2158,16 @@ where
         id: Uid,
         graph: std::sync::Arc<RBaseGraphEnvironment<TSchema>>,
     ) -> Self {
+        let read_versions = match builder_wrapper_instance {
+            Some(_) => std::collections::HashMap::new(),
+            None => std::collections::HashMap::from([(id, graph.version(&id))]),
+        };
         Self {
             graph,
             instantiables: RwSignal::new(vec![]),
             wip_instance: builder_wrapper_instance,
             id,
+            read_versions: RwSignal::new(read_versions),
             cumulative_errors: RwSignal::new(std::vec::Vec::new()),
             add_outgoing_updates: RwSignal::new(std::collections::HashSet::new()),
             add_incoming_updates: RwSignal::new(std::collections::HashSet::new()),
@@ -1302,12 +2181,21 @@ where
             to_delete_recursive: RwSignal::new(std::collections::HashSet::new()),
         }
     }
+    // Records the version of an existing instance the first time the builder relies on it
+    pub fn observe(&self, id: &Uid) {
+        let version = self.graph.version(id);
+        self.read_versions.update(|prev| {
+            prev.entry(*id).or_insert(version);
+        });
+    }
     pub fn raw_add_outgoing_to_updates(&mut self, slot_ref: SlotRef) {
+        self.observe(&slot_ref.host_instance_id);
         self.add_outgoing_updates.update(|prev| {
             prev.insert((slot_ref.host_instance_id, slot_ref));
         });
     }
     pub fn raw_add_incoming_to_updates(&mut self, slot_ref: SlotRef) {
+        self.observe(&slot_ref.target_instance_id);
         self.add_incoming_updates.update(|prev| {
             prev.insert((slot_ref.target_instance_id, slot_ref));
         });
@@ -1335,10 +2223,17 @@ where
         if let Some(instance) = &self.wip_instance {
             match &target_id {
                 BlueprintId::Existing(existing_target_id) => {
-                    let slot = instance.slots.get(slot_id).unwrap();
-                    slot.slotted_instances.update(|prev| {
-                        prev.push(*existing_target_id);
-                    });
+                    if let Some(slot) = instance.slots.get(slot_id) {
+                        slot.slotted_instances.update(|prev| {
+                            prev.push(*existing_target_id);
+                        });
+                    } else {
+                        let error = ElementCreationError::NonexistentSlot {
+                            instance_id: *instance.get_id(),
+                            slot_id: *slot_id,
+                        };
+                        self.add_error(error);
+                    }
                 }
                 BlueprintId::Temporary(_temp_target_id) => {
                     self.temp_add_outgoing(
@@ -1374,6 +2269,8 @@ where
         }
     }
     pub fn remove_outgoing(&mut self, slot_ref: SlotRef) {
+        self.observe(&slot_ref.host_instance_id);
+        self.observe(&slot_ref.target_instance_id);
         self.remove_outgoing_updates.update(|prev| {
             prev.insert((slot_ref.host_instance_id, slot_ref.clone()));
         });
@@ -1381,6 +2278,55 @@ where
             prev.insert((slot_ref.target_instance_id, slot_ref));
         });
     }
+    /// Detaches the target of `from` and slots it into `to_slot_id` on `to_host_id` instead.
+    /// `from` may also be an edge added by an earlier move of the same builder. Like every other
+    /// change, the bounds of both slots are only checked against the final state on `execute`
+    pub fn move_to(&mut self, from: SlotRef, to_host_id: &Uid, to_slot_id: &Uid) {
+        let to = SlotRef {
+            host_instance_id: *to_host_id,
+            target_instance_id: from.target_instance_id,
+            slot_id: *to_slot_id,
+        };
+        if to == from {
+            return;
+        }
+        let outgoing = (from.host_instance_id, from.clone());
+        let is_pending = self
+            .add_outgoing_updates
+            .with_untracked(|add_outgoing_updates| add_outgoing_updates.contains(&outgoing));
+        if is_pending {
+            // Moved on again before being committed, so the pending edge is dropped instead
+            self.add_outgoing_updates.update(|prev| {
+                prev.remove(&outgoing);
+            });
+            self.add_incoming_updates.update(|prev| {
+                prev.remove(&(from.target_instance_id, from.clone()));
+            });
+        } else {
+            let is_slotted = self.graph.get(&from.host_instance_id).is_some_and(|host| {
+                host.outgoing_slots().get(&from.slot_id).is_some_and(|slot| {
+                    slot.slotted_instances
+                        .with_untracked(|slotted_instances| {
+                            slotted_instances.contains(&from.target_instance_id)
+                        })
+                })
+            });
+            let is_removed = self
+                .remove_outgoing_updates
+                .with_untracked(|remove_outgoing_updates| remove_outgoing_updates.contains(&outgoing));
+            if !is_slotted || is_removed {
+                self.add_error(ElementCreationError::NonexistentEdge {
+                    host_id: from.host_instance_id,
+                    slot_id: from.slot_id,
+                    target_id: from.target_instance_id,
+                });
+                return;
+            }
+            self.remove_outgoing(from);
+        }
+        self.raw_add_outgoing_to_updates(to.clone());
+        self.raw_add_incoming_to_updates(to);
+    }
     pub fn add_incoming<
         C: std::fmt::Debug
             + Clone
@@ -1401,6 +2347,7 @@ where
                 .incoming_slots
                 .update(|prev| prev.push(slot_ref.clone()))
         } else {
+            self.observe(&slot_ref.target_instance_id);
             self.add_incoming_updates.update(|prev| {
                 prev.insert((slot_ref.target_instance_id, slot_ref));
             });
@@ -1411,34 +2358,59 @@ where
     }
     pub fn edit_field(&mut self, field_id: Uid, value: PrimitiveValues) {
         if let Some(instance) = &self.wip_instance {
-            let signal = instance.data.get(&field_id).unwrap();
-            let is_none = signal.with(|val| val.is_none());
-            if is_none {
-                signal.set(Some(RwSignal::new(value)));
-            } else {
-                signal.update(|prev| prev.unwrap().set(value))
+            let Some(signal) = instance.data.get(&field_id) else {
+                let error = ElementCreationError::NonexistentField {
+                    instance_id: *instance.get_id(),
+                    field_id,
+                };
+                self.add_error(error);
+                return;
+            };
+            if let Err(error) =
+                check_field_value_type(instance.id, instance.template, &field_id, &value)
+            {
+                self.add_error(error);
+                return;
+            }
+            match signal.get_untracked() {
+                Some(inner) => inner.set(value),
+                None => signal.set(Some(RwSignal::new(value))),
             }
         } else {
+            // TODO: It seems like there could be a better way to do this than looking up the
+            // value like this.
+            // At the very least, for ExistingBuilders which are entered into with `.edit()`,
+            // we would have access to the concrete node at the time of `ExistingBuilder` creation.
+            // The difficulty comes in that ExistingBuilders are also created through the process
+            // of adding some outgoing node, in which case we'd only have the id and would still
+            // have to do the lookup like this at some point.
+            let Some(existing_instance) = self.graph.get(self.get_id()) else {
+                self.add_error(ElementCreationError::NonexistentInstance { id: self.id });
+                return;
+            };
+            self.observe(&self.id);
+            let Some(prev_value) = existing_instance
+                .fields()
+                .get(&field_id)
+                .map(|field| field.get_untracked())
+            else {
+                self.add_error(ElementCreationError::NonexistentField {
+                    instance_id: self.id,
+                    field_id,
+                });
+                return;
+            };
+            if let Err(error) =
+                check_field_value_type(self.id, existing_instance.template(), &field_id, &value)
+            {
+                self.add_error(error);
+                return;
+            }
             self.field_updates.update(|prev| {
-                // TODO: It seems like there could be a better way to do this than looking up the
-                // value like this.
-                // At the very least, for ExistingBuilders which are entered into with `.edit()`,
-                // we would have access to the concrete node at the time of `ExistingBuilder` creation.
-                // The difficulty comes in that ExistingBuilders are also created through the process
-                // of adding some outgoing node, in which case we'd only have the id and would still
-                // have to do the lookup like this at some point.
-                let prev_value = self
-                    .graph
-                    .get(self.get_id())
-                    .unwrap()
-                    .fields()
-                    .get(&field_id)
-                    .unwrap()
-                    .get();
                 prev.insert((
-                    *self.get_id(),
+                    self.id,
                     HistoryFieldEdit {
-                        instance_id: *self.get_id(),
+                        instance_id: self.id,
                         field_id,
                         new_value: value,
                         prev_value,
@@ -1451,9 +2423,14 @@ where
         self.deleted_instances.update(|prev| {
             prev.insert(*to_delete_id);
         });
-        let existing_instance = self.graph.get(&self.id).unwrap();
+        let Some(existing_instance) = self.graph.get(to_delete_id) else {
+            self.add_error(ElementCreationError::NonexistentInstance { id: *to_delete_id });
+            return;
+        };
+        self.observe(to_delete_id);
         existing_instance.incoming_slots().with(|incoming_slots| {
             incoming_slots.iter().for_each(|incoming_slot| {
+                self.observe(&incoming_slot.host_instance_id);
                 self.remove_outgoing_updates.update(|removes| {
                     removes.insert((incoming_slot.host_instance_id, incoming_slot.clone()));
                 })
@@ -1465,6 +2442,7 @@ where
             .for_each(|slot| {
                 slot.slotted_instances.with(|slotted_instances| {
                     slotted_instances.iter().for_each(|target_instance_id| {
+                        self.observe(target_instance_id);
                         self.remove_incoming_updates.update(|removes| {
                             removes.insert((
                                 *target_instance_id,
@@ -1539,12 +2517,17 @@ where
         });
     }
 
-    fn add_outgoing(&mut self, target_id: &Uid, slot_id: &Uid) {
-        self.slots
+    fn add_outgoing(&mut self, target_id: &Uid, slot_id: &Uid) -> Result<(), ElementCreationError> {
+        let slot = self
+            .slots
             .get(slot_id)
-            .unwrap()
-            .slotted_instances
+            .ok_or(ElementCreationError::NonexistentSlot {
+                instance_id: self.id,
+                slot_id: *slot_id,
+            })?;
+        slot.slotted_instances
             .update(|slotted_instances| slotted_instances.push(*target_id));
+        Ok(())
     }
 }
 
@@ -1618,65 +2601,41 @@ where
 }
 
 pub mod from_reactive {
-    use std::{
-        cell::RefCell,
-        collections::HashMap,
-        io::Seek,
-        sync::{Arc, Mutex},
-    };
-
     use super::{HasSlotEnum, SharedGraph, SpecializedRActiveSlot};
-    use crate::post_generation::{BaseGraphEnvironment, GSOConcrete};
+    use crate::post_generation::{
+        plain::PlainGraphEnvironment, ElementCreationError, GSOConcrete, StandaloneRGSOConcrete,
+    };
     use leptos::prelude::*;
 
-    use super::{
-        EditRGSO, FromNonReactive, RActiveSlot, RBaseGraphEnvironment, RGSOConcrete,
-        RHistoryContainer, RGSO,
-    };
-    impl<RTSchema: Send + Sync, TSchema: Send + Sync> From<SharedGraph<RTSchema>>
-        for BaseGraphEnvironment<TSchema>
+    use super::{RActiveSlot, RGSOConcrete, RGSO};
+    impl<RTSchema: Send + Sync> TryFrom<SharedGraph<RTSchema>> for PlainGraphEnvironment
     where
-        RTSchema: Into<TSchema> + Clone,
+        RTSchema: Into<StandaloneRGSOConcrete> + Clone,
     {
-        fn from(value: SharedGraph<RTSchema>) -> Self {
-            Self {
-                created_instances: value
+        type Error = ElementCreationError;
+        fn try_from(value: SharedGraph<RTSchema>) -> Result<Self, Self::Error> {
+            PlainGraphEnvironment::from_instances(
+                value.0.constraint_schema,
+                value
                     .0
                     .created_instances
-                    .get()
-                    .into_iter()
-                    .map(|(id, val)| (id, val.into()))
+                    .get_untracked()
+                    .into_values()
+                    .map(Into::into)
                     .collect(),
-                constraint_schema: value.0.constraint_schema,
-            }
+            )
         }
     }
-    impl<RTSchema: Send + Sync, TSchema: Send + Sync> From<BaseGraphEnvironment<TSchema>>
-        for SharedGraph<RTSchema>
+    impl<RTSchema> TryFrom<PlainGraphEnvironment> for SharedGraph<RTSchema>
     where
-        RTSchema: FromNonReactive<TSchema>,
+        RTSchema: Send + Sync + RGSO + FromStandalone<Schema = RTSchema> + 'static,
     {
-        fn from(value: BaseGraphEnvironment<TSchema>) -> Self {
-            let new_graph = RBaseGraphEnvironment::<RTSchema> {
-                created_instances: RwSignal::new(HashMap::new()),
-                constraint_schema: value.constraint_schema,
-                history: Arc::new(Mutex::new(RHistoryContainer {
-                    undo: vec![],
-                    redo: vec![],
-                })),
-            };
-            let rc_graph = Arc::new(new_graph);
-            let members = value.created_instances.into_iter().map(|(id, val)| {
-                (
-                    id,
-                    <RTSchema as FromNonReactive<TSchema>>::from_non_reactive(
-                        val,
-                        rc_graph.clone(),
-                    ),
-                )
-            });
-            rc_graph.initialize(members.collect());
-            rc_graph.into()
+        type Error = ElementCreationError;
+        fn try_from(value: PlainGraphEnvironment) -> Result<Self, Self::Error> {
+            crate::utils::populate_graph(
+                value.constraint_schema,
+                value.instances().cloned().collect(),
+            )
         }
     }
     impl From<RActiveSlot> for crate::post_generation::ActiveSlot {
@@ -1734,9 +2693,425 @@ pub mod from_reactive {
         fn from_standalone(
             value: crate::post_generation::StandaloneRGSOConcrete,
             graph: SharedGraph<Self::Schema>,
-        ) -> Self::Schema;
+        ) -> Result<Self::Schema, crate::post_generation::ElementCreationError>;
     }
 }
 
 pub trait RootConstraints: HasSlotEnum + Send + Sync + Clone + 'static {}
 impl<T> RootConstraints for T where T: HasSlotEnum + Send + Sync + Clone + 'static {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn targets(graph: &SharedGraph<TestSchema>, host_id: &Uid, slot_id: &Uid) -> Vec<Uid> {
+        graph
+            .get(host_id)
+            .unwrap()
+            .outgoing_slots()
+            .get(slot_id)
+            .unwrap()
+            .slotted_instances
+            .get_untracked()
+    }
+    fn edge(host_id: Uid, slot_id: Uid, target_id: Uid) -> SlotRef {
+        SlotRef {
+            host_instance_id: host_id,
+            slot_id,
+            target_instance_id: target_id,
+        }
+    }
+    fn rename_labelled(graph: &SharedGraph<TestSchema>, node_id: &Uid, name: &str, label: &str) {
+        let mut builder = Node::initiate_edit(*node_id, graph.clone());
+        builder.edit_field(NAME_FIELD_ID, PrimitiveValues::String(name.to_string()));
+        builder.execute_with_label(label).unwrap();
+    }
+    // Deletes the node without recording the deletion, as if by a change which the history doesn't
+    // know about
+    fn delete_unrecorded(graph: &SharedGraph<TestSchema>, node_id: &Uid) {
+        let mut builder = Node::initiate_edit(*node_id, graph.clone());
+        builder.delete(node_id);
+        let (blueprint, _) = builder.get_blueprint().unwrap();
+        graph.process_blueprint(blueprint).unwrap();
+    }
+    // Accepts the given number of writes and fails every one after that
+    struct FailingStore {
+        writes_left: usize,
+    }
+    impl GraphStore for FailingStore {
+        fn load(&mut self) -> Result<Vec<StandaloneRGSOConcrete>, StoreError> {
+            Ok(Vec::new())
+        }
+        fn save(&mut self, _instances: &[StandaloneRGSOConcrete]) -> Result<(), StoreError> {
+            Ok(())
+        }
+        fn apply(&mut self, _blueprint: &StandaloneBlueprint) -> Result<(), StoreError> {
+            match self.writes_left.checked_sub(1) {
+                Some(writes_left) => {
+                    self.writes_left = writes_left;
+                    Ok(())
+                }
+                None => Err(StoreError::Backend("disk full".to_string())),
+            }
+        }
+    }
+    fn found(graph: &SharedGraph<TestSchema>, field_id: &Uid, value: &str) -> Vec<Uid> {
+        let mut ids = graph
+            .find_by_field(field_id, &PrimitiveValues::String(value.to_string()))
+            .iter()
+            .map(|instance| *instance.get_id())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+    fn entry(label: Option<&str>, blueprint_count: usize) -> HistoryEntry {
+        HistoryEntry {
+            label: label.map(str::to_string),
+            blueprint_count,
+        }
+    }
+
+    #[test]
+    fn move_to_swaps_the_partners_of_two_pairs() {
+        let graph = initialize_graph();
+        let a = create_node(&graph, "a").unwrap();
+        let b = create_node(&graph, "b").unwrap();
+        let first = create_pair(&graph, &a).unwrap();
+        let second = create_pair(&graph, &b).unwrap();
+
+        // Each pair only ever holds a single partner once both moves are done
+        let mut builder = Pair::initiate_edit(first, graph.clone());
+        builder.move_to(edge(first, PARTNER_SLOT_ID, a), &second, &PARTNER_SLOT_ID);
+        builder.move_to(edge(second, PARTNER_SLOT_ID, b), &first, &PARTNER_SLOT_ID);
+        builder.execute().unwrap();
+
+        assert_eq!(targets(&graph, &first, &PARTNER_SLOT_ID), vec![b]);
+        assert_eq!(targets(&graph, &second, &PARTNER_SLOT_ID), vec![a]);
+        assert!(graph.check_integrity().is_empty());
+    }
+
+    #[test]
+    fn move_to_continues_from_an_earlier_move() {
+        let graph = initialize_graph();
+        let [first, second, third, target] =
+            ["first", "second", "third", "target"].map(|name| create_node(&graph, name).unwrap());
+        link(&graph, &first, &target).unwrap();
+
+        let mut builder = Node::initiate_edit(first, graph.clone());
+        builder.move_to(edge(first, LINKS_SLOT_ID, target), &second, &LINKS_SLOT_ID);
+        builder.move_to(edge(second, LINKS_SLOT_ID, target), &third, &LINKS_SLOT_ID);
+        builder.execute().unwrap();
+
+        assert!(targets(&graph, &first, &LINKS_SLOT_ID).is_empty());
+        assert!(targets(&graph, &second, &LINKS_SLOT_ID).is_empty());
+        assert_eq!(targets(&graph, &third, &LINKS_SLOT_ID), vec![target]);
+        assert_eq!(
+            graph.get(&target).unwrap().incoming_slots().get_untracked(),
+            vec![edge(third, LINKS_SLOT_ID, target)]
+        );
+        assert!(graph.check_integrity().is_empty());
+    }
+
+    #[test]
+    fn move_to_fails_for_an_edge_which_was_moved_away() {
+        let graph = initialize_graph();
+        let [first, second, third, target] =
+            ["first", "second", "third", "target"].map(|name| create_node(&graph, name).unwrap());
+        link(&graph, &first, &target).unwrap();
+
+        let mut builder = Node::initiate_edit(first, graph.clone());
+        builder.move_to(edge(first, LINKS_SLOT_ID, target), &second, &LINKS_SLOT_ID);
+        builder.move_to(edge(first, LINKS_SLOT_ID, target), &third, &LINKS_SLOT_ID);
+
+        assert!(builder.execute().is_err());
+        assert_eq!(targets(&graph, &first, &LINKS_SLOT_ID), vec![target]);
+    }
+
+    #[test]
+    fn grouped_commits_are_undone_as_one_step() {
+        let graph = initialize_graph();
+        let a = create_node(&graph, "a").unwrap();
+        graph.begin_group(Some("Rename twice".to_string()));
+        rename(&graph, &a, "b").unwrap();
+        rename(&graph, &a, "c").unwrap();
+        graph.end_group();
+        assert_eq!(
+            graph.undo_history(),
+            vec![entry(Some("Rename twice"), 2), entry(None, 1)]
+        );
+
+        graph.undo().unwrap();
+        assert_eq!(name(&graph, &a).as_deref(), Some("a"));
+        assert_eq!(graph.peek_redo(), Some(entry(Some("Rename twice"), 2)));
+        graph.redo().unwrap();
+        assert_eq!(name(&graph, &a).as_deref(), Some("c"));
+        assert_eq!(graph.peek_undo(), Some(entry(Some("Rename twice"), 2)));
+    }
+
+    #[test]
+    fn nested_groups_end_with_the_outermost_one() {
+        let graph = initialize_graph();
+        let a = create_node(&graph, "a").unwrap();
+        graph.begin_group(Some("outer".to_string()));
+        rename(&graph, &a, "b").unwrap();
+        graph.begin_group(Some("inner".to_string()));
+        rename(&graph, &a, "c").unwrap();
+        graph.end_group();
+        rename(&graph, &a, "d").unwrap();
+        graph.end_group();
+        rename(&graph, &a, "e").unwrap();
+        assert_eq!(
+            graph.undo_history(),
+            vec![entry(None, 1), entry(Some("outer"), 3), entry(None, 1)]
+        );
+
+        graph.undo().unwrap();
+        graph.undo().unwrap();
+        assert_eq!(name(&graph, &a).as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn undoing_inside_an_open_group_starts_a_new_step() {
+        let graph = initialize_graph();
+        let a = create_node(&graph, "a").unwrap();
+        graph.begin_group(Some("open".to_string()));
+        rename(&graph, &a, "b").unwrap();
+        rename(&graph, &a, "c").unwrap();
+        assert_eq!(graph.peek_undo(), Some(entry(Some("open"), 2)));
+
+        graph.undo().unwrap();
+        assert_eq!(name(&graph, &a).as_deref(), Some("a"));
+        rename(&graph, &a, "d").unwrap();
+        assert!(graph.redo_history().is_empty());
+        assert_eq!(
+            graph.undo_history(),
+            vec![entry(Some("open"), 1), entry(None, 1)]
+        );
+        graph.end_group();
+        rename(&graph, &a, "e").unwrap();
+        assert_eq!(graph.peek_undo(), Some(entry(None, 1)));
+        assert_eq!(graph.undo_history().len(), 3);
+    }
+
+    #[test]
+    fn max_history_depth_trims_both_stacks() {
+        let graph = initialize_graph();
+        let a = create_node(&graph, "a").unwrap();
+        for name in ["b", "c", "d"] {
+            rename_labelled(&graph, &a, name, &format!("to {}", name));
+        }
+        graph.set_max_history_depth(Some(2));
+        assert_eq!(
+            graph.undo_history(),
+            vec![entry(Some("to d"), 1), entry(Some("to c"), 1)]
+        );
+
+        graph.undo().unwrap();
+        graph.undo().unwrap();
+        // The oldest steps are gone, so there is nothing left to undo
+        graph.undo().unwrap();
+        assert_eq!(name(&graph, &a).as_deref(), Some("b"));
+        assert!(graph.undo_history().is_empty());
+        assert_eq!(
+            graph.redo_history(),
+            vec![entry(Some("to c"), 1), entry(Some("to d"), 1)]
+        );
+
+        graph.set_max_history_depth(Some(1));
+        assert_eq!(graph.redo_history(), vec![entry(Some("to c"), 1)]);
+        rename(&graph, &a, "e").unwrap();
+        rename(&graph, &a, "f").unwrap();
+        assert!(graph.redo_history().is_empty());
+        assert_eq!(graph.undo_history(), vec![entry(None, 1)]);
+    }
+
+    #[test]
+    fn labels_survive_undo_and_redo() {
+        let graph = initialize_graph();
+        let a = create_node(&graph, "a").unwrap();
+        rename_labelled(&graph, &a, "b", "Rename a");
+        assert_eq!(graph.peek_undo(), Some(entry(Some("Rename a"), 1)));
+        assert_eq!(graph.peek_redo(), None);
+
+        graph.undo().unwrap();
+        graph.undo().unwrap();
+        assert_eq!(graph.peek_undo(), None);
+        assert_eq!(
+            graph.redo_history(),
+            vec![entry(None, 1), entry(Some("Rename a"), 1)]
+        );
+        graph.redo().unwrap();
+        graph.redo().unwrap();
+        assert_eq!(name(&graph, &a).as_deref(), Some("b"));
+        assert_eq!(
+            graph.undo_history(),
+            vec![entry(Some("Rename a"), 1), entry(None, 1)]
+        );
+        assert!(graph.redo_history().is_empty());
+    }
+
+    #[test]
+    fn undo_fails_without_changes_if_an_instance_was_deleted_underneath() {
+        let graph = initialize_graph();
+        let a = create_node(&graph, "a").unwrap();
+        let c = create_node(&graph, "c").unwrap();
+        graph.begin_group(Some("Rename both".to_string()));
+        rename(&graph, &c, "d").unwrap();
+        rename(&graph, &a, "b").unwrap();
+        graph.end_group();
+        delete_unrecorded(&graph, &c);
+
+        // Renaming a back succeeds, renaming c back doesn't, so a is renamed again
+        let error = graph.undo().unwrap_err();
+        assert!(!matches!(error, ElementCreationError::RollbackFailed { .. }), "{}", error);
+        assert_eq!(name(&graph, &a).as_deref(), Some("b"));
+        assert_eq!(graph.peek_undo(), Some(entry(Some("Rename both"), 2)));
+        assert!(graph.redo_history().is_empty());
+    }
+
+    #[test]
+    fn undo_reports_a_rollback_which_fails_as_well() {
+        let graph = initialize_graph();
+        let a = create_node(&graph, "a").unwrap();
+        graph.begin_group(None);
+        rename(&graph, &a, "b").unwrap();
+        rename(&graph, &a, "c").unwrap();
+        graph.end_group();
+        // Only the first blueprint of the undo can be written
+        graph.attach_store(FailingStore { writes_left: 1 });
+
+        let error = graph.undo().unwrap_err();
+        let ElementCreationError::RollbackFailed {
+            error: cause,
+            rollback_errors,
+        } = &error
+        else {
+            panic!("expected the rollback to fail: {}", error);
+        };
+        assert!(matches!(**cause, ElementCreationError::Persistence { .. }));
+        assert!(matches!(
+            rollback_errors.as_slice(),
+            [ElementCreationError::Persistence { .. }]
+        ));
+        assert!(error.to_string().contains("Rolling back the step failed as well"));
+        assert_eq!(name(&graph, &a).as_deref(), Some("b"));
+        assert_eq!(graph.undo_history().len(), 2);
+    }
+
+    #[test]
+    fn field_index_follows_edits_undo_and_deletes() {
+        let graph = initialize_graph();
+        let a = create_node(&graph, "a").unwrap();
+        let other = create_node(&graph, "a").unwrap();
+        let mut both = vec![a, other];
+        both.sort();
+        assert_eq!(found(&graph, &NAME_FIELD_ID, "a"), both);
+
+        rename(&graph, &a, "b").unwrap();
+        assert_eq!(found(&graph, &NAME_FIELD_ID, "a"), vec![other]);
+        assert_eq!(found(&graph, &NAME_FIELD_ID, "b"), vec![a]);
+        graph.undo().unwrap();
+        assert_eq!(found(&graph, &NAME_FIELD_ID, "a"), both);
+        assert!(found(&graph, &NAME_FIELD_ID, "b").is_empty());
+        graph.redo().unwrap();
+        assert_eq!(found(&graph, &NAME_FIELD_ID, "b"), vec![a]);
+
+        let mut builder = Node::initiate_edit(a, graph.clone());
+        builder.delete(&a);
+        builder.execute().unwrap();
+        assert!(found(&graph, &NAME_FIELD_ID, "b").is_empty());
+        graph.undo().unwrap();
+        assert_eq!(found(&graph, &NAME_FIELD_ID, "b"), vec![a]);
+    }
+
+    #[test]
+    fn unique_values_are_rejected_while_another_instance_holds_them() {
+        let graph = initialize_graph();
+        let first = create_label(&graph, "x", 1).unwrap();
+        let error = create_label(&graph, "x", 2).unwrap_err();
+        assert!(matches!(
+            error.flatten().as_slice(),
+            [ElementCreationError::UniqueFieldViolation(error)]
+                if error.field_id == TEXT_FIELD_ID && error.conflicting_id == first
+        ));
+        assert_eq!(found(&graph, &TEXT_FIELD_ID, "x"), vec![first]);
+
+        let mut builder = Label::initiate_edit(first, graph.clone());
+        builder.edit_field(TEXT_FIELD_ID, PrimitiveValues::String("y".to_string()));
+        builder.execute().unwrap();
+        let second = create_label(&graph, "x", 2).unwrap();
+        assert_eq!(found(&graph, &TEXT_FIELD_ID, "x"), vec![second]);
+        assert_eq!(found(&graph, &TEXT_FIELD_ID, "y"), vec![first]);
+
+        graph.undo().unwrap();
+        assert!(found(&graph, &TEXT_FIELD_ID, "x").is_empty());
+        graph.undo().unwrap();
+        assert_eq!(found(&graph, &TEXT_FIELD_ID, "x"), vec![first]);
+        assert!(create_label(&graph, "x", 3).is_err());
+    }
+
+    #[test]
+    fn slotting_an_instance_of_the_wrong_type_fails_at_execute() {
+        let graph = initialize_graph();
+        let label = create_label(&graph, "x", 1).unwrap();
+        let mut builder = Pair::initiate_build(graph.clone());
+        let pair = *builder.get_id();
+        // The builder accepts any id, the target is only checked when the subgraph is committed
+        builder.add_outgoing::<Node>(&PARTNER_SLOT_ID, BlueprintId::Existing(label), None);
+        assert!(builder.cumulative_errors.get_untracked().is_empty());
+
+        let error = builder.execute().unwrap_err();
+        assert!(matches!(
+            error.flatten().as_slice(),
+            [ElementCreationError::OutgoingElementIsWrongType {
+                host_id, slot_id, target_id, expected, received,
+            }] if *host_id == pair
+                && *slot_id == PARTNER_SLOT_ID
+                && *target_id == label
+                && expected == &["Node".to_string()]
+                && received == "Label"
+        ));
+        assert!(graph.get(&pair).is_none());
+        assert!(graph.check_integrity().is_empty());
+    }
+
+    #[test]
+    fn slotting_a_missing_instance_fails_at_execute() {
+        let graph = initialize_graph();
+        let a = create_node(&graph, "a").unwrap();
+        let pair = create_pair(&graph, &a).unwrap();
+        let mut builder = Pair::initiate_edit(pair, graph.clone());
+        builder.remove_outgoing(edge(pair, PARTNER_SLOT_ID, a));
+        builder.add_outgoing::<Node>(&PARTNER_SLOT_ID, BlueprintId::Existing(9999), None);
+        assert!(builder.cumulative_errors.get_untracked().is_empty());
+
+        let error = builder.execute().unwrap_err();
+        assert!(matches!(
+            error.flatten().as_slice(),
+            [ElementCreationError::OutgoingElementDoesntExist { host_id, slot_id, target_id }]
+                if *host_id == pair && *slot_id == PARTNER_SLOT_ID && *target_id == 9999
+        ));
+        assert_eq!(targets(&graph, &pair, &PARTNER_SLOT_ID), vec![a]);
+        assert!(graph.check_integrity().is_empty());
+    }
+
+    #[test]
+    fn slotting_an_instance_deleted_in_the_same_step_fails_at_execute() {
+        let graph = initialize_graph();
+        let a = create_node(&graph, "a").unwrap();
+        let b = create_node(&graph, "b").unwrap();
+        let mut builder = Node::initiate_edit(a, graph.clone());
+        builder.add_outgoing::<Node>(&LINKS_SLOT_ID, BlueprintId::Existing(b), None);
+        builder.delete(&b);
+
+        let error = builder.execute().unwrap_err();
+        assert!(error.flatten().iter().any(|error| matches!(
+            error,
+            ElementCreationError::OutgoingElementDoesntExist { host_id, target_id, .. }
+                if *host_id == a && *target_id == b
+        )));
+        assert!(graph.get(&b).is_some());
+        assert!(targets(&graph, &a, &LINKS_SLOT_ID).is_empty());
+    }
+}
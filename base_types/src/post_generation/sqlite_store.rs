@@ -0,0 +1,413 @@
+//! A [`GraphStore`] keeping the graph in an SQLite database.
+//!
+//! Instances, their field values and their edges are stored in separate tables, so that a commit
+//! only touches the rows it changes. Ids are stored as decimal text, since they don't fit into
+//! SQLite's integers.
+use std::{collections::BTreeMap, path::Path};
+
+use rusqlite::{params, Connection, Transaction};
+
+use crate::{
+    common::Uid,
+    post_generation::{
+        store::{GraphStore, StoreError},
+        SlotRef, StandaloneBlueprint, StandaloneRGSOConcrete,
+    },
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS instances (
+        id TEXT PRIMARY KEY,
+        operative TEXT NOT NULL,
+        template TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS fields (
+        instance_id TEXT NOT NULL REFERENCES instances(id) ON DELETE CASCADE,
+        field_id TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (instance_id, field_id)
+    );
+    CREATE TABLE IF NOT EXISTS edges (
+        host_id TEXT NOT NULL REFERENCES instances(id) ON DELETE CASCADE,
+        slot_id TEXT NOT NULL,
+        target_id TEXT NOT NULL REFERENCES instances(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (host_id, slot_id, target_id)
+    );
+    CREATE INDEX IF NOT EXISTS edges_by_target ON edges (target_id);
+";
+
+pub struct SqliteGraphStore {
+    connection: Connection,
+}
+
+impl SqliteGraphStore {
+    /// Opens the database at the given path, creating it and its tables if needed
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::from_connection(Connection::open(path).map_err(backend_error)?)
+    }
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::from_connection(Connection::open_in_memory().map_err(backend_error)?)
+    }
+    fn from_connection(connection: Connection) -> Result<Self, StoreError> {
+        connection
+            .execute_batch("PRAGMA foreign_keys = ON;")
+            .and_then(|_| connection.execute_batch(SCHEMA))
+            .map_err(backend_error)?;
+        Ok(Self { connection })
+    }
+}
+
+impl GraphStore for SqliteGraphStore {
+    fn load(&mut self) -> Result<Vec<StandaloneRGSOConcrete>, StoreError> {
+        let mut instances = BTreeMap::new();
+        let mut statement = self
+            .connection
+            .prepare("SELECT id, operative, template FROM instances")
+            .map_err(backend_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(backend_error)?;
+        for row in rows {
+            let (id, operative, template) = row.map_err(backend_error)?;
+            let id = parse_id(&id)?;
+            instances.insert(
+                id,
+                StandaloneRGSOConcrete {
+                    id,
+                    fields: Default::default(),
+                    outgoing_slots: Vec::new(),
+                    incoming_slots: Vec::new(),
+                    operative: parse_id(&operative)?,
+                    template: parse_id(&template)?,
+                },
+            );
+        }
+
+        let mut statement = self
+            .connection
+            .prepare("SELECT instance_id, field_id, value FROM fields")
+            .map_err(backend_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(backend_error)?;
+        for row in rows {
+            let (instance_id, field_id, value) = row.map_err(backend_error)?;
+            let value = serde_json::from_str(&value)
+                .map_err(|error| StoreError::Malformed(error.to_string()))?;
+            let instance = instances
+                .get_mut(&parse_id(&instance_id)?)
+                .ok_or_else(|| missing_instance(&instance_id))?;
+            instance.fields.insert(parse_id(&field_id)?, value);
+        }
+
+        // Ordered by position so that each slot keeps the order its instances were added in
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT host_id, slot_id, target_id FROM edges ORDER BY host_id, slot_id, position",
+            )
+            .map_err(backend_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(backend_error)?;
+        for row in rows {
+            let (host_id, slot_id, target_id) = row.map_err(backend_error)?;
+            let slot_ref = SlotRef {
+                host_instance_id: parse_id(&host_id)?,
+                slot_id: parse_id(&slot_id)?,
+                target_instance_id: parse_id(&target_id)?,
+            };
+            instances
+                .get_mut(&slot_ref.host_instance_id)
+                .ok_or_else(|| missing_instance(&host_id))?
+                .outgoing_slots
+                .push(slot_ref.clone());
+            instances
+                .get_mut(&slot_ref.target_instance_id)
+                .ok_or_else(|| missing_instance(&target_id))?
+                .incoming_slots
+                .push(slot_ref);
+        }
+        Ok(instances.into_values().collect())
+    }
+
+    fn save(&mut self, instances: &[StandaloneRGSOConcrete]) -> Result<(), StoreError> {
+        let transaction = self.connection.transaction().map_err(backend_error)?;
+        transaction
+            .execute_batch("DELETE FROM edges; DELETE FROM fields; DELETE FROM instances;")
+            .map_err(backend_error)?;
+        instances
+            .iter()
+            .try_for_each(|instance| insert_instance(&transaction, instance))?;
+        instances
+            .iter()
+            .flat_map(|instance| instance.outgoing_slots.iter())
+            .try_for_each(|slot_ref| insert_edge(&transaction, slot_ref))?;
+        transaction.commit().map_err(backend_error)
+    }
+
+    fn apply(&mut self, blueprint: &StandaloneBlueprint) -> Result<(), StoreError> {
+        let transaction = self.connection.transaction().map_err(backend_error)?;
+        // In the order the graph applies blueprints in, see `process_blueprint`
+        blueprint
+            .added_instances
+            .iter()
+            .try_for_each(|instance| insert_instance(&transaction, instance))?;
+        // Their fields and any remaining edges are removed along with them
+        blueprint
+            .deleted_instances
+            .iter()
+            .try_for_each(|instance| {
+                transaction
+                    .execute(
+                        "DELETE FROM instances WHERE id = ?1",
+                        params![instance.id.to_string()],
+                    )
+                    .map(|_| ())
+                    .map_err(backend_error)
+            })?;
+        blueprint
+            .added_instances
+            .iter()
+            .flat_map(|instance| instance.outgoing_slots.iter())
+            .chain(
+                blueprint
+                    .add_outgoing_updates
+                    .iter()
+                    .map(|(_, slot_ref)| slot_ref),
+            )
+            .try_for_each(|slot_ref| insert_edge(&transaction, slot_ref))?;
+        blueprint
+            .remove_outgoing_updates
+            .iter()
+            .try_for_each(|(_, slot_ref)| {
+                transaction
+                    .execute(
+                        "DELETE FROM edges WHERE host_id = ?1 AND slot_id = ?2 AND target_id = ?3",
+                        params![
+                            slot_ref.host_instance_id.to_string(),
+                            slot_ref.slot_id.to_string(),
+                            slot_ref.target_instance_id.to_string()
+                        ],
+                    )
+                    .map(|_| ())
+                    .map_err(backend_error)
+            })?;
+        blueprint
+            .field_updates
+            .iter()
+            .try_for_each(|(id, field_edit)| {
+                insert_field(
+                    &transaction,
+                    id,
+                    &field_edit.field_id,
+                    &field_edit.new_value,
+                )
+            })?;
+        transaction.commit().map_err(backend_error)
+    }
+}
+
+fn insert_instance(
+    transaction: &Transaction,
+    instance: &StandaloneRGSOConcrete,
+) -> Result<(), StoreError> {
+    transaction
+        .execute(
+            "INSERT INTO instances (id, operative, template) VALUES (?1, ?2, ?3)",
+            params![
+                instance.id.to_string(),
+                instance.operative.to_string(),
+                instance.template.to_string()
+            ],
+        )
+        .map_err(backend_error)?;
+    instance
+        .fields
+        .iter()
+        .try_for_each(|(field_id, value)| insert_field(transaction, &instance.id, field_id, value))
+}
+
+fn insert_field(
+    transaction: &Transaction,
+    instance_id: &Uid,
+    field_id: &Uid,
+    value: &crate::primitives::PrimitiveValues,
+) -> Result<(), StoreError> {
+    let value =
+        serde_json::to_string(value).map_err(|error| StoreError::Malformed(error.to_string()))?;
+    transaction
+        .execute(
+            "INSERT OR REPLACE INTO fields (instance_id, field_id, value) VALUES (?1, ?2, ?3)",
+            params![instance_id.to_string(), field_id.to_string(), value],
+        )
+        .map(|_| ())
+        .map_err(backend_error)
+}
+
+// Appends the edge to the end of its slot
+fn insert_edge(transaction: &Transaction, slot_ref: &SlotRef) -> Result<(), StoreError> {
+    transaction
+        .execute(
+            "INSERT OR IGNORE INTO edges (host_id, slot_id, target_id, position)
+             SELECT ?1, ?2, ?3, COALESCE(MAX(position) + 1, 0) FROM edges
+             WHERE host_id = ?1 AND slot_id = ?2",
+            params![
+                slot_ref.host_instance_id.to_string(),
+                slot_ref.slot_id.to_string(),
+                slot_ref.target_instance_id.to_string()
+            ],
+        )
+        .map(|_| ())
+        .map_err(backend_error)
+}
+
+fn parse_id(id: &str) -> Result<Uid, StoreError> {
+    id.parse()
+        .map_err(|_| StoreError::Malformed(format!("'{}' is not an id", id)))
+}
+
+fn missing_instance(id: &str) -> StoreError {
+    StoreError::Malformed(format!("no instance with id {}", id))
+}
+
+fn backend_error(error: rusqlite::Error) -> StoreError {
+    StoreError::Backend(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        post_generation::{store::MemoryGraphStore, HistoryFieldEdit},
+        primitives::PrimitiveValues,
+    };
+
+    const OPERATIVE: Uid = 10;
+    const TEMPLATE: Uid = 11;
+    const NAME: Uid = 20;
+    const LINKS: Uid = 30;
+
+    fn instance(id: Uid, name: &str, outgoing: &[Uid]) -> StandaloneRGSOConcrete {
+        StandaloneRGSOConcrete {
+            id,
+            fields: [(NAME, PrimitiveValues::String(name.to_string()))].into(),
+            outgoing_slots: outgoing.iter().map(|target| edge(id, *target)).collect(),
+            incoming_slots: Vec::new(),
+            operative: OPERATIVE,
+            template: TEMPLATE,
+        }
+    }
+    fn edge(host: Uid, target: Uid) -> SlotRef {
+        SlotRef {
+            host_instance_id: host,
+            slot_id: LINKS,
+            target_instance_id: target,
+        }
+    }
+    fn rename(id: Uid, from: &str, to: &str) -> (Uid, HistoryFieldEdit) {
+        let field_edit = HistoryFieldEdit {
+            instance_id: id,
+            field_id: NAME,
+            new_value: PrimitiveValues::String(to.to_string()),
+            prev_value: PrimitiveValues::String(from.to_string()),
+        };
+        (id, field_edit)
+    }
+    type Normalized = (
+        Uid,
+        Vec<(Uid, PrimitiveValues)>,
+        Vec<(Uid, Uid)>,
+        Vec<(Uid, Uid)>,
+    );
+    fn normalized(store: &mut impl GraphStore) -> Vec<Normalized> {
+        let ends = |slot_refs: &[SlotRef]| {
+            let mut ends = slot_refs
+                .iter()
+                .map(|slot_ref| (slot_ref.host_instance_id, slot_ref.target_instance_id))
+                .collect::<Vec<_>>();
+            ends.sort();
+            ends
+        };
+        let mut instances = store
+            .load()
+            .unwrap()
+            .into_iter()
+            .map(|instance| {
+                let mut fields = instance.fields.into_iter().collect::<Vec<_>>();
+                fields.sort_by_key(|(field_id, _)| *field_id);
+                (
+                    instance.id,
+                    fields,
+                    ends(&instance.outgoing_slots),
+                    ends(&instance.incoming_slots),
+                )
+            })
+            .collect::<Vec<_>>();
+        instances.sort_by_key(|(id, ..)| *id);
+        instances
+    }
+
+    #[test]
+    fn memory_and_sqlite_stores_apply_blueprints_alike() {
+        let blueprints = [
+            StandaloneBlueprint {
+                added_instances: vec![instance(1, "a", &[2]), instance(2, "b", &[])],
+                ..Default::default()
+            },
+            // Removes and re-adds the same edge, which the graph applies as a removal
+            StandaloneBlueprint {
+                add_outgoing_updates: vec![(1, edge(1, 2)), (2, edge(2, 1))],
+                remove_outgoing_updates: vec![(1, edge(1, 2))],
+                field_updates: vec![rename(1, "a", "renamed")],
+                ..Default::default()
+            },
+            StandaloneBlueprint {
+                added_instances: vec![instance(3, "c", &[1])],
+                deleted_instances: vec![instance(2, "b", &[1])],
+                ..Default::default()
+            },
+        ];
+        let mut memory = MemoryGraphStore::new();
+        let mut sqlite = SqliteGraphStore::open_in_memory().unwrap();
+        for blueprint in &blueprints {
+            memory.apply(blueprint).unwrap();
+            sqlite.apply(blueprint).unwrap();
+            assert_eq!(normalized(&mut memory), normalized(&mut sqlite));
+        }
+        let expected = vec![
+            (
+                1,
+                vec![(NAME, PrimitiveValues::String("renamed".to_string()))],
+                vec![],
+                vec![(3, 1)],
+            ),
+            (
+                3,
+                vec![(NAME, PrimitiveValues::String("c".to_string()))],
+                vec![(3, 1)],
+                vec![],
+            ),
+        ];
+        assert_eq!(normalized(&mut sqlite), expected);
+    }
+}
@@ -0,0 +1,189 @@
+//! Storage backends which a graph can be loaded from and which every commit can be written
+//! through to, instead of serializing the whole graph at once.
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    common::Uid,
+    post_generation::{ElementCreationError, StandaloneBlueprint, StandaloneRGSOConcrete},
+};
+
+#[derive(Debug)]
+pub enum StoreError {
+    /// The backend failed, e.g. because the database couldn't be reached
+    Backend(String),
+    /// The stored data couldn't be read back
+    Malformed(String),
+    /// The stored instances don't conform to the schema
+    Invalid(ElementCreationError),
+}
+
+impl Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::Backend(message) => write!(f, "Store failed: {}", message),
+            StoreError::Malformed(message) => write!(f, "Stored graph is malformed: {}", message),
+            StoreError::Invalid(error) => write!(f, "Stored graph is invalid:\n{}", error),
+        }
+    }
+}
+
+pub trait GraphStore {
+    /// Every stored instance, along with its edges
+    fn load(&mut self) -> Result<Vec<StandaloneRGSOConcrete>, StoreError>;
+    /// Replaces everything in the store with the given instances
+    fn save(&mut self, instances: &[StandaloneRGSOConcrete]) -> Result<(), StoreError>;
+    /// Applies the changes of a single committed blueprint. Each edge is stored once, so only the
+    /// outgoing side of the edge updates is used
+    fn apply(&mut self, blueprint: &StandaloneBlueprint) -> Result<(), StoreError>;
+}
+
+// So that a detached store can be attached again
+impl<T: GraphStore + ?Sized> GraphStore for Box<T> {
+    fn load(&mut self) -> Result<Vec<StandaloneRGSOConcrete>, StoreError> {
+        (**self).load()
+    }
+    fn save(&mut self, instances: &[StandaloneRGSOConcrete]) -> Result<(), StoreError> {
+        (**self).save(instances)
+    }
+    fn apply(&mut self, blueprint: &StandaloneBlueprint) -> Result<(), StoreError> {
+        (**self).apply(blueprint)
+    }
+}
+
+/// Keeps the graph in memory, e.g. for tests or as a cache in front of another store
+#[derive(Debug, Clone, Default)]
+pub struct MemoryGraphStore {
+    instances: HashMap<Uid, StandaloneRGSOConcrete>,
+}
+
+impl MemoryGraphStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn get(&self, id: &Uid) -> Option<&StandaloneRGSOConcrete> {
+        self.instances.get(id)
+    }
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+}
+
+impl GraphStore for MemoryGraphStore {
+    fn load(&mut self) -> Result<Vec<StandaloneRGSOConcrete>, StoreError> {
+        let mut instances = self.instances.values().cloned().collect::<Vec<_>>();
+        instances.sort_by_key(|instance| instance.id);
+        Ok(instances)
+    }
+    fn save(&mut self, instances: &[StandaloneRGSOConcrete]) -> Result<(), StoreError> {
+        self.instances = instances
+            .iter()
+            .map(|instance| (instance.id, instance.clone()))
+            .collect();
+        Ok(())
+    }
+    fn apply(&mut self, blueprint: &StandaloneBlueprint) -> Result<(), StoreError> {
+        // In the order the graph applies blueprints in, see `process_blueprint`
+        blueprint.added_instances.iter().for_each(|added| {
+            let mut added = added.clone();
+            // Incoming edges are added along with their host's outgoing side below
+            added.incoming_slots.clear();
+            self.instances.insert(added.id, added);
+        });
+        blueprint.deleted_instances.iter().for_each(|deleted| {
+            self.instances.remove(&deleted.id);
+            self.instances.values_mut().for_each(|instance| {
+                instance
+                    .outgoing_slots
+                    .retain(|slot_ref| slot_ref.target_instance_id != deleted.id);
+                instance
+                    .incoming_slots
+                    .retain(|slot_ref| slot_ref.host_instance_id != deleted.id);
+            });
+        });
+        let added_edges = blueprint
+            .added_instances
+            .iter()
+            .flat_map(|instance| instance.outgoing_slots.iter())
+            .map(|slot_ref| (false, slot_ref))
+            .chain(
+                blueprint
+                    .add_outgoing_updates
+                    .iter()
+                    .map(|(_, slot_ref)| (true, slot_ref)),
+            );
+        added_edges.for_each(|(add_to_host, slot_ref)| {
+            if add_to_host {
+                if let Some(host) = self.instances.get_mut(&slot_ref.host_instance_id) {
+                    host.outgoing_slots.push(slot_ref.clone());
+                }
+            }
+            if let Some(target) = self.instances.get_mut(&slot_ref.target_instance_id) {
+                target.incoming_slots.push(slot_ref.clone());
+            }
+        });
+        blueprint
+            .remove_outgoing_updates
+            .iter()
+            .for_each(|(_, slot_ref)| {
+                if let Some(host) = self.instances.get_mut(&slot_ref.host_instance_id) {
+                    host.outgoing_slots.retain(|existing| existing != slot_ref);
+                }
+                if let Some(target) = self.instances.get_mut(&slot_ref.target_instance_id) {
+                    target
+                        .incoming_slots
+                        .retain(|existing| existing != slot_ref);
+                }
+            });
+        blueprint.field_updates.iter().for_each(|(id, field_edit)| {
+            if let Some(instance) = self.instances.get_mut(id) {
+                instance
+                    .fields
+                    .insert(field_edit.field_id, field_edit.new_value.clone());
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(feature = "reactive")]
+/// A store attached to a graph environment, see
+/// [`crate::post_generation::reactive::RBaseGraphEnvironment::attach_store`]
+pub struct StoreWriter<TSchema> {
+    pub(crate) store: Box<dyn GraphStore + Send>,
+    to_standalone: fn(TSchema) -> StandaloneRGSOConcrete,
+}
+#[cfg(feature = "reactive")]
+impl<TSchema> std::fmt::Debug for StoreWriter<TSchema> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StoreWriter").finish_non_exhaustive()
+    }
+}
+#[cfg(feature = "reactive")]
+impl<TSchema: Clone> StoreWriter<TSchema> {
+    pub(crate) fn new(store: Box<dyn GraphStore + Send>) -> Self
+    where
+        TSchema: Into<StandaloneRGSOConcrete>,
+    {
+        Self {
+            store,
+            to_standalone: TSchema::into,
+        }
+    }
+    pub(crate) fn write(
+        &mut self,
+        blueprints: &[crate::post_generation::reactive::Blueprint<TSchema>],
+    ) -> Result<(), StoreError> {
+        blueprints.iter().try_for_each(|blueprint| {
+            self.store.apply(&StandaloneBlueprint::from_blueprint(
+                blueprint,
+                self.to_standalone,
+            ))
+        })
+    }
+    pub(crate) fn into_store(self) -> Box<dyn GraphStore + Send> {
+        self.store
+    }
+}
@@ -0,0 +1,156 @@
+//! Schema rules shared by the reactive and the plain graph runtimes
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    common::Uid,
+    constraint_schema::{
+        ConstraintSchema, LibraryOperative, LibraryTemplate, OperativeSlot, OperativeVariants,
+    },
+    constraint_schema_item::ConstraintSchemaItem,
+    post_generation::{
+        field_index::FieldValueIndex, ElementCreationError, HistoryFieldEdit, UniqueFieldError,
+    },
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+// Whether an instance of the given operative can be slotted into the given slot, i.e. whether it
+// descends from the slot's library operative or implements all of the slot's traits
+pub(crate) fn slot_accepts_operative(
+    constraint_schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    slot: &OperativeSlot,
+    operative: &LibraryOperative<PrimitiveTypes, PrimitiveValues>,
+) -> bool {
+    match &slot.operative_descriptor {
+        OperativeVariants::LibraryOperative(operative_id) => {
+            operative.check_ancestry(constraint_schema, operative_id)
+        }
+        OperativeVariants::TraitOperative(trait_operative) => {
+            let trait_impls = operative
+                .get_trait_impl_digest(constraint_schema)
+                .trait_impls;
+            trait_operative
+                .trait_ids
+                .iter()
+                .all(|trait_id| trait_impls.contains_key(trait_id))
+        }
+    }
+}
+pub(crate) fn check_field_value_type(
+    instance_id: Uid,
+    template: &LibraryTemplate<PrimitiveTypes, PrimitiveValues>,
+    field_id: &Uid,
+    value: &PrimitiveValues,
+) -> Result<(), ElementCreationError> {
+    let field_constraint =
+        template
+            .field_constraints
+            .get(field_id)
+            .ok_or(ElementCreationError::NonexistentField {
+                instance_id,
+                field_id: *field_id,
+            })?;
+    if value.conforms_to(&field_constraint.value_type) {
+        return Ok(());
+    }
+    Err(ElementCreationError::FieldValueTypeMismatch {
+        instance_id,
+        field_id: *field_id,
+        field_name: field_constraint.tag.name.clone(),
+        expected: field_constraint.value_type.clone(),
+        received: value.clone(),
+    })
+}
+// Ensures that no two instances will share a value in any field marked as unique once a change
+// has been applied. `added_fields` are the (instance, field, value) of every instance the change
+// adds, and `temp_id` names an instance in the resulting errors
+pub(crate) fn check_unique_fields(
+    constraint_schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    field_index: &FieldValueIndex,
+    added_fields: impl Iterator<Item = (Uid, Uid, PrimitiveValues)>,
+    field_updates: &[&(Uid, HistoryFieldEdit)],
+    deleted_ids: &HashSet<Uid>,
+    temp_id: impl Fn(&Uid) -> Option<String>,
+) -> Vec<ElementCreationError> {
+    let unique_field_ids = field_index.unique_field_ids();
+    if unique_field_ids.is_empty() {
+        return Vec::new();
+    }
+    // The final value of every unique field which is being set
+    let mut claims: Vec<(Uid, Uid, PrimitiveValues)> = added_fields
+        .chain(
+            field_updates
+                .iter()
+                .map(|(id, field_edit)| (*id, field_edit.field_id, field_edit.new_value.clone())),
+        )
+        .filter(|(_, field_id, _)| unique_field_ids.contains(field_id))
+        .collect();
+    claims.sort_by_key(|(id, field_id, _)| (*field_id, *id));
+
+    let mut errors = Vec::new();
+    let mut already_claimed: Vec<(Uid, PrimitiveValues, Uid)> = Vec::new();
+    for (instance_id, field_id, value) in claims {
+        let existing_holder = field_index
+            .lookup(&field_id, &value)
+            .into_iter()
+            .filter(|holder_id| *holder_id != instance_id && !deleted_ids.contains(holder_id))
+            // Holders which are moving off of this value in the same change don't conflict
+            .find(|holder_id| {
+                !field_updates.iter().any(|(id, field_edit)| {
+                    id == holder_id
+                        && field_edit.field_id == field_id
+                        && field_edit.new_value != value
+                })
+            });
+        let claimed_in_change = already_claimed
+            .iter()
+            .find(|(claimed_field_id, claimed_value, claimant_id)| {
+                *claimed_field_id == field_id
+                    && *claimed_value == value
+                    && *claimant_id != instance_id
+            })
+            .map(|(_, _, claimant_id)| *claimant_id);
+        if let Some(conflicting_id) = existing_holder.or(claimed_in_change) {
+            errors.push(
+                UniqueFieldError {
+                    instance_id,
+                    temp_id: temp_id(&instance_id),
+                    field_id,
+                    field_name: constraint_schema
+                        .template_library
+                        .values()
+                        .find_map(|template| template.field_constraints.get(&field_id))
+                        .map(|field| field.tag.name.clone())
+                        .unwrap_or_default(),
+                    value: value.clone(),
+                    conflicting_id,
+                }
+                .into(),
+            );
+        }
+        already_claimed.push((field_id, value, instance_id));
+    }
+    errors
+}
+// Fails with a conflict for every instance whose version has changed since it was read
+pub(crate) fn check_versions(
+    current_version: impl Fn(&Uid) -> u64,
+    read_versions: &HashMap<Uid, u64>,
+) -> Result<(), ElementCreationError> {
+    let mut read_versions = read_versions.iter().collect::<Vec<_>>();
+    read_versions.sort();
+    let conflicts = read_versions
+        .into_iter()
+        .filter_map(|(id, read_version)| {
+            let current_version = current_version(id);
+            (current_version != *read_version).then_some(ElementCreationError::Conflict {
+                instance_id: *id,
+                read_version: *read_version,
+                current_version,
+            })
+        })
+        .collect::<Vec<_>>();
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    Err(ElementCreationError::Stack(conflicts))
+}
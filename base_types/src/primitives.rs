@@ -120,23 +120,210 @@ impl Display for PrimitiveValues {
 }
 impl ConstraintTraits for PrimitiveValues {}
 impl PrimitiveValues {
-    pub fn get_primitive_type(&self) -> PrimitiveTypes {
+    /// The type of this value, or `None` if it can't be determined from the value alone, e.g. for
+    /// `None` or an empty list, or a list whose elements don't share a type.
+    /// Use `conforms_to` to check a value against a known type.
+    pub fn get_primitive_type(&self) -> Option<PrimitiveTypes> {
         match self {
-            PrimitiveValues::Int(_) => PrimitiveTypes::Int,
+            PrimitiveValues::Int(_) => Some(PrimitiveTypes::Int),
             // PrimitiveValues::Float(_) => PrimitiveTypes::Float,
-            PrimitiveValues::String(_) => PrimitiveTypes::String,
-            PrimitiveValues::Bool(_) => PrimitiveTypes::Bool,
+            PrimitiveValues::String(_) => Some(PrimitiveTypes::String),
+            PrimitiveValues::Bool(_) => Some(PrimitiveTypes::Bool),
             // PrimitiveValues::Char(_) => PrimitiveTypes::Char,
-            PrimitiveValues::Option(val) => match val.deref() {
-                Some(val) => val.get_primitive_type(),
-                None => PrimitiveTypes::Option(Box::new(PrimitiveTypes::EmptyTuple)),
-            },
+            PrimitiveValues::Option(val) => val
+                .deref()
+                .as_ref()
+                .and_then(|val| val.get_primitive_type())
+                .map(|inner| PrimitiveTypes::Option(Box::new(inner))),
             PrimitiveValues::List(val) => {
-                PrimitiveTypes::List(Box::new(match val.deref().iter().next() {
-                    Some(val) => val.get_primitive_type(),
-                    None => PrimitiveTypes::EmptyTuple,
-                }))
+                let mut element_types = val.iter().map(|val| val.get_primitive_type());
+                let first = element_types.next()??;
+                element_types
+                    .all(|element_type| element_type.as_ref() == Some(&first))
+                    .then(|| PrimitiveTypes::List(Box::new(first)))
             }
         }
     }
+    /// Whether this value is a valid instance of the given type, including the elements of any
+    /// nested `Option` or `List`
+    pub fn conforms_to(&self, value_type: &PrimitiveTypes) -> bool {
+        match (self, value_type) {
+            (PrimitiveValues::Int(_), PrimitiveTypes::Int) => true,
+            (PrimitiveValues::String(_), PrimitiveTypes::String) => true,
+            (PrimitiveValues::Bool(_), PrimitiveTypes::Bool) => true,
+            (PrimitiveValues::Option(val), PrimitiveTypes::Option(inner_type)) => val
+                .deref()
+                .as_ref()
+                .is_none_or(|val| val.conforms_to(inner_type)),
+            (PrimitiveValues::List(val), PrimitiveTypes::List(inner_type)) => {
+                val.iter().all(|val| val.conforms_to(inner_type))
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::LazyLock};
+
+    use super::*;
+    use crate::{
+        constraint_schema::ConstraintSchema,
+        post_generation::{
+            integrity::{find_problems, IntegrityProblem},
+            plain::{PlainGraphEnvironment, Transaction},
+            ElementCreationError, StandaloneRGSOConcrete,
+        },
+        testing::{
+            LABEL_OPERATIVE_ID, LABEL_TEMPLATE_ID, NAME_FIELD_ID, NODE_OPERATIVE_ID,
+            NODE_TEMPLATE_ID, RANK_FIELD_ID, TEST_SCHEMA, TEXT_FIELD_ID,
+        },
+    };
+
+    // The test schema with an optional rank
+    static OPTIONAL_RANK: LazyLock<ConstraintSchema<PrimitiveTypes, PrimitiveValues>> =
+        LazyLock::new(|| {
+            let mut schema = TEST_SCHEMA.clone();
+            schema
+                .template_library
+                .get_mut(&LABEL_TEMPLATE_ID)
+                .and_then(|template| template.field_constraints.get_mut(&RANK_FIELD_ID))
+                .unwrap()
+                .value_type = option(PrimitiveTypes::Int);
+            schema
+        });
+
+    fn option(value_type: PrimitiveTypes) -> PrimitiveTypes {
+        PrimitiveTypes::Option(Box::new(value_type))
+    }
+    fn list(value_type: PrimitiveTypes) -> PrimitiveTypes {
+        PrimitiveTypes::List(Box::new(value_type))
+    }
+    fn some(value: PrimitiveValues) -> PrimitiveValues {
+        PrimitiveValues::Option(Box::new(Some(value)))
+    }
+    fn none() -> PrimitiveValues {
+        PrimitiveValues::Option(Box::new(None))
+    }
+    fn string(value: &str) -> PrimitiveValues {
+        PrimitiveValues::String(value.to_string())
+    }
+    // The reactive builder also reports the rejected value's field as empty
+    fn is_type_mismatch(error: &ElementCreationError, field: Uid) -> bool {
+        error.flatten().into_iter().any(|error| {
+            matches!(error, ElementCreationError::FieldValueTypeMismatch { field_id, .. }
+                if *field_id == field)
+        })
+    }
+
+    #[test]
+    fn types_are_read_from_values_where_possible() {
+        let int = PrimitiveValues::Int(1);
+        assert_eq!(int.get_primitive_type(), Some(PrimitiveTypes::Int));
+        assert_eq!(
+            some(some(int.clone())).get_primitive_type(),
+            Some(option(option(PrimitiveTypes::Int)))
+        );
+        assert_eq!(
+            PrimitiveValues::List(vec![some(string("a")), some(string("b"))]).get_primitive_type(),
+            Some(list(option(PrimitiveTypes::String)))
+        );
+        // Nothing tells which type a missing value or an empty list would have had
+        assert_eq!(none().get_primitive_type(), None);
+        assert_eq!(some(none()).get_primitive_type(), None);
+        assert_eq!(PrimitiveValues::List(Vec::new()).get_primitive_type(), None);
+        assert_eq!(
+            PrimitiveValues::List(vec![int, string("a")]).get_primitive_type(),
+            None
+        );
+    }
+
+    #[test]
+    fn values_conform_to_their_type_at_every_level() {
+        let int = PrimitiveValues::Int(1);
+        assert!(int.conforms_to(&PrimitiveTypes::Int));
+        assert!(!int.conforms_to(&PrimitiveTypes::String));
+        assert!(!int.conforms_to(&option(PrimitiveTypes::Int)));
+        assert!(!some(int.clone()).conforms_to(&PrimitiveTypes::Int));
+
+        assert!(none().conforms_to(&option(PrimitiveTypes::Int)));
+        assert!(none().conforms_to(&option(option(PrimitiveTypes::String))));
+        assert!(!none().conforms_to(&PrimitiveTypes::Int));
+        assert!(some(none()).conforms_to(&option(option(PrimitiveTypes::Int))));
+        assert!(some(some(int.clone())).conforms_to(&option(option(PrimitiveTypes::Int))));
+        assert!(!some(some(string("a"))).conforms_to(&option(option(PrimitiveTypes::Int))));
+        assert!(!some(int.clone()).conforms_to(&option(option(PrimitiveTypes::Int))));
+
+        let optional_ints = list(option(PrimitiveTypes::Int));
+        assert!(PrimitiveValues::List(Vec::new()).conforms_to(&optional_ints));
+        assert!(PrimitiveValues::List(vec![none(), some(int.clone())]).conforms_to(&optional_ints));
+        assert!(!PrimitiveValues::List(vec![none(), int]).conforms_to(&optional_ints));
+    }
+
+    #[test]
+    fn edits_with_values_of_the_wrong_type_are_rejected() {
+        let mut graph = PlainGraphEnvironment::new(&OPTIONAL_RANK);
+        let create_label = |text: &str, rank: PrimitiveValues| {
+            let mut transaction = Transaction::new();
+            let id = transaction.create(LABEL_OPERATIVE_ID);
+            transaction
+                .set_field(id, TEXT_FIELD_ID, string(text))
+                .set_field(id, RANK_FIELD_ID, rank);
+            transaction
+        };
+        graph.execute(create_label("none", none())).unwrap();
+        graph
+            .execute(create_label("some", some(PrimitiveValues::Int(1))))
+            .unwrap();
+
+        for rank in [PrimitiveValues::Int(1), some(string("1")), some(none())] {
+            let error = graph.execute(create_label("wrong", rank)).unwrap_err();
+            assert!(is_type_mismatch(&error, RANK_FIELD_ID), "{}", error);
+        }
+        let mut transaction = Transaction::new();
+        let id = transaction.create(NODE_OPERATIVE_ID);
+        transaction.set_field(id, NAME_FIELD_ID, PrimitiveValues::Int(1));
+        let error = graph.execute(transaction).unwrap_err();
+        assert!(is_type_mismatch(&error, NAME_FIELD_ID), "{}", error);
+        assert_eq!(graph.len(), 2);
+    }
+
+    #[test]
+    fn loaded_values_of_the_wrong_type_are_reported() {
+        let node = StandaloneRGSOConcrete {
+            id: 1,
+            fields: HashMap::from([(NAME_FIELD_ID, some(string("a")))]),
+            outgoing_slots: Vec::new(),
+            incoming_slots: Vec::new(),
+            operative: NODE_OPERATIVE_ID,
+            template: NODE_TEMPLATE_ID,
+        };
+        let problems = find_problems(&TEST_SCHEMA, &[node]);
+        assert!(matches!(
+            problems.as_slice(),
+            [IntegrityProblem::Invalid(
+                ElementCreationError::FieldValueTypeMismatch {
+                    instance_id: 1,
+                    field_id: NAME_FIELD_ID,
+                    ..
+                }
+            )]
+        ));
+    }
+
+    #[cfg(feature = "reactive")]
+    #[test]
+    fn reactive_edits_with_values_of_the_wrong_type_are_rejected() {
+        use crate::{
+            post_generation::reactive::{RBuildable, RGraphEnvironment},
+            testing::{initialize_graph, Node},
+        };
+        let graph = initialize_graph();
+        let mut builder = Node::initiate_build(graph.clone());
+        builder.edit_field(NAME_FIELD_ID, PrimitiveValues::Int(1));
+        let error = builder.execute().unwrap_err();
+        assert!(is_type_mismatch(&error, NAME_FIELD_ID), "{}", error);
+        assert!(graph.get(builder.get_id()).is_none());
+    }
 }
@@ -0,0 +1,147 @@
+//! A small schema for tests, written out by hand the way `generate_schema_reactive` would generate
+//! it, so that the graph runtimes can be exercised without a generated crate.
+//!
+//! `Node`s have a `name` and can link to any number of other nodes. A `Pair` holds exactly one
+//! node as its `partner`. A `Label` has a `text` which no other label may share and a numeric
+//! `rank`.
+//!
+//! The schema itself doesn't need the `reactive` feature, so the plain graph can be tested without
+//! it.
+use std::{collections::BTreeMap, marker::PhantomData, sync::LazyLock};
+
+use crate::{
+    common::{Tag, Uid},
+    constraint_schema::{
+        ConstraintSchema, FieldConstraint, FieldIndex, LibraryOperative, LibraryTemplate,
+        OperativeSlot, OperativeVariants, SlotBounds,
+    },
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+#[cfg(feature = "reactive")]
+mod reactive;
+#[cfg(feature = "reactive")]
+pub use reactive::*;
+
+pub const NODE_OPERATIVE_ID: Uid = 290342014698977480850629738624973773045;
+pub const NODE_TEMPLATE_ID: Uid = 100220459449770881025491311741883971840;
+pub const NAME_FIELD_ID: Uid = 269736896877234046624871150730603336486;
+pub const LINKS_SLOT_ID: Uid = 36987223518749924246381647901592337168;
+pub const PAIR_OPERATIVE_ID: Uid = 109759289735304386637112664129473317666;
+pub const PAIR_TEMPLATE_ID: Uid = 25194196666182583166994668005317281332;
+pub const PARTNER_SLOT_ID: Uid = 278140384678259408739408463974375218926;
+pub const LABEL_OPERATIVE_ID: Uid = 61953740268170452931409512237413581734;
+pub const LABEL_TEMPLATE_ID: Uid = 187004712436279154880375126335049907427;
+pub const TEXT_FIELD_ID: Uid = 133542688319066251087601432016347902115;
+pub const RANK_FIELD_ID: Uid = 62816500798977923836510207181313179967;
+
+pub static TEST_SCHEMA: LazyLock<ConstraintSchema<PrimitiveTypes, PrimitiveValues>> =
+    LazyLock::new(|| {
+        let tag = |name: &str, id: Uid| Tag {
+            name: name.to_string(),
+            id,
+        };
+        let slot_to_node = |name: &str, id: Uid, bounds: SlotBounds| OperativeSlot {
+            tag: tag(name, id),
+            operative_descriptor: OperativeVariants::LibraryOperative(NODE_OPERATIVE_ID),
+            bounds,
+        };
+        let template =
+            |name: &str,
+             id: Uid,
+             field_constraints: BTreeMap<Uid, FieldConstraint<PrimitiveTypes>>,
+             operative_slots: BTreeMap<Uid, OperativeSlot>| LibraryTemplate {
+                tag: tag(name, id),
+                field_constraints,
+                operative_slots,
+                trait_impls: BTreeMap::new(),
+                instances: Vec::new(),
+                _phantom: PhantomData,
+            };
+        let operative = |name: &str, id: Uid, template_id: Uid| LibraryOperative {
+            tag: tag(name, id),
+            template_id,
+            parent_operative_id: None,
+            slotted_instances: BTreeMap::new(),
+            locked_fields: BTreeMap::new(),
+            trait_impls: BTreeMap::new(),
+            _phantom: PhantomData,
+        };
+        ConstraintSchema {
+            template_library: BTreeMap::from([
+                (
+                    NODE_TEMPLATE_ID,
+                    template(
+                        "Node",
+                        NODE_TEMPLATE_ID,
+                        BTreeMap::from([(
+                            NAME_FIELD_ID,
+                            FieldConstraint {
+                                tag: tag("name", NAME_FIELD_ID),
+                                value_type: PrimitiveTypes::String,
+                                index: FieldIndex::Indexed,
+                            },
+                        )]),
+                        BTreeMap::from([(
+                            LINKS_SLOT_ID,
+                            slot_to_node("links", LINKS_SLOT_ID, SlotBounds::LowerBoundOrZero(1)),
+                        )]),
+                    ),
+                ),
+                (
+                    PAIR_TEMPLATE_ID,
+                    template(
+                        "Pair",
+                        PAIR_TEMPLATE_ID,
+                        BTreeMap::new(),
+                        BTreeMap::from([(
+                            PARTNER_SLOT_ID,
+                            slot_to_node("partner", PARTNER_SLOT_ID, SlotBounds::Single),
+                        )]),
+                    ),
+                ),
+                (
+                    LABEL_TEMPLATE_ID,
+                    template(
+                        "Label",
+                        LABEL_TEMPLATE_ID,
+                        BTreeMap::from([
+                            (
+                                TEXT_FIELD_ID,
+                                FieldConstraint {
+                                    tag: tag("text", TEXT_FIELD_ID),
+                                    value_type: PrimitiveTypes::String,
+                                    index: FieldIndex::Unique,
+                                },
+                            ),
+                            (
+                                RANK_FIELD_ID,
+                                FieldConstraint {
+                                    tag: tag("rank", RANK_FIELD_ID),
+                                    value_type: PrimitiveTypes::Int,
+                                    index: FieldIndex::Unindexed,
+                                },
+                            ),
+                        ]),
+                        BTreeMap::new(),
+                    ),
+                ),
+            ]),
+            instance_library: BTreeMap::new(),
+            operative_library: BTreeMap::from([
+                (
+                    NODE_OPERATIVE_ID,
+                    operative("Node", NODE_OPERATIVE_ID, NODE_TEMPLATE_ID),
+                ),
+                (
+                    PAIR_OPERATIVE_ID,
+                    operative("Pair", PAIR_OPERATIVE_ID, PAIR_TEMPLATE_ID),
+                ),
+                (
+                    LABEL_OPERATIVE_ID,
+                    operative("Label", LABEL_OPERATIVE_ID, LABEL_TEMPLATE_ID),
+                ),
+            ]),
+            traits: BTreeMap::new(),
+        }
+    });
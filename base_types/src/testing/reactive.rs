@@ -0,0 +1,550 @@
+//! The reactive types of the test schema, as `generate_schema_reactive` would generate them for
+//! `Node`, `Pair` and `Label`, along with helpers which commit to the reactive graph.
+use std::collections::{BTreeMap, HashMap};
+
+use leptos::prelude::*;
+
+use super::*;
+use crate::{
+    common::Uid,
+    constraint_schema::{LibraryOperative, LibraryTemplate},
+    post_generation::{
+        reactive::{
+            from_reactive::FromStandalone, hidden::EditRGSO, BlueprintId, HasSlotEnum, RActiveSlot,
+            RBaseGraphEnvironment, RBuildable, RGSOConcrete, RGSOConcreteBuilder,
+            RGraphEnvironment, RIntoSchema, SharedGraph, SpecializedRActiveSlot, SubgraphBuilder,
+            RGSO,
+        },
+        ElementCreationError, HistoryFieldEdit, SlotRef, StandaloneRGSOConcrete,
+    },
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct Node {}
+#[derive(Clone, Debug, strum_macros::EnumString)]
+pub enum NodeAllSlots {
+    #[strum(serialize = "1bd37aea-069b-5064-a313-54ea34473b10")]
+    Links,
+}
+impl HasSlotEnum for Node {
+    type SlotEnum = NodeAllSlots;
+}
+impl RIntoSchema for Node {
+    type Schema = TestSchema;
+    fn into_schema(instantiable: RGSOConcrete<Self, TestSchema>) -> TestSchema {
+        TestSchema::Node(instantiable)
+    }
+}
+impl RBuildable for Node {
+    type Schema = TestSchema;
+    fn initiate_build(
+        graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<TestSchema>>>,
+    ) -> SubgraphBuilder<Node, TestSchema> {
+        let template = TEST_SCHEMA.template_library.get(&NODE_TEMPLATE_ID).unwrap();
+        let graph = graph.into();
+        let builder = RGSOConcreteBuilder::new(
+            HashMap::from([(NAME_FIELD_ID, RwSignal::new(None))]),
+            Some(BTreeMap::from([(
+                LINKS_SLOT_ID,
+                SpecializedRActiveSlot {
+                    base: RActiveSlot {
+                        slot: template.operative_slots.get(&LINKS_SLOT_ID).unwrap(),
+                        slotted_instances: RwSignal::new(vec![]),
+                    },
+                    slot_enum: NodeAllSlots::Links,
+                },
+            )])),
+            TEST_SCHEMA
+                .operative_library
+                .get(&NODE_OPERATIVE_ID)
+                .unwrap(),
+            template,
+            graph.clone(),
+        );
+        let id = *builder.get_id();
+        SubgraphBuilder::new(Some(builder), id, graph)
+    }
+    fn initiate_edit(
+        id: Uid,
+        graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<TestSchema>>>,
+    ) -> SubgraphBuilder<Node, TestSchema> {
+        SubgraphBuilder::new(None, id, graph.into())
+    }
+    fn get_operative_id() -> Uid {
+        NODE_OPERATIVE_ID
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Pair {}
+#[derive(Clone, Debug, strum_macros::EnumString)]
+pub enum PairAllSlots {
+    #[strum(serialize = "d13fe3ba-2c49-5efc-a075-ddb967d9deee")]
+    Partner,
+}
+impl HasSlotEnum for Pair {
+    type SlotEnum = PairAllSlots;
+}
+impl RIntoSchema for Pair {
+    type Schema = TestSchema;
+    fn into_schema(instantiable: RGSOConcrete<Self, TestSchema>) -> TestSchema {
+        TestSchema::Pair(instantiable)
+    }
+}
+impl RBuildable for Pair {
+    type Schema = TestSchema;
+    fn initiate_build(
+        graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<TestSchema>>>,
+    ) -> SubgraphBuilder<Pair, TestSchema> {
+        let template = TEST_SCHEMA.template_library.get(&PAIR_TEMPLATE_ID).unwrap();
+        let graph = graph.into();
+        let builder = RGSOConcreteBuilder::new(
+            HashMap::new(),
+            Some(BTreeMap::from([(
+                PARTNER_SLOT_ID,
+                SpecializedRActiveSlot {
+                    base: RActiveSlot {
+                        slot: template.operative_slots.get(&PARTNER_SLOT_ID).unwrap(),
+                        slotted_instances: RwSignal::new(vec![]),
+                    },
+                    slot_enum: PairAllSlots::Partner,
+                },
+            )])),
+            TEST_SCHEMA
+                .operative_library
+                .get(&PAIR_OPERATIVE_ID)
+                .unwrap(),
+            template,
+            graph.clone(),
+        );
+        let id = *builder.get_id();
+        SubgraphBuilder::new(Some(builder), id, graph)
+    }
+    fn initiate_edit(
+        id: Uid,
+        graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<TestSchema>>>,
+    ) -> SubgraphBuilder<Pair, TestSchema> {
+        SubgraphBuilder::new(None, id, graph.into())
+    }
+    fn get_operative_id() -> Uid {
+        PAIR_OPERATIVE_ID
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Label {}
+#[derive(Clone, Debug, strum_macros::EnumString)]
+pub enum LabelAllSlots {}
+impl HasSlotEnum for Label {
+    type SlotEnum = LabelAllSlots;
+}
+impl RIntoSchema for Label {
+    type Schema = TestSchema;
+    fn into_schema(instantiable: RGSOConcrete<Self, TestSchema>) -> TestSchema {
+        TestSchema::Label(instantiable)
+    }
+}
+impl RBuildable for Label {
+    type Schema = TestSchema;
+    fn initiate_build(
+        graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<TestSchema>>>,
+    ) -> SubgraphBuilder<Label, TestSchema> {
+        let template = TEST_SCHEMA
+            .template_library
+            .get(&LABEL_TEMPLATE_ID)
+            .unwrap();
+        let graph = graph.into();
+        let builder = RGSOConcreteBuilder::new(
+            HashMap::from([
+                (TEXT_FIELD_ID, RwSignal::new(None)),
+                (RANK_FIELD_ID, RwSignal::new(None)),
+            ]),
+            None,
+            TEST_SCHEMA
+                .operative_library
+                .get(&LABEL_OPERATIVE_ID)
+                .unwrap(),
+            template,
+            graph.clone(),
+        );
+        let id = *builder.get_id();
+        SubgraphBuilder::new(Some(builder), id, graph)
+    }
+    fn initiate_edit(
+        id: Uid,
+        graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<TestSchema>>>,
+    ) -> SubgraphBuilder<Label, TestSchema> {
+        SubgraphBuilder::new(None, id, graph.into())
+    }
+    fn get_operative_id() -> Uid {
+        LABEL_OPERATIVE_ID
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum TestSchema {
+    Node(RGSOConcrete<Node, TestSchema>),
+    Pair(RGSOConcrete<Pair, TestSchema>),
+    Label(RGSOConcrete<Label, TestSchema>),
+}
+impl PartialEq for TestSchema {
+    fn eq(&self, other: &Self) -> bool {
+        self.get_id() == other.get_id()
+    }
+}
+impl RGSO for TestSchema {
+    type Schema = TestSchema;
+    fn get_id(&self) -> &Uid {
+        match self {
+            Self::Node(item) => item.get_id(),
+            Self::Pair(item) => item.get_id(),
+            Self::Label(item) => item.get_id(),
+        }
+    }
+    fn operative(&self) -> &'static LibraryOperative<PrimitiveTypes, PrimitiveValues> {
+        match self {
+            Self::Node(item) => item.operative(),
+            Self::Pair(item) => item.operative(),
+            Self::Label(item) => item.operative(),
+        }
+    }
+    fn template(&self) -> &'static LibraryTemplate<PrimitiveTypes, PrimitiveValues> {
+        match self {
+            Self::Node(item) => item.template(),
+            Self::Pair(item) => item.template(),
+            Self::Label(item) => item.template(),
+        }
+    }
+    fn outgoing_slots(&self) -> BTreeMap<&Uid, &RActiveSlot> {
+        match self {
+            Self::Node(item) => item.outgoing_slots(),
+            Self::Pair(item) => item.outgoing_slots(),
+            Self::Label(item) => item.outgoing_slots(),
+        }
+    }
+    fn incoming_slots(&self) -> RwSignal<Vec<SlotRef>> {
+        match self {
+            Self::Node(item) => item.incoming_slots(),
+            Self::Pair(item) => item.incoming_slots(),
+            Self::Label(item) => item.incoming_slots(),
+        }
+    }
+    fn fields(&self) -> &HashMap<Uid, RwSignal<PrimitiveValues>> {
+        match self {
+            Self::Node(item) => item.fields(),
+            Self::Pair(item) => item.fields(),
+            Self::Label(item) => item.fields(),
+        }
+    }
+}
+impl EditRGSO for TestSchema {
+    fn add_incoming(&self, slot_ref: SlotRef) -> &Self {
+        match self {
+            Self::Node(item) => {
+                item.add_incoming(slot_ref);
+            }
+            Self::Pair(item) => {
+                item.add_incoming(slot_ref);
+            }
+            Self::Label(item) => {
+                item.add_incoming(slot_ref);
+            }
+        }
+        self
+    }
+    fn add_outgoing(&self, slot_ref: SlotRef) -> &Self {
+        match self {
+            Self::Node(item) => {
+                item.add_outgoing(slot_ref);
+            }
+            Self::Pair(item) => {
+                item.add_outgoing(slot_ref);
+            }
+            Self::Label(item) => {
+                item.add_outgoing(slot_ref);
+            }
+        }
+        self
+    }
+    fn remove_outgoing(&self, slot_ref: &SlotRef) -> &Self {
+        match self {
+            Self::Node(item) => {
+                item.remove_outgoing(slot_ref);
+            }
+            Self::Pair(item) => {
+                item.remove_outgoing(slot_ref);
+            }
+            Self::Label(item) => {
+                item.remove_outgoing(slot_ref);
+            }
+        }
+        self
+    }
+    fn remove_incoming(&self, parent_id: &Uid, slot_id: Option<&Uid>) -> Vec<SlotRef> {
+        match self {
+            Self::Node(item) => item.remove_incoming(parent_id, slot_id),
+            Self::Pair(item) => item.remove_incoming(parent_id, slot_id),
+            Self::Label(item) => item.remove_incoming(parent_id, slot_id),
+        }
+    }
+    fn update_field(&self, field_edit: HistoryFieldEdit) -> &Self {
+        match self {
+            Self::Node(item) => {
+                item.update_field(field_edit);
+            }
+            Self::Pair(item) => {
+                item.update_field(field_edit);
+            }
+            Self::Label(item) => {
+                item.update_field(field_edit);
+            }
+        }
+        self
+    }
+    fn get_graph(&self) -> &std::sync::Arc<RBaseGraphEnvironment<TestSchema>> {
+        match self {
+            Self::Node(item) => item.get_graph(),
+            Self::Pair(item) => item.get_graph(),
+            Self::Label(item) => item.get_graph(),
+        }
+    }
+}
+impl FromStandalone for TestSchema {
+    type Schema = TestSchema;
+    fn from_standalone(
+        value: StandaloneRGSOConcrete,
+        graph: SharedGraph<TestSchema>,
+    ) -> Result<TestSchema, ElementCreationError> {
+        match value.operative {
+            NODE_OPERATIVE_ID => {
+                RGSOConcrete::<Node, TestSchema>::from_standalone(value, graph, &TEST_SCHEMA)
+                    .map(TestSchema::Node)
+            }
+            PAIR_OPERATIVE_ID => {
+                RGSOConcrete::<Pair, TestSchema>::from_standalone(value, graph, &TEST_SCHEMA)
+                    .map(TestSchema::Pair)
+            }
+            LABEL_OPERATIVE_ID => {
+                RGSOConcrete::<Label, TestSchema>::from_standalone(value, graph, &TEST_SCHEMA)
+                    .map(TestSchema::Label)
+            }
+            _ => Err(ElementCreationError::NonexistentOperative {
+                instance_id: value.id,
+                operative_id: value.operative,
+            }),
+        }
+    }
+}
+impl From<TestSchema> for StandaloneRGSOConcrete {
+    fn from(value: TestSchema) -> Self {
+        let outgoing_slots = value
+            .outgoing_slots()
+            .values()
+            .flat_map(|slot| {
+                slot.slotted_instances
+                    .get_untracked()
+                    .into_iter()
+                    .map(|target_instance_id| SlotRef {
+                        host_instance_id: *value.get_id(),
+                        slot_id: slot.slot.tag.id,
+                        target_instance_id,
+                    })
+            })
+            .collect();
+        Self {
+            id: *value.get_id(),
+            fields: value
+                .fields()
+                .iter()
+                .map(|(field_id, field_value)| (*field_id, field_value.get_untracked()))
+                .collect(),
+            outgoing_slots,
+            incoming_slots: value.incoming_slots().get_untracked(),
+            operative: value.operative().tag.id,
+            template: value.template().tag.id,
+        }
+    }
+}
+
+pub fn initialize_graph() -> SharedGraph<TestSchema> {
+    crate::utils::initialize_graph_unpopulated(&TEST_SCHEMA)
+}
+
+/// Commits a new node and returns its id
+pub fn create_node(
+    graph: &SharedGraph<TestSchema>,
+    name: &str,
+) -> Result<Uid, ElementCreationError> {
+    let mut builder = Node::initiate_build(graph.clone());
+    builder.edit_field(NAME_FIELD_ID, PrimitiveValues::String(name.to_string()));
+    builder.execute()?;
+    Ok(*builder.get_id())
+}
+/// Commits a new label and returns its id
+pub fn create_label(
+    graph: &SharedGraph<TestSchema>,
+    text: &str,
+    rank: u32,
+) -> Result<Uid, ElementCreationError> {
+    let mut builder = Label::initiate_build(graph.clone());
+    builder.edit_field(TEXT_FIELD_ID, PrimitiveValues::String(text.to_string()));
+    builder.edit_field(RANK_FIELD_ID, PrimitiveValues::Int(rank));
+    builder.execute()?;
+    Ok(*builder.get_id())
+}
+/// Commits a new pair whose partner is the given node and returns its id
+pub fn create_pair(
+    graph: &SharedGraph<TestSchema>,
+    partner_id: &Uid,
+) -> Result<Uid, ElementCreationError> {
+    let mut builder = Pair::initiate_build(graph.clone());
+    builder.add_outgoing::<Node>(
+        &PARTNER_SLOT_ID,
+        BlueprintId::Existing(*partner_id),
+        Some(partner_edit(graph, builder.get_id(), partner_id)),
+    );
+    builder.execute()?;
+    Ok(*builder.get_id())
+}
+fn partner_edit(
+    graph: &SharedGraph<TestSchema>,
+    pair_id: &Uid,
+    partner_id: &Uid,
+) -> SubgraphBuilder<Node, TestSchema> {
+    let mut partner = Node::initiate_edit(*partner_id, graph.clone());
+    partner.add_incoming::<Pair>(
+        SlotRef {
+            host_instance_id: *pair_id,
+            slot_id: PARTNER_SLOT_ID,
+            target_instance_id: *partner_id,
+        },
+        None,
+    );
+    partner
+}
+pub fn rename(
+    graph: &SharedGraph<TestSchema>,
+    node_id: &Uid,
+    name: &str,
+) -> Result<(), ElementCreationError> {
+    let mut builder = Node::initiate_edit(*node_id, graph.clone());
+    builder.edit_field(NAME_FIELD_ID, PrimitiveValues::String(name.to_string()));
+    builder.execute().map(|_| ())
+}
+pub fn link(
+    graph: &SharedGraph<TestSchema>,
+    host_id: &Uid,
+    target_id: &Uid,
+) -> Result<(), ElementCreationError> {
+    let mut builder = Node::initiate_edit(*host_id, graph.clone());
+    let mut target = Node::initiate_edit(*target_id, graph.clone());
+    target.add_incoming::<Node>(
+        SlotRef {
+            host_instance_id: *host_id,
+            slot_id: LINKS_SLOT_ID,
+            target_instance_id: *target_id,
+        },
+        None,
+    );
+    builder.add_outgoing::<Node>(
+        &LINKS_SLOT_ID,
+        BlueprintId::Existing(*target_id),
+        Some(target),
+    );
+    builder.execute().map(|_| ())
+}
+pub fn unlink(
+    graph: &SharedGraph<TestSchema>,
+    host_id: &Uid,
+    target_id: &Uid,
+) -> Result<(), ElementCreationError> {
+    let mut builder = Node::initiate_edit(*host_id, graph.clone());
+    builder.remove_outgoing(SlotRef {
+        host_instance_id: *host_id,
+        slot_id: LINKS_SLOT_ID,
+        target_instance_id: *target_id,
+    });
+    builder.execute().map(|_| ())
+}
+/// Moves the pair on to another partner
+pub fn repartner(
+    graph: &SharedGraph<TestSchema>,
+    pair_id: &Uid,
+    partner_id: &Uid,
+) -> Result<(), ElementCreationError> {
+    let current = graph
+        .get(pair_id)
+        .ok_or(ElementCreationError::NonexistentInstance { id: *pair_id })?;
+    let mut builder = Pair::initiate_edit(*pair_id, graph.clone());
+    current
+        .outgoing_slots()
+        .get(&PARTNER_SLOT_ID)
+        .map(|slot| slot.slotted_instances.get_untracked())
+        .unwrap_or_default()
+        .into_iter()
+        .for_each(|target_instance_id| {
+            builder.remove_outgoing(SlotRef {
+                host_instance_id: *pair_id,
+                slot_id: PARTNER_SLOT_ID,
+                target_instance_id,
+            })
+        });
+    builder.add_outgoing::<Node>(
+        &PARTNER_SLOT_ID,
+        BlueprintId::Existing(*partner_id),
+        Some(partner_edit(graph, pair_id, partner_id)),
+    );
+    builder.execute().map(|_| ())
+}
+/// The name of the node, if it exists
+pub fn name(graph: &SharedGraph<TestSchema>, node_id: &Uid) -> Option<String> {
+    let node = graph.get(node_id)?;
+    match node.fields().get(&NAME_FIELD_ID)?.get_untracked() {
+        PrimitiveValues::String(name) => Some(name),
+        _ => None,
+    }
+}
+/// Every instance of the graph with its fields and edges in a fixed order, so that graphs built
+/// in different orders can be compared
+pub fn normalized(graph: &SharedGraph<TestSchema>) -> Vec<String> {
+    let mut instances = graph
+        .created_instances
+        .get_untracked()
+        .into_values()
+        .map(|instance| {
+            let standalone = StandaloneRGSOConcrete::from(instance);
+            let mut fields = standalone
+                .fields
+                .iter()
+                .map(|(field_id, value)| format!("{}={}", field_id, value))
+                .collect::<Vec<_>>();
+            fields.sort();
+            let edges = |slot_refs: &[SlotRef]| {
+                let mut edges = slot_refs
+                    .iter()
+                    .map(|slot_ref| {
+                        format!(
+                            "{}-{}->{}",
+                            slot_ref.host_instance_id,
+                            slot_ref.slot_id,
+                            slot_ref.target_instance_id
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                edges.sort();
+                edges
+            };
+            format!(
+                "{} {} {:?} out {:?} in {:?}",
+                standalone.id,
+                standalone.operative,
+                fields,
+                edges(&standalone.outgoing_slots),
+                edges(&standalone.incoming_slots)
+            )
+        })
+        .collect::<Vec<_>>();
+    instances.sort();
+    instances
+}
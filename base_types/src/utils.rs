@@ -1,11 +1,18 @@
-use std::{collections::HashMap, sync::Arc};
+#[cfg(feature = "reactive")]
+use std::{collections::HashMap, path::Path, sync::Arc};
 
+#[cfg(feature = "reactive")]
+use crate::post_generation::{
+    integrity::{check_instances, IntegrityProblem, IntegrityReport, LoadError},
+    journal::{instances_from_json, replay, snapshot_fingerprint, BlueprintJournal, JournalError},
+    store::{GraphStore, StoreError},
+    reactive::{
+        from_reactive::FromStandalone, hidden::EditRGSO, RBaseGraphEnvironment, SharedGraph, RGSO,
+    },
+    ElementCreationError, StandaloneRGSOConcrete,
+};
 use crate::{
     constraint_schema::ConstraintSchema,
-    post_generation::{
-        reactive::{from_reactive::FromStandalone, RBaseGraphEnvironment, SharedGraph},
-        StandaloneRGSOConcrete,
-    },
     primitives::{PrimitiveTypes, PrimitiveValues},
 };
 
@@ -97,100 +104,166 @@ impl<T: IntoPrimitiveValue> IntoPrimitiveValue for Vec<T> {
     }
 }
 
-// pub trait FromPrimitiveValue {
-//     type Output;
-//     fn from_primitive_value(value: &PrimitiveValues) -> Self::Output;
-// }
-
-// impl FromPrimitiveValue for u32 {
-//     type Output = u32;
-//     fn from_primitive_value(value: &PrimitiveValues) -> Self::Output {
-//         if let PrimitiveValues::Int(val) = value {
-//             *val
-//         } else {
-//             unreachable!()
-//         }
-//     }
-// }
-
-// impl FromPrimitiveValue for f32 {
-//     type Output = f32;
-//     fn from_primitive_value(value: &PrimitiveValues) -> Self::Output {
-//         if let PrimitiveValues::Float(val) = value {
-//             *val
-//         } else {
-//             unreachable!()
-//         }
-//     }
-// }
+/// Reads a field value back into its Rust type, `None` if the value is of another type
+pub trait FromPrimitiveValue: Sized {
+    fn from_primitive_value(value: &PrimitiveValues) -> Option<Self>;
+}
 
-// impl FromPrimitiveValue for String {
-//     type Output = String;
-//     fn from_primitive_value(value: &PrimitiveValues) -> Self::Output {
-//         if let PrimitiveValues::String(val) = value {
-//             val.clone()
-//         } else {
-//             unreachable!()
-//         }
-//     }
-// }
+impl FromPrimitiveValue for u32 {
+    fn from_primitive_value(value: &PrimitiveValues) -> Option<Self> {
+        match value {
+            PrimitiveValues::Int(val) => Some(*val),
+            _ => None,
+        }
+    }
+}
 
-// impl FromPrimitiveValue for bool {
-//     type Output = bool;
-//     fn from_primitive_value(value: &PrimitiveValues) -> Self::Output {
-//         if let PrimitiveValues::Bool(val) = value {
-//             *val
-//         } else {
-//             unreachable!()
-//         }
-//     }
-// }
-// impl FromPrimitiveValue for Box<Option<PrimitiveValues>> {
-//     type Output = Option<PrimitiveValues>;
-//     fn from_primitive_value(value: &PrimitiveValues) -> Self::Output {
-//         if let PrimitiveValues::Option(val) = value {
-//             val.deref().clone()
-//         } else {
-//             unreachable!()
-//         }
-//     }
-// }
+impl FromPrimitiveValue for String {
+    fn from_primitive_value(value: &PrimitiveValues) -> Option<Self> {
+        match value {
+            PrimitiveValues::String(val) => Some(val.clone()),
+            _ => None,
+        }
+    }
+}
 
-// impl FromPrimitiveValue for Vec<PrimitiveValues> {
-//     type Output = Vec<PrimitiveValues>;
-//     fn from_primitive_value(value: &PrimitiveValues) -> Self::Output {
-//         if let PrimitiveValues::List(val) = value {
-//             val.clone()
-//         } else {
-//             unreachable!()
-//         }
-//     }
-// }
+impl FromPrimitiveValue for bool {
+    fn from_primitive_value(value: &PrimitiveValues) -> Option<Self> {
+        match value {
+            PrimitiveValues::Bool(val) => Some(*val),
+            _ => None,
+        }
+    }
+}
+impl<T: FromPrimitiveValue> FromPrimitiveValue for Option<T> {
+    fn from_primitive_value(value: &PrimitiveValues) -> Option<Self> {
+        match value {
+            PrimitiveValues::Option(val) => match val.as_ref() {
+                Some(inner) => T::from_primitive_value(inner).map(Some),
+                None => Some(None),
+            },
+            _ => None,
+        }
+    }
+}
+impl<T: FromPrimitiveValue> FromPrimitiveValue for Vec<T> {
+    fn from_primitive_value(value: &PrimitiveValues) -> Option<Self> {
+        match value {
+            PrimitiveValues::List(val) => val.iter().map(T::from_primitive_value).collect(),
+            _ => None,
+        }
+    }
+}
 
+#[cfg(feature = "reactive")]
 pub fn initialize_graph_unpopulated<TSchema: Sync + Send + 'static>(
     constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
 ) -> SharedGraph<TSchema> {
     Arc::new(RBaseGraphEnvironment::new(&constraint_schema)).into()
 }
+#[cfg(feature = "reactive")]
 pub fn initialize_graph_populated<
-    TSchema: Sync + Send + FromStandalone<Schema = TSchema> + 'static,
+    TSchema: Sync + Send + RGSO + FromStandalone<Schema = TSchema> + 'static,
 >(
     constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
     json_initial_population: &str,
 ) -> SharedGraph<TSchema> {
-    let initial_population: Vec<StandaloneRGSOConcrete> =
-        serde_json::from_str(json_initial_population)
-            .expect("initial population data formatted incorrectly");
+    let initial_population = instances_from_json(json_initial_population)
+        .expect("initial population data formatted incorrectly");
+    populate_graph(constraint_schema, initial_population)
+        .expect("initial population does not conform to the schema")
+}
+#[cfg(feature = "reactive")]
+/// Builds a graph from JSON instances, checking them against the schema first, see
+/// [`crate::post_generation::integrity`]. With `repair` set, dangling edges are dropped and missing
+/// mirrors added before checking. The report lists any repairs made.
+pub fn initialize_graph_validated<
+    TSchema: Sync + Send + RGSO + FromStandalone<Schema = TSchema> + 'static,
+>(
+    constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    json: &str,
+    repair: bool,
+) -> Result<(SharedGraph<TSchema>, IntegrityReport), LoadError> {
+    let instances =
+        instances_from_json(json).map_err(|error| LoadError::Malformed(error.to_string()))?;
+    let (instances, mut report) = check_instances(constraint_schema, instances, repair);
+    if !report.is_valid() {
+        return Err(LoadError::Invalid(report));
+    }
+    match populate_graph(constraint_schema, instances) {
+        Ok(graph) => Ok((graph, report)),
+        Err(error) => {
+            report.problems.push(IntegrityProblem::Invalid(error));
+            Err(LoadError::Invalid(report))
+        }
+    }
+}
+#[cfg(feature = "reactive")]
+pub(crate) fn populate_graph<TSchema: Sync + Send + RGSO + FromStandalone<Schema = TSchema> + 'static>(
+    constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    population: Vec<StandaloneRGSOConcrete>,
+) -> Result<SharedGraph<TSchema>, ElementCreationError> {
     let graph = Arc::new(RBaseGraphEnvironment::new(&constraint_schema));
-    let formatted_initial_population = initial_population
+    let formatted_population = population
         .into_iter()
         .map(|standalone| {
-            (
-                standalone.id.clone(),
-                TSchema::from_standalone(standalone, graph.clone().into()),
-            )
+            let id = standalone.id;
+            TSchema::from_standalone(standalone, graph.clone().into()).map(|instance| (id, instance))
         })
-        .collect::<HashMap<crate::common::Uid, TSchema>>();
-    graph.initialize(formatted_initial_population);
-    graph.into()
+        .collect::<Result<HashMap<crate::common::Uid, TSchema>, _>>()?;
+    graph.initialize(formatted_population);
+    Ok(graph.into())
+}
+#[cfg(feature = "reactive")]
+/// Rebuilds a graph by replaying the journal at `journal_path` over the snapshot at
+/// `snapshot_path`, then attaches the journal so that further commits are appended to it.
+/// Either file may be missing, in which case it is treated as empty.
+pub fn initialize_graph_from_journal<TSchema>(
+    constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    snapshot_path: impl AsRef<Path>,
+    journal_path: impl AsRef<Path>,
+) -> Result<SharedGraph<TSchema>, JournalError>
+where
+    TSchema: Sync
+        + Send
+        + EditRGSO<Schema = TSchema>
+        + FromStandalone<Schema = TSchema>
+        + Into<StandaloneRGSOConcrete>
+        + 'static,
+{
+    let snapshot_json = match std::fs::read_to_string(snapshot_path) {
+        Ok(json) => Some(json),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => return Err(error.into()),
+    };
+    let snapshot: Vec<StandaloneRGSOConcrete> = match &snapshot_json {
+        Some(json) => instances_from_json(json)
+            .map_err(|error| JournalError::MalformedSnapshot(error.to_string()))?,
+        None => Vec::new(),
+    };
+    let graph = populate_graph(constraint_schema, snapshot)
+        .map_err(JournalError::InvalidSnapshot)?;
+    let fingerprint = snapshot_json.as_deref().map(snapshot_fingerprint);
+    replay(&graph, BlueprintJournal::recover(&journal_path, fingerprint.as_deref())?)?;
+    graph.attach_journal(BlueprintJournal::open(&journal_path)?);
+    Ok(graph)
+}
+#[cfg(feature = "reactive")]
+/// Builds a graph from everything in the store, then attaches the store so that further commits
+/// are written through to it
+pub fn initialize_graph_from_store<TSchema>(
+    constraint_schema: &'static ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    mut store: impl GraphStore + Send + 'static,
+) -> Result<SharedGraph<TSchema>, StoreError>
+where
+    TSchema: Sync
+        + Send
+        + EditRGSO<Schema = TSchema>
+        + FromStandalone<Schema = TSchema>
+        + Into<StandaloneRGSOConcrete>
+        + 'static,
+{
+    let graph = populate_graph(constraint_schema, store.load()?).map_err(StoreError::Invalid)?;
+    graph.attach_store(store);
+    Ok(graph)
 }
@@ -140,10 +140,11 @@ pub(crate) fn generate_operative_streams(
             Span::call_site(),
         );
         let field_value_type = get_primitive_type(
-            &locked_field_digest
-                .fulfilled_field
-                .value
-                .get_primitive_type(),
+            &field_digest
+                .field_constraints
+                .get(&locked_field_digest.fulfilled_field.field_constraint_id)
+                .expect("locked field must have a field constraint")
+                .value_type,
         );
         let locked_return_val = get_primitive_value(&locked_field_digest.fulfilled_field.value);
         quote! {
@@ -332,6 +333,61 @@ pub(crate) fn generate_operative_streams(
         }
     });
 
+    // Lookups by value for fields which the schema marks as indexed or unique. Library instances
+    // are found through the lookups of their operative, which return each instance as whichever
+    // subclass it belongs to
+    let indexed_fields = match constraint_schema.operative_library.contains_key(&operative_id) {
+        true => unfulfilled_fields
+            .iter()
+            .filter(|field| field.index != FieldIndex::Unindexed)
+            .collect::<Vec<_>>(),
+        false => Vec::new(),
+    };
+    let find_by_fields_stream = indexed_fields
+        .iter()
+        .map(|field| {
+            let field_id = field.tag.id;
+            let field_value_type = get_primitive_type(&field.value_type);
+            let find_by_fn_name = Ident::new(
+                &format!("find_by_{}", field.tag.name.to_lowercase()),
+                Span::call_site(),
+            );
+            let return_type = get_operative_subclass_enum_name(constraint_schema, &operative_id);
+            let subclasses_names = get_all_subclasses(constraint_schema, &operative_id)
+                .iter()
+                .map(|sub| get_operative_variant_name(&sub.get_tag().name))
+                .collect::<Vec<_>>();
+            let variants_match = if subclasses_names.len() <= 1 {
+                quote! {
+                    Schema::#struct_name(wrapper) => Some(wrapper),
+                    _ => None,
+                }
+            } else {
+                quote! {
+                    #(Schema::#subclasses_names(wrapper) => Some(#return_type::#subclasses_names(wrapper)),)*
+                    _ => None,
+                }
+            };
+            let (return_signature, collection_stream) = match field.index {
+                FieldIndex::Unique => (quote! {Option<#return_type>}, quote! {.next()}),
+                _ => (quote! {Vec<#return_type>}, quote! {.collect::<Vec<_>>()}),
+            };
+            quote! {
+                impl #struct_name {
+                    pub fn #find_by_fn_name(graph: &RBaseGraphEnvironment<Schema>, value: impl Into<#field_value_type>) -> #return_signature {
+                        graph
+                            .find_by_field(&#field_id, &value.into().into_primitive_value())
+                            .into_iter()
+                            .filter_map(|instance| match instance {
+                                #variants_match
+                            })
+                            #collection_stream
+                    }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
     let manipulate_slots_stream = all_slot_digests.iter().enumerate().map(|(slot_index, slot)| {
         let slot_name = &slot.slot.tag.name;
         let slot_id = slot.slot.tag.id;
@@ -783,33 +839,6 @@ pub(crate) fn generate_operative_streams(
                 );
                 existing_multi_item_generate_add_temp_fn_definition(add_new_fn_name)
             };
-            let all_unacceptable_types = constraint_schema.operative_library.values().filter_map(|op|
-                if !items.iter().any(|super_el| super_el.tag.id == op.tag.id) {
-                    Some(op)
-                } else {
-                    None
-                }
-            );
-            let unacceptable_type_names = all_unacceptable_types.clone().map(|op| get_operative_variant_name(&op.tag.name)).collect::<Vec<_>>();
-            let unacceptable_string_names = all_unacceptable_types.map(|op| op.tag.name.clone()).collect::<Vec<_>>();
-            let acceptable_type_names = items.iter().map(|op| get_operative_variant_name(&op.tag.name)).collect::<Vec<_>>();
-            let expected_type_names_string = items.iter().map(|op| op.tag.name.clone()).collect::<Vec<_>>().join(", ");
-            let mismatch_error_handling = quote!{
-                let error = if let Some(existing_item) = self.inner_builder.get_graph().get(&existing_item_id) {
-                    match existing_item {
-                        #(Schema::#acceptable_type_names(_) => None,)*
-                        #(Schema::#unacceptable_type_names(_) => {
-                            Some(base_types::post_generation::ElementCreationError::OutgoingElementIsWrongType{expected: #expected_type_names_string.to_string(), recieved: #unacceptable_string_names.to_string() })
-                        },)*
-                    }
-                } else {
-                    Some(base_types::post_generation::ElementCreationError::OutgoingElementDoesntExist{id: existing_item_id.clone()})
-                };
-                if error.is_some() {
-                    self.inner_builder.add_error(error.unwrap());
-                }
-            };
-
             quote!{
                 #(#marker_trait_stream)*
                 impl< FieldsTS, #generic_slot_generics_stream_with_trait_bound> FreshBuilder<#struct_name, Schema, FieldsTS, (#main_builder_slot_generics_stream)>
@@ -831,8 +860,6 @@ pub(crate) fn generate_operative_streams(
                     pub #fresh_add_existing_fn_signature
                     {
                         let existing_item_id = existing_item_id.clone();
-                        #mismatch_error_handling
-
                         let mut new_builder = ExistingBuilder {
                             inner_builder: #single_item_variant_name::initiate_edit(existing_item_id.clone(), self.inner_builder.get_graph().clone()) ,
                         };
@@ -860,8 +887,6 @@ pub(crate) fn generate_operative_streams(
                     pub #existing_add_existing_fn_signature
                     {
                         let existing_item_id = existing_item_id.clone();
-                        #mismatch_error_handling
-
                         let mut new_builder = ExistingBuilder {
                             inner_builder: #single_item_variant_name::initiate_edit(existing_item_id.clone(), self.inner_builder.get_graph().clone()) ,
                         };
@@ -984,6 +1009,11 @@ pub(crate) fn generate_operative_streams(
         quote! {base_types::post_generation::type_level::SlotTS<to_composite_id_macro::to_comp_id!(#index), #count, #local_min, #local_min_nonexistent, #local_max, #local_max_nonexistent, #local_zero_allowed>}
     });
     let item_default_slot_typestate_stream = quote! { #(#item_default_slot_typestate_stream,)*};
+    // Instances of subclasses can be duplicated as this operative as well
+    let subclass_ids = get_all_subclasses(constraint_schema, &operative_id)
+        .iter()
+        .map(|subclass| subclass.tag.id)
+        .collect::<Vec<_>>();
 
     quote! {
         #[derive(Clone, Debug, Default)]
@@ -1018,6 +1048,29 @@ pub(crate) fn generate_operative_streams(
                     _slots_typestate: std::marker::PhantomData,
                 }
             }
+            /// Copies the instance along with everything reachable through `owning_slots` as a
+            /// single undo step, see [`base_types::post_generation::duplicate`]. The copies' ids
+            /// are listed under the originals' ids. Fails if the instance isn't of this operative
+            pub fn duplicate(
+                id: &base_types::common::Uid,
+                owning_slots: &[base_types::common::Uid],
+                add_to_holders: bool,
+                graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<Schema>>>,
+            ) -> Result<base_types::post_generation::ExecutionResult, base_types::post_generation::ElementCreationError> {
+                let graph = graph.into();
+                let operative_id = graph
+                    .get(id)
+                    .map(|instance| instance.operative().tag.id)
+                    .ok_or(base_types::post_generation::ElementCreationError::NonexistentInstance { id: *id })?;
+                if ![#(#subclass_ids,)*].contains(&operative_id) {
+                    return Err(base_types::post_generation::ElementCreationError::WrongOperative {
+                        instance_id: *id,
+                        expected: #operative_id,
+                        found: operative_id,
+                    });
+                }
+                base_types::post_generation::duplicate::duplicate_in_graph(graph, id, owning_slots, add_to_holders)
+            }
         }
 
 
@@ -1069,6 +1122,7 @@ pub(crate) fn generate_operative_streams(
         }
 
         #(#manipulate_fields_stream)*
+        #(#find_by_fields_stream)*
         #(#manipulate_slots_stream)*
         #(#get_locked_fields_stream)*
 
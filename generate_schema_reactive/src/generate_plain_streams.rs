@@ -0,0 +1,288 @@
+use base_types::constraint_schema::{ConstraintSchema, FieldIndex, LibraryOperative};
+use base_types::constraint_schema_item::ConstraintSchemaItem;
+use base_types::primitives::*;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::utils::{
+    get_all_subclasses, get_operative_variant_name, get_primitive_type, get_primitive_value,
+    get_template_get_field_fn_name, get_template_get_slot_fn_name_id_only,
+};
+
+/// Typed editor, view and lookups over the non-reactive runtime for a single operative
+pub(crate) fn generate_plain_operative_streams(
+    operative: &LibraryOperative<PrimitiveTypes, PrimitiveValues>,
+    constraint_schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+) -> TokenStream {
+    let struct_name = get_operative_variant_name(&operative.tag.name);
+    let editor_name = Ident::new(&format!("{}Editor", struct_name), Span::call_site());
+    let view_name = Ident::new(&format!("{}View", struct_name), Span::call_site());
+    let operative_id = operative.tag.id;
+    let template = constraint_schema
+        .template_library
+        .get(&operative.template_id)
+        .expect("operative must be based on a template");
+    let locked_fields = operative
+        .get_locked_fields_digest(constraint_schema)
+        .expect("operative must have a field digest")
+        .locked_fields;
+    // Instances of subclasses are also instances of this operative
+    let subclass_ids = get_all_subclasses(constraint_schema, &operative_id)
+        .iter()
+        .map(|subclass| subclass.tag.id)
+        .collect::<Vec<_>>();
+
+    let field_setters = template
+        .field_constraints
+        .values()
+        .filter(|field| !locked_fields.contains_key(&field.tag.id))
+        .map(|field| {
+            let field_id = field.tag.id;
+            let field_value_type = get_primitive_type(&field.value_type);
+            let field_setter_fn_name = Ident::new(
+                &format!("set_{}", field.tag.name.to_lowercase()),
+                Span::call_site(),
+            );
+            quote! {
+                pub fn #field_setter_fn_name(self, new_val: impl Into<#field_value_type>) -> Self {
+                    self.transaction.set_field(self.id, #field_id, new_val.into().into_primitive_value());
+                    self
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let field_getters = template
+        .field_constraints
+        .values()
+        .map(|field| {
+            let field_id = field.tag.id;
+            let field_value_type = get_primitive_type(&field.value_type);
+            let field_getter_fn_name = get_template_get_field_fn_name(&field.tag.name);
+            let body = match locked_fields.get(&field_id) {
+                Some(locked_field) => {
+                    let locked_return_val =
+                        get_primitive_value(&locked_field.fulfilled_field.value);
+                    quote! { #locked_return_val.into() }
+                }
+                None => quote! {
+                    self.instance
+                        .fields
+                        .get(&#field_id)
+                        .and_then(<#field_value_type as FromPrimitiveValue>::from_primitive_value)
+                        .expect("field values are checked against the schema")
+                },
+            };
+            quote! {
+                pub fn #field_getter_fn_name(&self) -> #field_value_type {
+                    #body
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let slot_streams = template
+        .operative_slots
+        .values()
+        .map(|slot| {
+            let slot_id = slot.tag.id;
+            let slot_name = slot.tag.name.to_lowercase();
+            let add_fn_name = Ident::new(&format!("add_{}", slot_name), Span::call_site());
+            let remove_fn_name =
+                Ident::new(&format!("remove_from_{}", slot_name), Span::call_site());
+            let get_ids_fn_name = get_template_get_slot_fn_name_id_only(&slot.tag.name);
+            let editor_stream = quote! {
+                pub fn #add_fn_name(self, target_id: Uid) -> Self {
+                    self.transaction.add_edge(self.id, #slot_id, target_id);
+                    self
+                }
+                pub fn #remove_fn_name(self, target_id: Uid) -> Self {
+                    self.transaction.remove_edge(self.id, #slot_id, target_id);
+                    self
+                }
+            };
+            let view_stream = quote! {
+                pub fn #get_ids_fn_name(&self) -> Vec<Uid> {
+                    self.instance
+                        .outgoing_slots
+                        .iter()
+                        .filter(|slot_ref| slot_ref.slot_id == #slot_id)
+                        .map(|slot_ref| slot_ref.target_instance_id)
+                        .collect()
+                }
+            };
+            (editor_stream, view_stream)
+        })
+        .collect::<Vec<_>>();
+    let slot_editor_streams = slot_streams.iter().map(|(editor, _)| editor);
+    let slot_view_streams = slot_streams.iter().map(|(_, view)| view);
+
+    let find_by_streams = template
+        .field_constraints
+        .values()
+        .filter(|field| field.index != FieldIndex::Unindexed)
+        .filter(|field| !locked_fields.contains_key(&field.tag.id))
+        .map(|field| {
+            let field_id = field.tag.id;
+            let field_value_type = get_primitive_type(&field.value_type);
+            let find_by_fn_name = Ident::new(
+                &format!("find_by_{}", field.tag.name.to_lowercase()),
+                Span::call_site(),
+            );
+            let (return_signature, collection_stream) = match field.index {
+                FieldIndex::Unique => (quote! {Option<#view_name<'_>>}, quote! {.next()}),
+                _ => (quote! {Vec<#view_name<'_>>}, quote! {.collect::<Vec<_>>()}),
+            };
+            quote! {
+                pub fn #find_by_fn_name(graph: &PlainGraphEnvironment, value: impl Into<#field_value_type>) -> #return_signature {
+                    graph
+                        .find_by_field(&#field_id, &value.into().into_primitive_value())
+                        .into_iter()
+                        .filter_map(#view_name::try_from_instance)
+                        #collection_stream
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    quote! {
+        #[derive(Debug, Clone, Copy)]
+        pub struct #struct_name;
+        impl #struct_name {
+            pub const OPERATIVE_ID: Uid = #operative_id;
+            /// Adds a new instance to the transaction
+            pub fn create(transaction: &mut Transaction) -> #editor_name<'_> {
+                let id = transaction.create(Self::OPERATIVE_ID);
+                #editor_name { transaction, id }
+            }
+            /// Records edits to an existing instance in the transaction
+            pub fn edit(transaction: &mut Transaction, id: Uid) -> #editor_name<'_> {
+                #editor_name { transaction, id }
+            }
+            /// Copies the instance along with everything reachable through `owning_slots`, see
+            /// [`PlainGraphEnvironment::duplicate`]. Fails if the instance isn't of this operative
+            pub fn duplicate(graph: &mut PlainGraphEnvironment, id: &Uid, owning_slots: &[Uid], add_to_holders: bool) -> Result<ExecutionResult, ElementCreationError> {
+                let instance = graph.get(id).ok_or(ElementCreationError::NonexistentInstance { id: *id })?;
+                if #view_name::try_from_instance(instance).is_none() {
+                    return Err(ElementCreationError::WrongOperative {
+                        instance_id: *id,
+                        expected: Self::OPERATIVE_ID,
+                        found: instance.operative,
+                    });
+                }
+                graph.duplicate(id, owning_slots, add_to_holders)
+            }
+            pub fn get<'a>(graph: &'a PlainGraphEnvironment, id: &Uid) -> Option<#view_name<'a>> {
+                graph.get(id).and_then(#view_name::try_from_instance)
+            }
+            /// All instances of this operative and its subclasses, ordered by id
+            pub fn all(graph: &PlainGraphEnvironment) -> Vec<#view_name<'_>> {
+                let mut all = graph
+                    .instances()
+                    .filter_map(#view_name::try_from_instance)
+                    .collect::<Vec<_>>();
+                all.sort_by_key(|view| view.id());
+                all
+            }
+            #(#find_by_streams)*
+        }
+
+        pub struct #editor_name<'a> {
+            transaction: &'a mut Transaction,
+            id: Uid,
+        }
+        impl<'a> #editor_name<'a> {
+            pub fn id(&self) -> Uid {
+                self.id
+            }
+            pub fn set_temp_id(self, temp_id: &str) -> Self {
+                self.transaction.set_temp_id(self.id, temp_id);
+                self
+            }
+            pub fn delete(self) {
+                self.transaction.delete(self.id);
+            }
+            /// Moves the target from one of this instance's slots into a slot of another instance
+            pub fn move_to(self, target_id: Uid, from_slot_id: Uid, to_host_id: Uid, to_slot_id: Uid) -> Self {
+                self.transaction.move_to(self.id, from_slot_id, target_id, to_host_id, to_slot_id);
+                self
+            }
+            #(#field_setters)*
+            #(#slot_editor_streams)*
+        }
+
+        #[derive(Debug, Clone, Copy)]
+        pub struct #view_name<'a> {
+            instance: &'a StandaloneRGSOConcrete,
+        }
+        impl<'a> #view_name<'a> {
+            fn try_from_instance(instance: &'a StandaloneRGSOConcrete) -> Option<Self> {
+                [#(#subclass_ids,)*]
+                    .contains(&instance.operative)
+                    .then_some(Self { instance })
+            }
+            pub fn id(&self) -> Uid {
+                self.instance.id
+            }
+            pub fn instance(&self) -> &'a StandaloneRGSOConcrete {
+                self.instance
+            }
+            #(#field_getters)*
+            #(#slot_view_streams)*
+        }
+    }
+}
+
+/// The `plain` module of the generated crate, which exposes the schema over
+/// [`base_types::post_generation::plain::PlainGraphEnvironment`] and doesn't depend on Leptos
+pub(crate) fn generate_plain_module(
+    constraint_schema: &ConstraintSchema<PrimitiveTypes, PrimitiveValues>,
+    constraint_schema_stream: TokenStream,
+    raw_json_initial_population: Option<String>,
+) -> TokenStream {
+    let operative_streams = constraint_schema
+        .operative_library
+        .values()
+        .map(|operative| generate_plain_operative_streams(operative, constraint_schema))
+        .collect::<Vec<_>>();
+    let initialize_graph_stream = match raw_json_initial_population {
+        Some(initial_population) => quote! {
+            pub fn initialize_graph() -> PlainGraphEnvironment {
+                let population = base_types::post_generation::journal::instances_from_json(#initial_population).expect("initial population formatted incorrectly");
+                PlainGraphEnvironment::from_instances(&CONSTRAINT_SCHEMA, population)
+                    .expect("initial population must conform to the schema")
+            }
+        },
+        None => quote! {
+            pub fn initialize_graph() -> PlainGraphEnvironment {
+                PlainGraphEnvironment::new(&CONSTRAINT_SCHEMA)
+            }
+        },
+    };
+
+    quote! {
+        pub mod plain {
+            pub use base_types::common::Uid;
+            pub use base_types::post_generation::change_set::{ChangeOrigin, ChangeSet};
+            pub use base_types::post_generation::concurrent::*;
+            pub use base_types::post_generation::plain::*;
+            pub use base_types::post_generation::{
+                ElementCreationError, ExecutionResult, StandaloneBlueprint, StandaloneRGSOConcrete,
+            };
+            use base_types::primitives::*;
+            use base_types::utils::{FromPrimitiveValue, IntoPrimitiveValue};
+
+            #constraint_schema_stream
+
+            #initialize_graph_stream
+            pub fn read_snapshot(
+                path: impl AsRef<std::path::Path>,
+            ) -> Result<PlainGraphEnvironment, base_types::post_generation::journal::JournalError> {
+                PlainGraphEnvironment::read_snapshot(&CONSTRAINT_SCHEMA, path)
+            }
+
+            #(#operative_streams)*
+        }
+    }
+}
@@ -32,9 +32,20 @@ use crate::utils::get_template_get_field_trait_name;
 use crate::utils::get_template_get_slot_fn_name_id_only;
 
 mod generate_operative_streams;
+mod generate_plain_streams;
 mod generate_trait_impl_streams;
 mod utils;
 
+/// Which API the generated crate exposes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GenerationTarget {
+    /// The reactive API in `prelude`, along with the non-reactive API in `plain`
+    #[default]
+    Reactive,
+    /// Only the non-reactive API, for services which shouldn't depend on Leptos
+    NonReactive,
+}
+
 struct FieldFnDetails {
     fn_name: TokenStream,
     fn_signature: TokenStream,
@@ -508,15 +519,27 @@ pub fn generate_concrete_schema_reactive(
         |(id, op)| {
             let struct_name = get_operative_variant_name(&op.get_tag().name);
             quote! {
-                #id => super::#struct_name::into_schema(
-                    base_types::post_generation::reactive::RGSOConcrete::<super::#struct_name, Schema>::from_standalone(
-                        value, graph, &super::CONSTRAINT_SCHEMA
-                    )
-                ),
+                #id => base_types::post_generation::reactive::RGSOConcrete::<super::#struct_name, Schema>::from_standalone(
+                    value, graph, &super::CONSTRAINT_SCHEMA
+                ).map(super::#struct_name::into_schema),
+            }
+        },
+    ).collect::<Vec<_>>();
+    let build_by_operative_match_stream = constraint_schema.operative_library.iter().map(
+        |(id, op)| {
+            let struct_name = get_operative_variant_name(&op.get_tag().name);
+            quote! {
+                #id => Some(visitor.visit(super::#struct_name::initiate_build(graph))),
             }
         },
     ).collect::<Vec<_>>();
 
+    let plain_module_stream = generate_plain_streams::generate_plain_module(
+        &constraint_schema,
+        quote! { pub use super::prelude::CONSTRAINT_SCHEMA; },
+        raw_json_initial_population.clone(),
+    );
+
     let doctored_initialize_graph_stream = {
         if let Some(initial_population) = raw_json_initial_population {
             quote! {
@@ -532,6 +555,25 @@ pub fn generate_concrete_schema_reactive(
             }
         }
     };
+    let initialize_graph_from_journal_stream = quote! {
+        pub fn initialize_graph_from_journal(
+            snapshot_path: impl AsRef<std::path::Path>,
+            journal_path: impl AsRef<std::path::Path>,
+        ) -> Result<SharedGraph<Schema>, base_types::post_generation::journal::JournalError> {
+            base_types::utils::initialize_graph_from_journal(&CONSTRAINT_SCHEMA, snapshot_path, journal_path)
+        }
+        pub fn initialize_graph_from_store(
+            store: impl base_types::post_generation::store::GraphStore + Send + 'static,
+        ) -> Result<SharedGraph<Schema>, base_types::post_generation::store::StoreError> {
+            base_types::utils::initialize_graph_from_store(&CONSTRAINT_SCHEMA, store)
+        }
+        pub fn initialize_graph_validated(
+            json: &str,
+            repair: bool,
+        ) -> Result<(SharedGraph<Schema>, base_types::post_generation::integrity::IntegrityReport), base_types::post_generation::integrity::LoadError> {
+            base_types::utils::initialize_graph_validated(&CONSTRAINT_SCHEMA, json, repair)
+        }
+    };
 
     let final_output = quote! {
         pub mod prelude {
@@ -540,6 +582,7 @@ pub fn generate_concrete_schema_reactive(
         use base_types::post_generation::*;
         use base_types::primitives::*;
         pub use base_types::post_generation::non_reactive::StandaloneRGSOConcrete;
+        pub use base_types::post_generation::merge::{MergeReport, ReplicatedBlueprint, Stamp};
         pub use leptos::prelude::*;
         use typenum::*;
         use base_types::utils::*;
@@ -604,6 +647,9 @@ pub fn generate_concrete_schema_reactive(
             pub fn execute(&self) -> Result<ExecutionResult, ElementCreationError> {
                 self.inner_builder.execute()
             }
+            pub fn execute_with_label(&self, label: impl Into<String>) -> Result<ExecutionResult, ElementCreationError> {
+                self.inner_builder.execute_with_label(label)
+            }
             pub fn incorporate<C: Send + Sync + std::fmt::Debug + Clone + RIntoSchema<Schema = TSchema> + 'static + HasSlotEnum>(&mut self, other_builder: impl Incorporatable<C, TSchema>)
             where
                 <C as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug + Send + Sync,
@@ -614,6 +660,17 @@ pub fn generate_concrete_schema_reactive(
                 self.inner_builder.set_temp_id(temp_id);
                 self
             }
+            /// Moves the target from one of this instance's slots into a slot of another instance
+            /// (e.g. `AllSlots::ElementsSentence`). Bounds are only checked on the final state
+            pub fn move_to(mut self, target_id: &Uid, from_slot: impl Into<Uid>, to_host_id: &Uid, to_slot: impl Into<Uid>) -> Self {
+                let from = base_types::post_generation::SlotRef {
+                    host_instance_id: *self.inner_builder.get_id(),
+                    target_instance_id: *target_id,
+                    slot_id: from_slot.into(),
+                };
+                self.inner_builder.move_to(from, to_host_id, &to_slot.into());
+                self
+            }
         }
         impl <T, TSchema: hidden::EditRGSO<Schema = TSchema> + 'static, FieldsTS, SlotsTS> FreshBuilder<T, TSchema, FieldsTS, SlotsTS>
             where
@@ -628,6 +685,9 @@ pub fn generate_concrete_schema_reactive(
             pub fn execute(&self) -> Result<ExecutionResult, ElementCreationError> {
                 self.inner_builder.execute()
             }
+            pub fn execute_with_label(&self, label: impl Into<String>) -> Result<ExecutionResult, ElementCreationError> {
+                self.inner_builder.execute_with_label(label)
+            }
             pub fn incorporate<C: Send + Sync + std::fmt::Debug + Clone + RIntoSchema<Schema = TSchema> + 'static + HasSlotEnum>(&mut self, other_builder: impl Incorporatable<C, TSchema>)
             where
                 <C as HasSlotEnum>::SlotEnum: Clone + std::fmt::Debug + Send + Sync,
@@ -649,10 +709,6 @@ pub fn generate_concrete_schema_reactive(
         #(#slot_trait_enums_stream)*
         #(#trait_definition_streams)*
 
-        fn validate_signal_is_some<T: Send + Sync + 'static>(signal: &leptos::prelude::RwSignal<Option<T>>) -> Result<(), base_types::post_generation::ElementCreationError> {
-            signal.with(|val| {if val.is_some() {return Ok(())} return Err(ElementCreationError::RequiredFieldIsEmpty);})
-        }
-
         lazy_static::lazy_static!{
             pub static ref CONSTRAINT_SCHEMA: base_types::constraint_schema::ConstraintSchema<PrimitiveTypes, PrimitiveValues>
             = serde_json::from_str::<base_types::constraint_schema::ConstraintSchema<PrimitiveTypes, PrimitiveValues>>(#raw_json_schema).expect("Schema formatted incorrectly");
@@ -738,13 +794,28 @@ pub fn generate_concrete_schema_reactive(
             }
             impl base_types::post_generation::reactive::from_reactive::FromStandalone for Schema {
                 type Schema = Schema;
-                fn from_standalone(value: base_types::post_generation::StandaloneRGSOConcrete, graph: SharedGraph<Self::Schema>, ) -> Self::Schema {
+                fn from_standalone(value: base_types::post_generation::StandaloneRGSOConcrete, graph: SharedGraph<Self::Schema>, ) -> Result<Self::Schema, base_types::post_generation::ElementCreationError> {
                    match value.operative {
                        #(#repatriate_num_match_stream)*
-                       _ => unreachable!()
+                       _ => Err(base_types::post_generation::ElementCreationError::NonexistentOperative {
+                           instance_id: value.id,
+                           operative_id: value.operative,
+                       })
                    }
                }
             }
+            impl base_types::post_generation::reactive::BuildByOperative for Schema {
+                fn build_by_operative<V: base_types::post_generation::reactive::BuilderVisitor<Self>>(
+                    operative_id: &base_types::common::Uid,
+                    graph: impl Into<std::sync::Arc<RBaseGraphEnvironment<Self>>>,
+                    visitor: V,
+                ) -> Option<V::Output> {
+                    match *operative_id {
+                        #(#build_by_operative_match_stream)*
+                        _ => None,
+                    }
+                }
+            }
         }
 
         impl From<Schema> for base_types::post_generation::StandaloneRGSOConcrete {
@@ -774,8 +845,38 @@ pub fn generate_concrete_schema_reactive(
         }
 
         #doctored_initialize_graph_stream
+        #initialize_graph_from_journal_stream
 
         }
+
+        #plain_module_stream
+    };
+    final_output.to_string()
+}
+
+/// Generates only the non-reactive API of the schema, see [`GenerationTarget::NonReactive`]
+pub fn generate_concrete_schema_non_reactive(
+    raw_json_schema: String,
+    raw_json_initial_population: Option<String>,
+) -> String {
+    let constraint_schema: ConstraintSchema<PrimitiveTypes, PrimitiveValues> =
+        serde_json::from_str(&raw_json_schema).expect("Schema formatted incorrectly");
+    let plain_module_stream = generate_plain_streams::generate_plain_module(
+        &constraint_schema,
+        quote! {
+            lazy_static::lazy_static!{
+                pub static ref CONSTRAINT_SCHEMA: base_types::constraint_schema::ConstraintSchema<PrimitiveTypes, PrimitiveValues>
+                = serde_json::from_str::<base_types::constraint_schema::ConstraintSchema<PrimitiveTypes, PrimitiveValues>>(#raw_json_schema).expect("Schema formatted incorrectly");
+            }
+        },
+        raw_json_initial_population,
+    );
+    let final_output = quote! {
+        #plain_module_stream
+
+        pub mod prelude {
+            pub use super::plain::*;
+        }
     };
     final_output.to_string()
 }
@@ -790,6 +891,23 @@ pub fn generate_crate(
     schema_location: &str,
     molecule_schema_workspace_location: &str,
     initial_population_location: Option<&str>,
+) {
+    generate_crate_for_target(
+        schema_location,
+        molecule_schema_workspace_location,
+        initial_population_location,
+        GenerationTarget::Reactive,
+    )
+}
+
+/// Same as [`generate_crate`], but lets the caller choose which API is generated.
+///
+/// example usage: generate_crate_for_target("resources/my_schema.json", "../molecule_schema", None, GenerationTarget::NonReactive);
+pub fn generate_crate_for_target(
+    schema_location: &str,
+    molecule_schema_workspace_location: &str,
+    initial_population_location: Option<&str>,
+    target: GenerationTarget,
 ) {
     use std::{env, fs, path::Path, process::Command};
 
@@ -813,7 +931,14 @@ pub fn generate_crate(
         None
     };
 
-    let generated_code = generate_concrete_schema_reactive(raw_json_schema, initial_population);
+    let generated_code = match target {
+        GenerationTarget::Reactive => {
+            generate_concrete_schema_reactive(raw_json_schema, initial_population)
+        }
+        GenerationTarget::NonReactive => {
+            generate_concrete_schema_non_reactive(raw_json_schema, initial_population)
+        }
+    };
 
     // Ensure directory exists
     fs::create_dir_all(&generated_crate_dir).unwrap();
@@ -824,23 +949,13 @@ pub fn generate_crate(
     fs::remove_file(&generated_cargo_toml_dir);
 
     // Write out the crate's Cargo.toml and lib.rs
-    fs::write(
-            generated_cargo_toml_dir,
-            format!(
-                r#"
-                    [package]
-                    name = "generated_crate"
-                    version = "0.1.0"
-                    edition = "2021"
-
-                    [lib]
-                    path = "src/lib.rs"
-
-                    [dependencies]
-                    to_composite_id_macro = {{ path = "{}/to_composite_id_macro" }}
-                    molecule_core = {{ path = "{}/molecule_core" }}
-                    base_types = {{ path = "{}/base_types", features = ["serde"] }}
-                    reactive_types = {{ path = "{}/reactive_types/" }}
+    let dependencies = match target {
+        GenerationTarget::Reactive => format!(
+            r#"
+                    to_composite_id_macro = {{ path = "{0}/to_composite_id_macro" }}
+                    molecule_core = {{ path = "{0}/molecule_core" }}
+                    base_types = {{ path = "{0}/base_types", features = ["serde", "reactive"] }}
+                    reactive_types = {{ path = "{0}/reactive_types/" }}
                     lazy_static = "1.4"
                     strum = {{version = "0.26.1", features=["derive"]}}
                     strum_macros = "0.26.1"
@@ -857,10 +972,36 @@ pub fn generate_crate(
                         "js",
                     ]
                 "#,
-                molecule_schema_workspace_location, molecule_schema_workspace_location, molecule_schema_workspace_location, molecule_schema_workspace_location ,
-            )
-        )
-        .unwrap();
+            molecule_schema_workspace_location
+        ),
+        GenerationTarget::NonReactive => format!(
+            r#"
+                    base_types = {{ path = "{0}/base_types", default-features = false, features = ["serde"] }}
+                    lazy_static = "1.4"
+                    serde_json = "1"
+                "#,
+            molecule_schema_workspace_location
+        ),
+    };
+    fs::write(
+        generated_cargo_toml_dir,
+        format!(
+            r#"
+                    [package]
+                    name = "generated_crate"
+                    version = "0.1.0"
+                    edition = "2021"
+
+                    [lib]
+                    path = "src/lib.rs"
+
+                    [dependencies]
+                    {}
+                "#,
+            dependencies.trim()
+        ),
+    )
+    .unwrap();
 
     fs::write(generated_code_dir.clone(), generated_code).unwrap();
 
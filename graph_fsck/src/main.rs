@@ -0,0 +1,110 @@
+//! Audits a saved snapshot against its schema, see [`base_types::post_generation::integrity`].
+//!
+//! ```text
+//! graph_fsck <schema.json> <snapshot.json> [--repair <output.json>] [--uuid]
+//! ```
+//!
+//! Every problem is printed on its own line. With `--repair`, dangling edges are dropped and
+//! missing mirrors added, and the repaired snapshot is written to the output path (with UUID
+//! string ids if `--uuid` is given). Exits with 1 if problems remain, and 2 if the files can't
+//! be read or written.
+use std::process::ExitCode;
+
+use base_types::{
+    constraint_schema::ConstraintSchema,
+    post_generation::{
+        integrity::check_instances,
+        journal::{instances_from_json, write_snapshot_as},
+        IdFormat,
+    },
+    primitives::{PrimitiveTypes, PrimitiveValues},
+};
+
+const USAGE: &str =
+    "usage: graph_fsck <schema.json> <snapshot.json> [--repair <output.json>] [--uuid]";
+
+struct Args {
+    schema_path: String,
+    snapshot_path: String,
+    repair_path: Option<String>,
+    id_format: IdFormat,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut paths = Vec::new();
+    let mut repair_path = None;
+    let mut id_format = IdFormat::Numeric;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--repair" => {
+                repair_path = Some(args.next().ok_or("--repair needs an output path")?);
+            }
+            "--uuid" => id_format = IdFormat::Uuid,
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            _ => paths.push(arg),
+        }
+    }
+    let [schema_path, snapshot_path] = <[String; 2]>::try_from(paths)
+        .map_err(|_| "expected a schema and a snapshot path".to_string())?;
+    Ok(Args {
+        schema_path,
+        snapshot_path,
+        repair_path,
+        id_format,
+    })
+}
+
+fn run(args: Args) -> Result<bool, String> {
+    let schema_json = std::fs::read_to_string(&args.schema_path)
+        .map_err(|error| format!("{}: {}", args.schema_path, error))?;
+    let constraint_schema: ConstraintSchema<PrimitiveTypes, PrimitiveValues> =
+        serde_json::from_str(&schema_json)
+            .map_err(|error| format!("{}: {}", args.schema_path, error))?;
+    // The checks take the schema for the rest of the program, as the generated crates do
+    let constraint_schema = Box::leak(Box::new(constraint_schema));
+    let snapshot_json = std::fs::read_to_string(&args.snapshot_path)
+        .map_err(|error| format!("{}: {}", args.snapshot_path, error))?;
+    let instances = instances_from_json(&snapshot_json)
+        .map_err(|error| format!("{}: {}", args.snapshot_path, error))?;
+    let instance_count = instances.len();
+
+    let (instances, report) =
+        check_instances(constraint_schema, instances, args.repair_path.is_some());
+    report
+        .repaired
+        .iter()
+        .for_each(|problem| println!("repaired: {}", problem));
+    report
+        .problems
+        .iter()
+        .for_each(|problem| println!("{}", problem));
+    if let Some(repair_path) = &args.repair_path {
+        write_snapshot_as(repair_path, &instances, args.id_format)
+            .map_err(|error| format!("{}: {}", repair_path, error))?;
+    }
+    println!(
+        "{} instances, {} problems, {} repaired",
+        instance_count,
+        report.problems.len(),
+        report.repaired.len()
+    );
+    Ok(report.is_valid())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Runs `graph_fsck` against the snapshots in `tests/fixtures`, which are checked against the
+//! schema of [`base_types::testing`].
+//!
+//! `broken_edge.json` holds two nodes where `1` links to `2` but `2` doesn't list the edge, which
+//! repairing fixes. `dangling_partner.json` holds a pair whose partner doesn't exist, so dropping
+//! the edge leaves the pair without a partner and the snapshot can't be repaired.
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use base_types::{
+    post_generation::journal::instances_from_json,
+    testing::{LINKS_SLOT_ID, PARTNER_SLOT_ID, TEST_SCHEMA},
+};
+
+// A directory of its own for each test, holding the schema and whatever the test writes
+fn work_dir(test: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(test);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("schema.json"),
+        serde_json::to_string(&*TEST_SCHEMA).unwrap(),
+    )
+    .unwrap();
+    dir
+}
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn fsck(dir: &Path, snapshot: &Path, options: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_graph_fsck"))
+        .arg(dir.join("schema.json"))
+        .arg(snapshot)
+        .args(options)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn problems_are_reported_with_exit_code_1() {
+    let dir = work_dir("problems_are_reported_with_exit_code_1");
+    let output = fsck(&dir, &fixture("broken_edge.json"), &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "1: edge 1 -[{}]-> 2 is not listed by its other end\n\
+             2 instances, 1 problems, 0 repaired\n",
+            LINKS_SLOT_ID
+        )
+    );
+}
+
+#[test]
+fn repaired_snapshots_are_written_and_pass_the_check() {
+    let dir = work_dir("repaired_snapshots_are_written_and_pass_the_check");
+    let repaired = dir.join("repaired.json");
+    let output = fsck(
+        &dir,
+        &fixture("broken_edge.json"),
+        &["--repair", repaired.to_str().unwrap()],
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "repaired: 1: edge 1 -[{}]-> 2 is not listed by its other end\n\
+             2 instances, 0 problems, 1 repaired\n",
+            LINKS_SLOT_ID
+        )
+    );
+
+    let instances = instances_from_json(&std::fs::read_to_string(&repaired).unwrap()).unwrap();
+    let target = instances.iter().find(|instance| instance.id == 2).unwrap();
+    assert_eq!(target.incoming_slots.len(), 1);
+    let output = fsck(&dir, &repaired, &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "2 instances, 0 problems, 0 repaired\n");
+}
+
+#[test]
+fn repaired_snapshots_can_be_written_with_uuids() {
+    let dir = work_dir("repaired_snapshots_can_be_written_with_uuids");
+    let repaired = dir.join("repaired.json");
+    let output = fsck(
+        &dir,
+        &fixture("broken_edge.json"),
+        &["--repair", repaired.to_str().unwrap(), "--uuid"],
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let json = std::fs::read_to_string(&repaired).unwrap();
+    assert!(json.contains("\"00000000-0000-0000-0000-000000000002\""));
+    assert_eq!(fsck(&dir, &repaired, &[]).status.code(), Some(0));
+}
+
+#[test]
+fn problems_left_after_repairing_exit_with_1() {
+    let dir = work_dir("problems_left_after_repairing_exit_with_1");
+    let repaired = dir.join("repaired.json");
+    let output = fsck(
+        &dir,
+        &fixture("dangling_partner.json"),
+        &["--repair", repaired.to_str().unwrap()],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = stdout(&output);
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3, "{}", stdout);
+    assert_eq!(
+        lines[0],
+        format!(
+            "repaired: 3: edge 3 -[{}]-> 9 refers to missing instance 9",
+            PARTNER_SLOT_ID
+        )
+    );
+    assert!(lines[1].contains("partner"), "{}", lines[1]);
+    assert_eq!(lines[2], "1 instances, 1 problems, 1 repaired");
+    // The repaired snapshot is still written, so that it can be fixed by hand
+    assert!(repaired.exists());
+}
+
+#[test]
+fn unreadable_input_exits_with_2() {
+    let dir = work_dir("unreadable_input_exits_with_2");
+    let missing = dir.join("missing.json");
+    let output = fsck(&dir, &missing, &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with(missing.to_str().unwrap()));
+
+    let output = fsck(&dir, &fixture("broken_edge.json"), &["--repair"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("usage: graph_fsck"));
+}
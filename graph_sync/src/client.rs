@@ -0,0 +1,146 @@
+use std::{
+    io::BufReader,
+    net::{Shutdown, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError},
+    time::Duration,
+};
+
+use base_types::{
+    common::Uid,
+    post_generation::{
+        merge::MergeReport,
+        reactive::{from_reactive::FromStandalone, hidden::EditRGSO, SharedGraph},
+        StandaloneRGSOConcrete,
+    },
+};
+
+use crate::protocol::{read_message, write_message, ClientMessage, ServerMessage, SyncError};
+
+#[derive(Debug, Clone)]
+pub enum SyncEvent {
+    /// Blueprints committed by other clients have been merged into the local graph
+    Merged(MergeReport),
+    /// The server has merged the blueprints last pushed by this client
+    Acknowledged { bound_violations: Vec<String> },
+    /// The server couldn't merge the blueprints last pushed by this client. They remain applied
+    /// to the local graph, which has diverged from the server's until they are undone
+    Rejected { error: String },
+}
+
+/// Keeps a local graph in sync with the graph hosted by a [`crate::SyncServer`]
+///
+/// Blueprints committed on the local graph are sent with [`Self::push`], and those committed by
+/// other clients are merged in by [`Self::poll`]. Merged blueprints are announced to the graph's
+/// subscribers, but don't enter its undo history.
+pub struct SyncClient<TSchema: 'static> {
+    graph: SharedGraph<TSchema>,
+    writer: TcpStream,
+    messages: Receiver<Result<ServerMessage, SyncError>>,
+}
+
+impl<TSchema> SyncClient<TSchema>
+where
+    TSchema: EditRGSO<Schema = TSchema>
+        + FromStandalone<Schema = TSchema>
+        + Into<StandaloneRGSOConcrete>
+        + Send
+        + Sync
+        + 'static,
+{
+    /// Attaches the graph to the server as a replica with the given id, which must be unique among
+    /// the clients and differ from [`crate::SERVER_REPLICA_ID`]. The graph must be in the same
+    /// initial state as the server's, everything the server has merged so far is merged into it
+    /// before this returns
+    pub fn connect(
+        addr: impl ToSocketAddrs,
+        graph: SharedGraph<TSchema>,
+        replica_id: Uid,
+    ) -> Result<Self, SyncError> {
+        let writer = TcpStream::connect(addr)?;
+        let mut reader = BufReader::new(writer.try_clone()?);
+        let history = match read_message(&mut reader)? {
+            Some(ServerMessage::Welcome { history }) => history,
+            Some(other) => return Err(SyncError::Protocol(format!("{:?}", other))),
+            None => return Err(SyncError::Disconnected),
+        };
+        graph.attach_replica(replica_id);
+        graph.merge(history).map_err(SyncError::Merge)?;
+
+        let (sender, messages) = mpsc::channel();
+        std::thread::spawn(move || loop {
+            let message = match read_message(&mut reader) {
+                Ok(Some(message)) => Ok(message),
+                Ok(None) => Err(SyncError::Disconnected),
+                Err(error) => Err(error),
+            };
+            let failed = message.is_err();
+            if sender.send(message).is_err() || failed {
+                return;
+            }
+        });
+        Ok(Self {
+            graph,
+            writer,
+            messages,
+        })
+    }
+    pub fn graph(&self) -> &SharedGraph<TSchema> {
+        &self.graph
+    }
+    /// Sends the blueprints committed on the local graph since the last push, and returns how many
+    /// were sent
+    pub fn push(&mut self) -> Result<usize, SyncError> {
+        let blueprints = self.graph.take_replicated();
+        if blueprints.is_empty() {
+            return Ok(0);
+        }
+        let count = blueprints.len();
+        write_message(&mut self.writer, &ClientMessage::Commit { blueprints })?;
+        Ok(count)
+    }
+    /// Handles every message received from the server so far, without waiting for more
+    pub fn poll(&mut self) -> Result<Vec<SyncEvent>, SyncError> {
+        let mut events = Vec::new();
+        loop {
+            match self.messages.try_recv() {
+                Ok(message) => events.push(self.handle(message?)?),
+                Err(TryRecvError::Empty) => return Ok(events),
+                Err(TryRecvError::Disconnected) => return Err(SyncError::Disconnected),
+            }
+        }
+    }
+    /// Same as [`Self::poll`], but first waits up to `timeout` for a message to arrive
+    pub fn poll_timeout(&mut self, timeout: Duration) -> Result<Vec<SyncEvent>, SyncError> {
+        let first = match self.messages.recv_timeout(timeout) {
+            Ok(message) => self.handle(message?)?,
+            Err(RecvTimeoutError::Timeout) => return Ok(Vec::new()),
+            Err(RecvTimeoutError::Disconnected) => return Err(SyncError::Disconnected),
+        };
+        let mut events = vec![first];
+        events.extend(self.poll()?);
+        Ok(events)
+    }
+
+    fn handle(&self, message: ServerMessage) -> Result<SyncEvent, SyncError> {
+        match message {
+            ServerMessage::Relayed { blueprints } => self
+                .graph
+                .merge(blueprints)
+                .map(SyncEvent::Merged)
+                .map_err(SyncError::Merge),
+            ServerMessage::Merged { bound_violations } => {
+                Ok(SyncEvent::Acknowledged { bound_violations })
+            }
+            ServerMessage::Rejected { error } => Ok(SyncEvent::Rejected { error }),
+            ServerMessage::Welcome { .. } => {
+                Err(SyncError::Protocol("Welcome after connecting".to_string()))
+            }
+        }
+    }
+}
+
+impl<TSchema> Drop for SyncClient<TSchema> {
+    fn drop(&mut self) {
+        let _ = self.writer.shutdown(Shutdown::Both);
+    }
+}
@@ -0,0 +1,13 @@
+//! Keeps the graph environments of several clients in sync through a server.
+//!
+//! The server hosts a replica of the graph. Clients push the blueprints committed on their own
+//! replica to it, and the server merges them and relays them to every other client, see
+//! [`base_types::post_generation::merge`]. Messages are exchanged as line-delimited JSON over
+//! TCP, see [`protocol`].
+mod client;
+pub mod protocol;
+mod server;
+
+pub use client::{SyncClient, SyncEvent};
+pub use protocol::SyncError;
+pub use server::{SyncServer, SERVER_REPLICA_ID};
@@ -0,0 +1,90 @@
+use std::{
+    fmt::Display,
+    io::{BufRead, Write},
+};
+
+use base_types::post_generation::{merge::ReplicatedBlueprint, ElementCreationError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Sent from a client to the server, one JSON object per line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// Blueprints committed on the client, in the order they were committed
+    Commit {
+        blueprints: Vec<ReplicatedBlueprint>,
+    },
+}
+
+/// Sent from the server to a client, one JSON object per line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ServerMessage {
+    /// Every blueprint the server has merged so far, sent once when the client connects
+    Welcome { history: Vec<ReplicatedBlueprint> },
+    /// Blueprints committed by another client
+    Relayed {
+        blueprints: Vec<ReplicatedBlueprint>,
+    },
+    /// The client's last commit has been merged and relayed to the other clients. Lists the slots
+    /// which the merge has left outside of their bounds
+    Merged { bound_violations: Vec<String> },
+    /// The client's last commit couldn't be merged and hasn't been relayed
+    Rejected { error: String },
+}
+
+#[derive(Debug)]
+pub enum SyncError {
+    Io(std::io::Error),
+    Protocol(String),
+    Merge(ElementCreationError),
+    Disconnected,
+}
+
+impl Display for SyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncError::Io(error) => write!(f, "Connection failed: {}", error),
+            SyncError::Protocol(message) => write!(f, "Unexpected message: {}", message),
+            SyncError::Merge(error) => {
+                write!(f, "Remote blueprints could not be merged:\n{}", error)
+            }
+            SyncError::Disconnected => write!(f, "The connection has been closed"),
+        }
+    }
+}
+
+impl From<std::io::Error> for SyncError {
+    fn from(value: std::io::Error) -> Self {
+        SyncError::Io(value)
+    }
+}
+
+pub(crate) fn write_message(
+    writer: &mut impl Write,
+    message: &impl Serialize,
+) -> Result<(), SyncError> {
+    let mut line =
+        serde_json::to_string(message).map_err(|error| SyncError::Protocol(error.to_string()))?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the next message, `None` once the other side has closed the connection
+pub(crate) fn read_message<T: DeserializeOwned>(
+    reader: &mut impl BufRead,
+) -> Result<Option<T>, SyncError> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim().is_empty() {
+            break;
+        }
+    }
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|error| SyncError::Protocol(error.to_string()))
+}
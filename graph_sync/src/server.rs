@@ -0,0 +1,254 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    io::BufReader,
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use base_types::{
+    common::Uid,
+    post_generation::{
+        merge::ReplicatedBlueprint,
+        reactive::{from_reactive::FromStandalone, hidden::EditRGSO, SharedGraph},
+        StandaloneRGSOConcrete,
+    },
+};
+
+use crate::protocol::{read_message, write_message, ClientMessage, ServerMessage};
+
+/// The replica id of the graph hosted by the server, clients must use any other id
+pub const SERVER_REPLICA_ID: Uid = 0;
+
+/// Runs on the hub thread with the hosted `SharedGraph`
+type Inspection = Box<dyn FnOnce(&dyn Any) + Send>;
+
+enum HubMessage {
+    Connected {
+        client_id: u64,
+        stream: TcpStream,
+    },
+    Received {
+        client_id: u64,
+        message: ClientMessage,
+    },
+    Disconnected {
+        client_id: u64,
+    },
+    Inspect(Inspection),
+    Shutdown,
+}
+
+/// Hosts a graph environment which clients on other threads or processes keep in sync with, see
+/// [`crate::SyncClient`]
+///
+/// Every commit a client sends is merged into the hosted graph and relayed to the other clients.
+/// Clients which connect later first receive everything merged before them.
+pub struct SyncServer {
+    local_addr: SocketAddr,
+    shutting_down: Arc<AtomicBool>,
+    hub: Sender<HubMessage>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl SyncServer {
+    /// Starts serving on the given address. `make_graph` is called on the server's own thread, so
+    /// that the hosted graph never leaves it, and must return a graph in the same initial state as
+    /// those of the clients
+    pub fn bind<TSchema>(
+        addr: impl ToSocketAddrs,
+        make_graph: impl FnOnce() -> SharedGraph<TSchema> + Send + 'static,
+    ) -> std::io::Result<Self>
+    where
+        TSchema: EditRGSO<Schema = TSchema>
+            + FromStandalone<Schema = TSchema>
+            + Into<StandaloneRGSOConcrete>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let shutting_down = Arc::new(AtomicBool::new(false));
+        let (hub, hub_receiver) = mpsc::channel();
+
+        let hub_thread = std::thread::spawn(move || run_hub(make_graph(), hub_receiver));
+        let accept_thread = {
+            let hub = hub.clone();
+            let shutting_down = shutting_down.clone();
+            std::thread::spawn(move || accept_clients(listener, hub, shutting_down))
+        };
+        Ok(Self {
+            local_addr,
+            shutting_down,
+            hub,
+            threads: vec![hub_thread, accept_thread],
+        })
+    }
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+    /// Runs `inspect` on the server's thread with the hosted graph, after every message received
+    /// so far has been handled. `None` if the server has shut down or hosts a graph of another
+    /// schema
+    pub fn inspect<TSchema: 'static, R: Send + 'static>(
+        &self,
+        inspect: impl FnOnce(&SharedGraph<TSchema>) -> R + Send + 'static,
+    ) -> Option<R> {
+        let (sender, receiver) = mpsc::channel();
+        let inspect = move |graph: &dyn Any| {
+            let result = graph.downcast_ref::<SharedGraph<TSchema>>().map(inspect);
+            let _ = sender.send(result);
+        };
+        self.hub.send(HubMessage::Inspect(Box::new(inspect))).ok()?;
+        receiver.recv().ok().flatten()
+    }
+    /// Disconnects every client and waits for the server's threads to finish
+    pub fn shutdown(self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let _ = self.hub.send(HubMessage::Shutdown);
+        // Wakes up the listener so that it notices the shutdown
+        let _ = TcpStream::connect(self.local_addr);
+        self.threads.into_iter().for_each(|thread| {
+            let _ = thread.join();
+        });
+    }
+}
+
+fn accept_clients(listener: TcpListener, hub: Sender<HubMessage>, shutting_down: Arc<AtomicBool>) {
+    let mut next_client_id = 0;
+    for stream in listener.incoming() {
+        if shutting_down.load(Ordering::SeqCst) {
+            break;
+        }
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let Ok(reader) = stream.try_clone() else {
+            continue;
+        };
+        let client_id = next_client_id;
+        next_client_id += 1;
+        if hub
+            .send(HubMessage::Connected { client_id, stream })
+            .is_err()
+        {
+            break;
+        }
+        let hub = hub.clone();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            // A malformed message ends the connection just like a closed one
+            while let Ok(Some(message)) = read_message::<ClientMessage>(&mut reader) {
+                if hub
+                    .send(HubMessage::Received { client_id, message })
+                    .is_err()
+                {
+                    return;
+                }
+            }
+            let _ = hub.send(HubMessage::Disconnected { client_id });
+        });
+    }
+}
+
+/// Writes the messages for one client on its own thread, so that a client which doesn't read
+/// them can't hold up the others. Closes the connection once a write fails or the hub drops the
+/// sender, which in turn ends the client's reading thread
+fn spawn_writer(mut stream: TcpStream) -> Sender<Arc<ServerMessage>> {
+    let (sender, messages) = mpsc::channel::<Arc<ServerMessage>>();
+    std::thread::spawn(move || {
+        while let Ok(message) = messages.recv() {
+            if write_message(&mut stream, &*message).is_err() {
+                break;
+            }
+        }
+        let _ = stream.shutdown(Shutdown::Both);
+    });
+    sender
+}
+
+fn run_hub<TSchema>(graph: SharedGraph<TSchema>, messages: Receiver<HubMessage>)
+where
+    TSchema: EditRGSO<Schema = TSchema>
+        + FromStandalone<Schema = TSchema>
+        + Into<StandaloneRGSOConcrete>
+        + Send
+        + Sync
+        + 'static,
+{
+    graph.attach_replica(SERVER_REPLICA_ID);
+    let mut history: Vec<ReplicatedBlueprint> = Vec::new();
+    let mut clients: HashMap<u64, Sender<Arc<ServerMessage>>> = HashMap::new();
+    while let Ok(message) = messages.recv() {
+        match message {
+            HubMessage::Connected { client_id, stream } => {
+                let writer = spawn_writer(stream);
+                let welcome = ServerMessage::Welcome {
+                    history: history.clone(),
+                };
+                if writer.send(Arc::new(welcome)).is_ok() {
+                    clients.insert(client_id, writer);
+                }
+            }
+            HubMessage::Received {
+                client_id,
+                message: ClientMessage::Commit { blueprints },
+            } => {
+                // Merged one at a time so that the blueprints before a failing one are still
+                // relayed, the ones after it depend on it and are dropped
+                let mut merged = Vec::new();
+                let mut bound_violations = Vec::new();
+                let mut error = None;
+                for blueprint in blueprints {
+                    match graph.merge([blueprint.clone()]) {
+                        Ok(report) => {
+                            report.bound_violations.iter().for_each(|violation| {
+                                let violation = violation.to_string();
+                                if !bound_violations.contains(&violation) {
+                                    bound_violations.push(violation);
+                                }
+                            });
+                            merged.push(blueprint);
+                        }
+                        Err(merge_error) => {
+                            error = Some(merge_error);
+                            break;
+                        }
+                    }
+                }
+                if !merged.is_empty() {
+                    history.extend(merged.iter().cloned());
+                    let relayed = Arc::new(ServerMessage::Relayed { blueprints: merged });
+                    clients
+                        .iter()
+                        .filter(|(id, _)| **id != client_id)
+                        .for_each(|(_, writer)| {
+                            let _ = writer.send(relayed.clone());
+                        });
+                }
+                let reply = match error {
+                    Some(error) => ServerMessage::Rejected {
+                        error: error.to_string(),
+                    },
+                    None => ServerMessage::Merged { bound_violations },
+                };
+                if let Some(writer) = clients.get(&client_id) {
+                    let _ = writer.send(Arc::new(reply));
+                }
+            }
+            HubMessage::Disconnected { client_id } => {
+                clients.remove(&client_id);
+            }
+            HubMessage::Inspect(inspect) => inspect(&graph),
+            HubMessage::Shutdown => break,
+        }
+    }
+    // Dropping the writers closes every connection once its pending messages are written
+    drop(clients);
+}
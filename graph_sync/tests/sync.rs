@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use base_types::testing::{self, TestSchema};
+use graph_sync::{SyncClient, SyncEvent, SyncServer};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn pushed_commits_reach_the_server_and_other_clients() {
+    let server = SyncServer::bind("127.0.0.1:0", testing::initialize_graph).unwrap();
+    let mut first =
+        SyncClient::connect(server.local_addr(), testing::initialize_graph(), 1).unwrap();
+    let mut second =
+        SyncClient::connect(server.local_addr(), testing::initialize_graph(), 2).unwrap();
+
+    let host = testing::create_node(first.graph(), "host").unwrap();
+    let target = testing::create_node(first.graph(), "target").unwrap();
+    testing::link(first.graph(), &host, &target).unwrap();
+    assert_eq!(first.push().unwrap(), 3);
+
+    let events = second.poll_timeout(TIMEOUT).unwrap();
+    assert!(matches!(events.as_slice(), [SyncEvent::Merged(_)]));
+    let events = first.poll_timeout(TIMEOUT).unwrap();
+    assert!(matches!(
+        events.as_slice(),
+        [SyncEvent::Acknowledged { bound_violations }] if bound_violations.is_empty()
+    ));
+
+    let expected = testing::normalized(first.graph());
+    assert_eq!(
+        testing::name(second.graph(), &target).as_deref(),
+        Some("target")
+    );
+    assert_eq!(testing::normalized(second.graph()), expected);
+    assert_eq!(
+        server.inspect::<TestSchema, _>(testing::normalized),
+        Some(expected)
+    );
+    server.shutdown();
+}
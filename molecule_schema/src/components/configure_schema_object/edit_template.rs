@@ -2,6 +2,7 @@ use std::{collections::HashMap, sync::Arc};
 
 use base_types::{
     common::Uid,
+    constraint_schema::FieldIndex,
     primitives::{PrimitiveTypes, PrimitiveValues},
 };
 use leptos::web_sys::MouseEvent;
@@ -45,6 +46,7 @@ pub fn EditTemplate(element: TreeRef) -> impl IntoView {
         let new_field = RFieldConstraint::<PrimitiveTypes> {
             tag: RTag::new("New Field".to_string()),
             value_type: RwSignal::new(PrimitiveTypes::String),
+            index: RwSignal::new(FieldIndex::default()),
         };
         active_object.get().field_constraints.update(|prev| {
             prev.insert(new_field.tag.id.get(), new_field);
@@ -422,6 +424,7 @@ pub fn EditTemplate(element: TreeRef) -> impl IntoView {
                                     on_select=change_field_type
                                     value=item.value_type
                                 />
+                                <SelectInputEnum value=item.index/>
                                 <button on:click=get_on_click_delete_field(
                                     item.tag.id.get(),
                                 )>Delete Field</button>
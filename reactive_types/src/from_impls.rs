@@ -304,6 +304,7 @@ impl<TTypes: ConstraintTraits> From<FieldConstraint<TTypes>> for RFieldConstrain
         Self {
             tag: value.tag.into(),
             value_type: RwSignal::new(value.value_type),
+            index: RwSignal::new(value.index),
         }
     }
 }
@@ -312,6 +313,7 @@ impl<TTypes: ConstraintTraits> From<RFieldConstraint<TTypes>> for FieldConstrain
         Self {
             tag: value.tag.into(),
             value_type: value.value_type.get(),
+            index: value.index.get(),
         }
     }
 }
@@ -1,4 +1,5 @@
 use base_types::common::{ConstraintTraits, Uid};
+use base_types::constraint_schema::FieldIndex;
 use std::{collections::HashMap, marker::PhantomData};
 use strum_macros::{Display, EnumIter, EnumString};
 
@@ -135,6 +136,7 @@ pub enum ROperativeVariants {
 pub struct RFieldConstraint<TTypes: ConstraintTraits> {
     pub tag: RTag,
     pub value_type: RwSignal<TTypes>,
+    pub index: RwSignal<FieldIndex>,
 }
 impl<TTypes: ConstraintTraits, TValues: ConstraintTraits> FieldInfo<TTypes, TValues>
     for RFieldConstraint<TTypes>
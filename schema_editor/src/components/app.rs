@@ -20,11 +20,15 @@ pub fn App() -> impl IntoView {
 
     let ctx_for_undo = shared_graph.clone();
     let undo_graph_action = move |_| {
-        ctx_for_undo.undo();
+        if let Err(error) = ctx_for_undo.undo() {
+            leptos::logging::log!("{:#?}", error);
+        }
     };
     let ctx_for_redo = shared_graph.clone();
     let redo_graph_action = move |_| {
-        ctx_for_redo.redo();
+        if let Err(error) = ctx_for_redo.redo() {
+            leptos::logging::log!("{:#?}", error);
+        }
     };
 
     let serialize_graph = move |_| {